@@ -1,16 +1,33 @@
-use axum::{response::Redirect, Router};
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    http::{header, HeaderValue},
+    middleware,
+    response::Redirect,
+    Router,
+};
+use tower_http::set_header::SetResponseHeaderLayer;
 use ulid::Ulid;
 use utoipa::OpenApi;
 use utoipa_rapidoc::RapiDoc;
 
 use crate::{
-    common::{Config, DatabaseDriver},
+    common::{Config, DatabaseDriver, RateLimitBackend},
     resource::v1::{
-        health::{HealthController, HealthRepository, HealthService},
+        calendar::{CalendarController, CalendarRepository, CalendarService},
+        events::{EventBus, EventPublisher, EventSubscriber, OutboxRelay, OutboxRepository},
+        health::{HealthController, HealthRepository, HealthService, Readiness},
         todos::{TodoController, TodoRepositoryImpl, TodoService},
+        views::{ViewController, ViewRepository, ViewService},
+        webhooks::{WebhookController, WebhookRepository, WebhookService, WebhookSubscriber},
         ApiDoc,
     },
-    util::{Clock, IdGenerator, Telemetry},
+    util::{
+        enforce_rate_limit, metrics_router, propagate_request_id, reject_while_open,
+        track_requests, with_http_layers, ApiKey, CircuitBreaker, Clock, IdGenerator,
+        InMemoryRateLimitStore, Metrics, RateLimiter, Shutdown, SurrealRateLimitStore, Telemetry,
+        DOCS_CONTENT_SECURITY_POLICY,
+    },
 };
 
 pub struct AppBuilder<C: Clock, G: IdGenerator<Ulid>> {
@@ -18,7 +35,14 @@ pub struct AppBuilder<C: Clock, G: IdGenerator<Ulid>> {
     database_driver: Option<DatabaseDriver>,
     clock: Option<C>,
     telemetry: Option<Telemetry>,
+    metrics: Option<Metrics>,
+    readiness: Option<Readiness>,
+    shutdown: Option<Shutdown>,
+    circuit_breaker: Option<CircuitBreaker>,
+    rate_limiter: Option<RateLimiter>,
     id_generator: Option<G>,
+    subscribers: Vec<Arc<dyn EventSubscriber>>,
+    outbox_relay_interval: Option<Duration>,
 }
 
 impl<C: Clock + Clone, G: IdGenerator<Ulid> + Clone> Default for AppBuilder<C, G> {
@@ -34,7 +58,14 @@ impl<C: Clock + Clone, G: IdGenerator<Ulid> + Clone> AppBuilder<C, G> {
             database_driver: None,
             clock: None,
             telemetry: None,
+            metrics: None,
+            readiness: None,
+            shutdown: None,
+            circuit_breaker: None,
+            rate_limiter: None,
             id_generator: None,
+            subscribers: vec![],
+            outbox_relay_interval: None,
         }
     }
 
@@ -62,12 +93,65 @@ impl<C: Clock + Clone, G: IdGenerator<Ulid> + Clone> AppBuilder<C, G> {
         self
     }
 
+    /// Shares the metrics with a server on the admin port. The app makes its
+    /// own when none are given.
+    pub fn metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+
+        self
+    }
+
+    /// Shares the readiness with whatever drains the app on shutdown.
+    pub fn readiness(mut self, readiness: Readiness) -> Self {
+        self.readiness = Some(readiness);
+
+        self
+    }
+
+    /// Stops the background tasks the app spawns, and tracks them so the
+    /// server can wait for them on shutdown.
+    pub fn shutdown(mut self, shutdown: Shutdown) -> Self {
+        self.shutdown = Some(shutdown);
+
+        self
+    }
+
+    /// Requests touching the database are answered with a 503 while it is
+    /// open. None are when no breaker is given.
+    pub fn circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+
+        self
+    }
+
+    /// Limits the API routes with this limiter rather than one built from
+    /// `config.rate_limit`, even when that one is disabled.
+    pub fn rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+
+        self
+    }
+
     pub fn id_generator(mut self, id_generator: G) -> Self {
         self.id_generator = Some(id_generator);
 
         self
     }
 
+    /// Registers a subscriber for todo events, after the built-in webhooks.
+    pub fn subscriber<S: EventSubscriber>(mut self, subscriber: S) -> Self {
+        self.subscribers.push(Arc::new(subscriber));
+
+        self
+    }
+
+    /// Spawns the outbox relay with this interval when the app is built.
+    pub fn outbox_relay_interval(mut self, interval: Duration) -> Self {
+        self.outbox_relay_interval = Some(interval);
+
+        self
+    }
+
     pub async fn build(self) -> Result<Router, String> {
         let Some(database_driver) = self.database_driver else {
             return Err(String::from("Database driver not set"));
@@ -77,7 +161,7 @@ impl<C: Clock + Clone, G: IdGenerator<Ulid> + Clone> AppBuilder<C, G> {
             return Err(String::from("Clock not set"));
         };
 
-        let Some(_config) = self.config else {
+        let Some(config) = self.config else {
             return Err(String::from("Config not set"));
         };
 
@@ -85,17 +169,101 @@ impl<C: Clock + Clone, G: IdGenerator<Ulid> + Clone> AppBuilder<C, G> {
             return Err(String::from("Id generator not set"));
         };
 
-        let health_repository = HealthRepository::new(database_driver.clone());
-        let todo_repository = TodoRepositoryImpl::new(database_driver.clone());
+        let metrics = self.metrics.unwrap_or_default();
+
+        let health_repository =
+            HealthRepository::new(database_driver.clone()).with_metrics(metrics.clone());
+        let todo_repository =
+            TodoRepositoryImpl::new(database_driver.clone()).with_metrics(metrics.clone());
+        let calendar_repository = CalendarRepository::new(database_driver.clone());
+        let view_repository = ViewRepository::new(database_driver.clone());
+        let webhook_repository = WebhookRepository::new(database_driver.clone());
+
+        let mut event_bus = EventBus::new();
+        event_bus.subscribe(Arc::new(WebhookSubscriber::new(webhook_repository.clone())));
+        for subscriber in self.subscribers {
+            event_bus.subscribe(subscriber);
+        }
+        let event_publisher =
+            EventPublisher::new(OutboxRepository::new(database_driver.clone()), event_bus);
+
+        if let Some(interval) = self.outbox_relay_interval {
+            let shutdown = self.shutdown.clone().unwrap_or_default();
+            let relay = OutboxRelay::new(
+                OutboxRepository::new(database_driver.clone()),
+                clock.clone(),
+                event_publisher.clone(),
+            )
+            .with_interval(interval)
+            .with_shutdown(shutdown.clone())
+            .spawn();
+
+            shutdown.track(relay);
+        }
 
-        let health_service = HealthService::new(health_repository);
-        let todo_service = TodoService::new(todo_repository, clock.clone(), id_generator.clone());
+        let health_service =
+            HealthService::new(health_repository, self.readiness.unwrap_or_default());
+        let todo_service = TodoService::new(
+            todo_repository,
+            clock.clone(),
+            id_generator.clone(),
+            config.timezone,
+        )
+        .with_events(event_publisher);
+        let calendar_service = CalendarService::new(
+            calendar_repository,
+            TodoService::new(
+                TodoRepositoryImpl::new(database_driver.clone()).with_metrics(metrics.clone()),
+                clock.clone(),
+                id_generator.clone(),
+                config.timezone,
+            ),
+            clock.clone(),
+        );
+        let view_service = ViewService::new(
+            view_repository,
+            TodoService::new(
+                TodoRepositoryImpl::new(database_driver.clone()).with_metrics(metrics.clone()),
+                clock.clone(),
+                id_generator.clone(),
+                config.timezone,
+            ),
+            clock.clone(),
+            id_generator.clone(),
+        );
+        let webhook_service =
+            WebhookService::new(webhook_repository, clock.clone(), id_generator.clone())
+                .with_private_urls(config.webhook_allow_private_urls);
+
+        let rate_limiter = self.rate_limiter.or_else(|| {
+            let rate_limit = &config.rate_limit;
+
+            rate_limit.enabled.then(|| match rate_limit.backend {
+                RateLimitBackend::Memory => {
+                    RateLimiter::new(InMemoryRateLimitStore::new(), clock.clone(), rate_limit)
+                }
+                RateLimitBackend::SurrealDb => RateLimiter::new(
+                    SurrealRateLimitStore::new(database_driver.clone()),
+                    clock.clone(),
+                    rate_limit,
+                ),
+            })
+        });
+        // Each controller is a route group with its own quotas.
+        let rate_limited = |group: &str, router: Router| match &rate_limiter {
+            Some(limiter) => router.route_layer(middleware::from_fn_with_state(
+                limiter.group(group),
+                enforce_rate_limit,
+            )),
+            None => router,
+        };
 
         let v1_prefix = "/v1";
 
         let health_controller = HealthController::new()
             .with_prefix(&format!("{}/health", &v1_prefix))
             .with_service(health_service)
+            .with_app(config.app.clone())
             .build();
 
         let todo_controller = TodoController::new()
@@ -103,24 +271,75 @@ impl<C: Clock + Clone, G: IdGenerator<Ulid> + Clone> AppBuilder<C, G> {
             .with_service(todo_service)
             .build();
 
+        let calendar_controller = CalendarController::new()
+            .with_prefix(&format!("{}/calendar", &v1_prefix))
+            .with_service(calendar_service)
+            .with_api_key(ApiKey::new(config.admin_api_key.as_deref()))
+            .build();
+
+        let view_controller = ViewController::new()
+            .with_prefix(&format!("{}/views", &v1_prefix))
+            .with_service(view_service)
+            .build();
+
+        let webhook_controller = WebhookController::new()
+            .with_prefix(&format!("{}/webhooks", &v1_prefix))
+            .with_service(webhook_service)
+            .with_api_key(ApiKey::new(config.admin_api_key.as_deref()))
+            .build();
+
+        // Health checks keep answering while the database is down, to say so.
+        let api = Router::new()
+            .merge(rate_limited("todos", todo_controller))
+            .merge(rate_limited("calendar", calendar_controller))
+            .merge(rate_limited("views", view_controller))
+            .merge(rate_limited("webhooks", webhook_controller))
+            .route_layer(middleware::from_fn_with_state(
+                self.circuit_breaker.unwrap_or_default(),
+                reject_while_open,
+            ));
+
+        let docs = Router::from(
+            RapiDoc::with_openapi(&format!("{}/docs.json", &v1_prefix), ApiDoc::openapi())
+                .path(&format!("{}/docs", &v1_prefix)),
+        );
+        let docs = if config.http.security_headers_enabled {
+            docs.layer(SetResponseHeaderLayer::overriding(
+                header::CONTENT_SECURITY_POLICY,
+                HeaderValue::from_static(DOCS_CONTENT_SECURITY_POLICY),
+            ))
+        } else {
+            docs
+        };
+
         let app = Router::new()
             .merge(health_controller)
-            .merge(todo_controller)
-            .merge(
-                RapiDoc::with_openapi(&format!("{}/docs.json", &v1_prefix), ApiDoc::openapi())
-                    .path(&format!("{}/docs", &v1_prefix)),
-            );
-
-        if let Some(telemetry) = self.telemetry {
-            let app = app
-                .layer(telemetry)
-                .fallback(Redirect::permanent("/v1/docs"));
-
-            Ok(app)
+            .merge(api)
+            .merge(docs)
+            .route_layer(middleware::from_fn_with_state(
+                metrics.clone(),
+                track_requests,
+            ));
+
+        let app = if config.metrics_port.is_some() {
+            app
         } else {
-            let app = app.fallback(Redirect::permanent("/v1/docs"));
+            app.merge(metrics_router(metrics))
+        };
 
-            Ok(app)
-        }
+        // Before the layers, which only wrap the fallback set by then.
+        let app = app.fallback(Redirect::permanent("/v1/docs"));
+
+        let app = with_http_layers(app, &config.http);
+
+        let app = match self.telemetry {
+            Some(telemetry) => app.layer(telemetry),
+            None => app,
+        };
+
+        // Outermost, so the request span already sees the request id.
+        let app = app.layer(middleware::from_fn(propagate_request_id));
+
+        Ok(app)
     }
 }
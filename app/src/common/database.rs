@@ -1,53 +1,217 @@
+use std::{
+    fmt::{self, Display},
+    time::Duration,
+};
+
 use surrealdb::{
     engine::remote::ws::{Client, Ws, Wss},
     opt::auth::Root,
     Surreal,
 };
-use tracing::info;
+use tokio::{
+    task::JoinHandle,
+    time::{interval, sleep, timeout, MissedTickBehavior},
+};
+use tracing::{info, warn};
 
-use crate::common::Environment;
+use crate::{
+    common::Environment,
+    util::{CircuitBreaker, Shutdown},
+};
 
 use super::Config;
 
+/// First wait between two connection attempts, doubled after every failure.
+static INITIAL_RETRY_DELAY: Duration = Duration::from_millis(500);
+static MAX_RETRY_DELAY: Duration = Duration::from_secs(10);
+
+pub static DEFAULT_HEALTH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A ping taking longer counts as a failure. While the websocket is down
+/// queries wait for it to come back rather than fail.
+static PING_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug)]
+pub enum DatabaseError {
+    Connection(String),
+    SignIn(String),
+    Namespace(String),
+}
+
+impl Display for DatabaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DatabaseError::Connection(err) => write!(f, "Unable to connect to DB: {}", err),
+            DatabaseError::SignIn(err) => write!(f, "Failed to authorize DB access: {}", err),
+            DatabaseError::Namespace(err) => write!(f, "Unable to config namespace: {}", err),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct DatabaseDriver {
     pub client: Surreal<Client>,
 }
 
 impl DatabaseDriver {
-    pub async fn init(config: &Config) -> Result<Self, ()> {
+    /// Connects and signs in, retrying with a growing delay up to
+    /// `DB_CONNECT_ATTEMPTS` times, so the app can start before the database.
+    pub async fn init(config: &Config) -> Result<Self, DatabaseError> {
+        let mut delay = INITIAL_RETRY_DELAY;
+        let mut attempt = 1;
+
+        loop {
+            match Self::connect(config).await {
+                Ok(driver) => return Ok(driver),
+                Err(err) if attempt < config.db.connect_attempts => {
+                    warn!(
+                        "{} (attempt {} of {}), retrying in {}ms",
+                        err,
+                        attempt,
+                        config.db.connect_attempts,
+                        delay.as_millis()
+                    );
+
+                    sleep(delay).await;
+                    delay = (delay * 2).min(MAX_RETRY_DELAY);
+                    attempt += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    async fn connect(config: &Config) -> Result<Self, DatabaseError> {
         let client = match config.env {
-            Environment::Production => Surreal::new::<Wss>(&config.db_url)
-                .await
-                .expect("Unable to connect to DB!"),
-            _ => Surreal::new::<Ws>(&config.db_url)
-                .await
-                .expect("Unable to connect to test DB!"),
-        };
+            Environment::Production => Surreal::new::<Wss>(&config.db.url).await,
+            _ => Surreal::new::<Ws>(&config.db.url).await,
+        }
+        .map_err(|err| DatabaseError::Connection(err.to_string()))?;
+
+        info!("Connected to the Database on {}", &config.db.url);
 
-        info!("Connected to the Database on {}", &config.db_url);
+        let driver = Self { client };
+        driver.sign_in(config).await?;
 
-        client
+        Ok(driver)
+    }
+
+    /// Signs in and selects the namespace and database, again after the
+    /// connection was lost and the session with it.
+    pub async fn sign_in(&self, config: &Config) -> Result<(), DatabaseError> {
+        self.client
             .signin(Root {
-                username: &config.db_username,
-                password: &config.db_password,
+                username: &config.db.username,
+                password: &config.db.password,
             })
             .await
-            .expect("Failed to authorize DB access!");
+            .map_err(|err| DatabaseError::SignIn(err.to_string()))?;
 
-        info!("Database access granted to {}", &config.db_username);
+        info!("Database access granted to {}", &config.db.username);
 
-        client
-            .use_ns(&config.db_namespace)
-            .use_db(&config.db_name)
+        self.client
+            .use_ns(&config.db.namespace)
+            .use_db(&config.db.name)
             .await
-            .expect("Unable to config namespace!");
+            .map_err(|err| DatabaseError::Namespace(err.to_string()))?;
 
         info!(
             "Using {} namespace and {} database",
-            &config.db_namespace, &config.db_name
+            &config.db.namespace, &config.db.name
         );
 
-        Ok(Self { client })
+        Ok(())
+    }
+
+    /// Signs out and drops this handle. The connection closes once every
+    /// other clone, held by the app and the background tasks, is dropped too.
+    pub async fn close(self) -> Result<(), surrealdb::Error> {
+        self.client.invalidate().await
+    }
+}
+
+/// Background task that pings the database and keeps the breaker in step,
+/// so requests fail fast while it is unreachable. The client reopens a
+/// dropped websocket on its own, once it answers again the session is
+/// restored before the breaker closes.
+pub struct DatabaseMonitor {
+    driver: DatabaseDriver,
+    config: Config,
+    breaker: CircuitBreaker,
+    interval: Duration,
+    shutdown: Shutdown,
+}
+
+impl DatabaseMonitor {
+    pub fn new(driver: DatabaseDriver, config: Config, breaker: CircuitBreaker) -> Self {
+        Self {
+            driver,
+            config,
+            breaker,
+            interval: DEFAULT_HEALTH_INTERVAL,
+            shutdown: Shutdown::new(),
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+
+        self
+    }
+
+    /// Stops the spawned task between ticks once `shutdown` is triggered.
+    pub fn with_shutdown(mut self, shutdown: Shutdown) -> Self {
+        self.shutdown = shutdown;
+
+        self
+    }
+
+    /// Pings the database once and records the outcome on the breaker.
+    pub async fn tick(&self) {
+        let reachable = matches!(
+            timeout(PING_TIMEOUT, self.driver.client.health()).await,
+            Ok(Ok(()))
+        );
+
+        if !reachable {
+            if self.breaker.record_failure() {
+                warn!("Database is unreachable, failing requests until it is back");
+            }
+
+            return;
+        }
+
+        if !self.breaker.is_open() {
+            self.breaker.record_success();
+
+            return;
+        }
+
+        match self.driver.sign_in(&self.config).await {
+            Ok(()) => {
+                self.breaker.record_success();
+                info!("Database is reachable again");
+            }
+            Err(err) => warn!("Database is reachable but the session is not: {}", err),
+        }
+    }
+
+    /// Runs [`Self::tick`] on the interval until shut down or aborted.
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticks = interval(self.interval);
+            ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                tokio::select! {
+                    _ = ticks.tick() => {}
+                    _ = self.shutdown.triggered() => break,
+                }
+
+                self.tick().await;
+            }
+
+            info!("Database monitor stopped");
+        })
     }
 }
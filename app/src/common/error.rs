@@ -1,28 +1,60 @@
-use std::vec;
+use std::{
+    fmt::{self, Display},
+    vec,
+};
 
 use axum::{
-    extract::rejection::{FormRejection, JsonRejection, PathRejection, QueryRejection},
-    http::StatusCode,
+    extract::{
+        multipart::{MultipartError, MultipartRejection},
+        rejection::{BytesRejection, FormRejection, JsonRejection, PathRejection, QueryRejection},
+    },
+    http::{header, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
 use serde::Serialize;
 use surrealdb::Error as SurrealDBError;
 use tracing::{error, warn};
+use ulid::Ulid;
 use utoipa::ToSchema;
 use validator::ValidationErrors;
 
-use crate::util::ParseError;
+use crate::util::{current_request_id, ParseError};
 
 #[derive(Serialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
 pub struct Problem<'a> {
     pub code: &'a str,
     pub issues: Vec<String>,
+    /// The `x-request-id` of the request that failed, to look it up in the logs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// Set on server errors, whose details are only logged, under this id
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_id: Option<String>,
 }
 
 impl<'a> Problem<'a> {
-    fn new(code: &'a str, issues: Vec<String>) -> Self {
-        Self { code, issues }
+    pub fn new(code: &'a str, issues: Vec<String>) -> Self {
+        Self {
+            code,
+            issues,
+            request_id: None,
+            error_id: None,
+        }
+    }
+
+    /// Tags the problem with the id of the request being handled.
+    pub fn for_current_request(mut self) -> Self {
+        self.request_id = current_request_id();
+
+        self
+    }
+
+    pub fn with_error_id(mut self, error_id: String) -> Self {
+        self.error_id = Some(error_id);
+
+        self
     }
 }
 
@@ -31,6 +63,13 @@ pub enum ApplicationError {
     ValidationError(Vec<String>),
     ServerError(Vec<String>),
     NotFound(String),
+    Unauthorized(String),
+    Unavailable(String),
+    /// Seconds until the client may try again
+    TooManyRequests(u64),
+    PayloadTooLarge(String),
+    /// Seconds the request was given
+    RequestTimeout(u64),
 }
 
 pub enum RepositoryError {
@@ -43,39 +82,104 @@ pub enum RepositoryError {
 impl IntoResponse for ApplicationError {
     fn into_response(self) -> Response {
         match self {
-            ApplicationError::ValidationError(issues) => {
-                return (
-                    StatusCode::BAD_REQUEST,
-                    Json(Problem::new("VALIDATION_ERROR", issues)),
-                )
-                    .into_response()
-            }
+            ApplicationError::ValidationError(issues) => (
+                StatusCode::BAD_REQUEST,
+                Json(Problem::new("VALIDATION_ERROR", issues).for_current_request()),
+            )
+                .into_response(),
             ApplicationError::ServerError(issues) => {
-                error!("{:?}", Problem::new("SERVER_ERROR", issues));
+                // The issues can hold queries or driver messages, so the caller
+                // only gets the id to quote while they stay in the logs.
+                let error_id = Ulid::new().to_string();
+                error!(error_id = %error_id, issues = ?issues, "Server error");
 
-                return (
+                (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(Problem::new(
-                        "SERVER_ERROR",
-                        vec![String::from("This is on us, we will take care of it.")],
-                    )),
+                    Json(
+                        Problem::new(
+                            "SERVER_ERROR",
+                            vec![String::from("This is on us, we will take care of it.")],
+                        )
+                        .for_current_request()
+                        .with_error_id(error_id),
+                    ),
                 )
-                    .into_response();
+                    .into_response()
             }
             ApplicationError::NotFound(resource_id) => {
                 let message = vec![format!("resource with id {} does not exist!", resource_id)];
-                let problem = Problem::new("RESOURCE_NOT_FOUND", message);
-                warn!("{:?}", problem);
+                let problem = Problem::new("RESOURCE_NOT_FOUND", message).for_current_request();
+                warn!(resource_id = %resource_id, "Resource not found");
 
                 (StatusCode::NOT_FOUND, Json(problem)).into_response()
             }
+            ApplicationError::Unauthorized(reason) => (
+                StatusCode::UNAUTHORIZED,
+                [(header::WWW_AUTHENTICATE, "Bearer")],
+                Json(Problem::new("UNAUTHORIZED", vec![reason]).for_current_request()),
+            )
+                .into_response(),
+            // Not logged, the outage is reported once by whatever detected it.
+            ApplicationError::Unavailable(reason) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(Problem::new("SERVICE_UNAVAILABLE", vec![reason]).for_current_request()),
+            )
+                .into_response(),
+            // Not logged, an abusive client would flood the logs too.
+            ApplicationError::TooManyRequests(retry_after) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(header::RETRY_AFTER, retry_after.to_string())],
+                Json(
+                    Problem::new(
+                        "TOO_MANY_REQUESTS",
+                        vec![format!(
+                            "Rate limit exceeded, retry in {} seconds.",
+                            retry_after
+                        )],
+                    )
+                    .for_current_request(),
+                ),
+            )
+                .into_response(),
+            ApplicationError::PayloadTooLarge(reason) => (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                Json(Problem::new("PAYLOAD_TOO_LARGE", vec![reason]).for_current_request()),
+            )
+                .into_response(),
+            // The server ran out of time, not the client, so a 503 to retry
+            // rather than a 408 that blames the request.
+            ApplicationError::RequestTimeout(seconds) => {
+                warn!("Request not answered within {} seconds", seconds);
+
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    Json(
+                        Problem::new(
+                            "SERVICE_UNAVAILABLE",
+                            vec![format!("The request took longer than {} seconds.", seconds)],
+                        )
+                        .for_current_request(),
+                    ),
+                )
+                    .into_response()
+            }
         }
     }
 }
 
+/// A body over `DefaultBodyLimit` is rejected with a 413, any other body
+/// the caller got wrong.
+fn body_rejection(status: StatusCode, reason: String) -> ApplicationError {
+    if status == StatusCode::PAYLOAD_TOO_LARGE {
+        ApplicationError::PayloadTooLarge(reason)
+    } else {
+        ApplicationError::ValidationError(vec![reason])
+    }
+}
+
 impl From<FormRejection> for ApplicationError {
     fn from(value: FormRejection) -> Self {
-        Self::ValidationError(vec![value.body_text()])
+        body_rejection(value.status(), value.body_text())
     }
 }
 
@@ -93,22 +197,42 @@ impl From<QueryRejection> for ApplicationError {
 
 impl From<JsonRejection> for ApplicationError {
     fn from(value: JsonRejection) -> Self {
-        Self::ValidationError(vec![value.body_text()])
+        body_rejection(value.status(), value.body_text())
+    }
+}
+
+impl From<BytesRejection> for ApplicationError {
+    fn from(value: BytesRejection) -> Self {
+        body_rejection(value.status(), value.body_text())
+    }
+}
+
+impl From<MultipartRejection> for ApplicationError {
+    fn from(value: MultipartRejection) -> Self {
+        body_rejection(value.status(), value.body_text())
+    }
+}
+
+impl From<MultipartError> for ApplicationError {
+    fn from(value: MultipartError) -> Self {
+        body_rejection(value.status(), value.body_text())
     }
 }
 
 impl From<ValidationErrors> for ApplicationError {
     fn from(value: ValidationErrors) -> Self {
-        Self::ValidationError(
-            value
-                .to_string()
-                .split('\n')
-                .map(ToString::to_string)
-                .collect(),
-        )
+        Self::ValidationError(validation_issues(&value))
     }
 }
 
+pub fn validation_issues(errors: &ValidationErrors) -> Vec<String> {
+    errors
+        .to_string()
+        .split('\n')
+        .map(ToString::to_string)
+        .collect()
+}
+
 impl From<SurrealDBError> for RepositoryError {
     fn from(value: SurrealDBError) -> Self {
         match value {
@@ -118,6 +242,17 @@ impl From<SurrealDBError> for RepositoryError {
     }
 }
 
+impl Display for RepositoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RepositoryError::Connection(err) => write!(f, "Connection error: {}", err),
+            RepositoryError::Query(err) => write!(f, "Query error: {}", err),
+            RepositoryError::NotFound(id) => write!(f, "{} not found", id),
+            RepositoryError::InsertError(err) => write!(f, "Insert error: {}", err),
+        }
+    }
+}
+
 impl From<RepositoryError> for ApplicationError {
     fn from(value: RepositoryError) -> Self {
         match value {
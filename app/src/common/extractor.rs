@@ -5,6 +5,7 @@ use axum::{
     http::{request::Parts, Request},
     BoxError, Form, Json,
 };
+use chrono_tz::Tz;
 use serde::de::DeserializeOwned;
 use validator::Validate;
 
@@ -72,3 +73,33 @@ where
         Ok(ValidatedBody(data))
     }
 }
+
+pub static TIME_ZONE_HEADER: &str = "time-zone";
+
+/// The caller's IANA time zone from the `Time-Zone` header, if sent. Due dates
+/// are rendered and days such as `today` are read in it.
+pub struct RequestTimezone(pub Option<Tz>);
+
+#[async_trait]
+impl<State> FromRequestParts<State> for RequestTimezone
+where
+    State: Send + Sync,
+{
+    type Rejection = ApplicationError;
+
+    async fn from_request_parts(parts: &mut Parts, _: &State) -> Result<Self, Self::Rejection> {
+        let Some(value) = parts.headers.get(TIME_ZONE_HEADER) else {
+            return Ok(RequestTimezone(None));
+        };
+
+        let value = value.to_str().unwrap_or_default().trim();
+
+        match value.parse::<Tz>() {
+            Ok(timezone) => Ok(RequestTimezone(Some(timezone))),
+            Err(_) => Err(ApplicationError::ValidationError(vec![format!(
+                "Time-Zone: `{}` is not an IANA time zone such as Europe/Berlin",
+                value
+            )])),
+        }
+    }
+}
@@ -11,5 +11,3 @@ pub use constant::*;
 pub use database::*;
 pub use error::*;
 pub use extractor::*;
-pub use traits::*;
-pub use types::*;
@@ -0,0 +1,10 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CalendarFeedModel {
+    pub id: Thing,
+    pub token_hash: String,
+    pub created_at: DateTime<Utc>,
+}
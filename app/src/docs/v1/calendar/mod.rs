@@ -0,0 +1,5 @@
+pub mod database;
+pub mod response;
+
+pub use database::*;
+pub use response::*;
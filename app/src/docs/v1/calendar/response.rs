@@ -0,0 +1,24 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarFeedResponse {
+    #[schema(example = "3f9c2a5e0b7d41c8a6e2f1d09b8c7a6e5d4c3b2a1f0e9d8c7b6a5f4e3d2c1b0a")]
+    pub token: String,
+    #[schema(
+        example = "/v1/calendar/3f9c2a5e0b7d41c8a6e2f1d09b8c7a6e5d4c3b2a1f0e9d8c7b6a5f4e3d2c1b0a.ics"
+    )]
+    pub url: String,
+}
+
+impl IntoResponse for CalendarFeedResponse {
+    fn into_response(self) -> Response {
+        (StatusCode::OK, Json(self)).into_response()
+    }
+}
@@ -0,0 +1,24 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::Thing;
+
+use crate::docs::v1::todos::TodoModel;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Created,
+    Updated,
+    Deleted,
+}
+
+/// A todo write recorded in the outbox, in the same transaction as the write
+/// itself, with the todo before and after it.
+#[derive(Deserialize, Debug)]
+pub struct OutboxModel {
+    pub id: Thing,
+    pub change: ChangeKind,
+    pub before: Option<TodoModel>,
+    pub after: Option<TodoModel>,
+    pub occurred_at: DateTime<Utc>,
+}
@@ -0,0 +1,149 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use utoipa::ToSchema;
+
+use crate::docs::v1::todos::Todo;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EventKind {
+    TodoCreated,
+    TodoUpdated,
+    TodoCompleted,
+    TodoDeleted,
+}
+
+impl EventKind {
+    pub fn all() -> [EventKind; 4] {
+        [
+            EventKind::TodoCreated,
+            EventKind::TodoUpdated,
+            EventKind::TodoCompleted,
+            EventKind::TodoDeleted,
+        ]
+    }
+
+    pub fn from_name(name: &str) -> Option<EventKind> {
+        Self::all().into_iter().find(|kind| kind.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EventKind::TodoCreated => "todo.created",
+            EventKind::TodoUpdated => "todo.updated",
+            EventKind::TodoCompleted => "todo.completed",
+            EventKind::TodoDeleted => "todo.deleted",
+        }
+    }
+}
+
+/// One field of a todo that an update changed, with its values as the API
+/// renders them.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChange {
+    #[schema(example = "isDone")]
+    pub field: String,
+    #[schema(value_type = Object, example = false)]
+    pub from: Value,
+    #[schema(value_type = Object, example = true)]
+    pub to: Value,
+}
+
+#[derive(Debug, Clone)]
+pub enum TodoEvent {
+    TodoCreated {
+        todo: Todo,
+    },
+    TodoUpdated {
+        todo: Todo,
+        changes: Vec<FieldChange>,
+    },
+    TodoCompleted {
+        todo: Todo,
+    },
+    TodoDeleted {
+        todo: Todo,
+    },
+}
+
+impl TodoEvent {
+    pub fn kind(&self) -> EventKind {
+        match self {
+            TodoEvent::TodoCreated { .. } => EventKind::TodoCreated,
+            TodoEvent::TodoUpdated { .. } => EventKind::TodoUpdated,
+            TodoEvent::TodoCompleted { .. } => EventKind::TodoCompleted,
+            TodoEvent::TodoDeleted { .. } => EventKind::TodoDeleted,
+        }
+    }
+
+    /// The todo after the change, or as it was when it got deleted.
+    pub fn todo(&self) -> &Todo {
+        match self {
+            TodoEvent::TodoCreated { todo }
+            | TodoEvent::TodoUpdated { todo, .. }
+            | TodoEvent::TodoCompleted { todo }
+            | TodoEvent::TodoDeleted { todo } => todo,
+        }
+    }
+
+    /// The events one update makes: always an update, and a completion when
+    /// the todo became done.
+    pub fn from_update(before: &Todo, after: Todo) -> Vec<TodoEvent> {
+        let completed = after.is_done && !before.is_done;
+        let mut events = vec![TodoEvent::TodoUpdated {
+            changes: field_changes(before, &after),
+            todo: after.clone(),
+        }];
+
+        if completed {
+            events.push(TodoEvent::TodoCompleted { todo: after });
+        }
+
+        events
+    }
+}
+
+/// A todo event as handed to subscribers. The id stays the same when the
+/// event is published again, so subscribers can drop a repeat.
+#[derive(Debug, Clone)]
+pub struct DomainEvent {
+    pub id: String,
+    pub occurred_at: DateTime<Utc>,
+    pub event: TodoEvent,
+}
+
+fn field_changes(before: &Todo, after: &Todo) -> Vec<FieldChange> {
+    let reminders =
+        |todo: &Todo| -> Vec<String> { todo.reminders.iter().map(ToString::to_string).collect() };
+
+    [
+        ("subject", json!(before.subject), json!(after.subject)),
+        (
+            "description",
+            json!(before.description),
+            json!(after.description),
+        ),
+        ("isDone", json!(before.is_done), json!(after.is_done)),
+        ("dueDate", json!(before.due_date), json!(after.due_date)),
+        (
+            "timezone",
+            json!(before.timezone.map(|tz| tz.name())),
+            json!(after.timezone.map(|tz| tz.name())),
+        ),
+        ("allDay", json!(before.all_day), json!(after.all_day)),
+        (
+            "reminders",
+            json!(reminders(before)),
+            json!(reminders(after)),
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, from, to)| from != to)
+    .map(|(field, from, to)| FieldChange {
+        field: field.to_string(),
+        from,
+        to,
+    })
+    .collect()
+}
@@ -0,0 +1,39 @@
+use std::time::Duration;
+
+/// Outcome of checking one dependency of the app.
+#[derive(Debug, Clone)]
+pub struct DependencyCheck {
+    pub healthy: bool,
+    pub latency: Duration,
+    /// Why the dependency is unhealthy, or what is off about a healthy one,
+    /// when it is safe to tell
+    pub detail: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct HealthReport {
+    pub database: DependencyCheck,
+    pub migrations: DependencyCheck,
+    pub draining: bool,
+    pub uptime: Duration,
+}
+
+impl HealthReport {
+    /// Whether traffic should be sent to the app.
+    pub fn is_ready(&self) -> bool {
+        self.failing().is_empty()
+    }
+
+    /// Names of the checks keeping the app from being ready.
+    pub fn failing(&self) -> Vec<String> {
+        [
+            ("database", !self.database.healthy),
+            ("migrations", !self.migrations.healthy),
+            ("draining", self.draining),
+        ]
+        .into_iter()
+        .filter(|(_, failing)| *failing)
+        .map(|(name, _)| name.to_string())
+        .collect()
+    }
+}
@@ -1,3 +1,5 @@
+pub mod domain;
 pub mod response;
 
+pub use domain::*;
 pub use response::*;
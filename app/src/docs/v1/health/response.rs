@@ -5,14 +5,120 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 
+use crate::common::Application;
+
+use super::{DependencyCheck, HealthReport};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum HealthStatus {
+    Ok,
+    Nok,
+}
+
+impl HealthStatus {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            HealthStatus::Ok => StatusCode::OK,
+            HealthStatus::Nok => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+}
+
+impl From<bool> for HealthStatus {
+    fn from(healthy: bool) -> Self {
+        if healthy {
+            HealthStatus::Ok
+        } else {
+            HealthStatus::Nok
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyHealthResponse {
+    pub status: HealthStatus,
+    pub latency_ms: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl From<DependencyCheck> for DependencyHealthResponse {
+    fn from(value: DependencyCheck) -> Self {
+        Self {
+            status: value.healthy.into(),
+            latency_ms: value.latency.as_secs_f64() * 1000.0,
+            detail: value.detail,
+        }
+    }
+}
+
+/// Answered with `503 Service Unavailable` when the app is not ready.
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct HealthStatusResponse {
-    pub api: String,
-    pub database: String,
+    pub status: HealthStatus,
+    pub name: String,
+    pub version: String,
+    pub uptime_seconds: u64,
+    pub draining: bool,
+    pub database: DependencyHealthResponse,
+    pub migrations: DependencyHealthResponse,
+}
+
+impl HealthStatusResponse {
+    pub fn new(report: HealthReport, app: &Application) -> Self {
+        Self {
+            status: report.is_ready().into(),
+            name: app.name.clone(),
+            version: app.version.clone(),
+            uptime_seconds: report.uptime.as_secs(),
+            draining: report.draining,
+            database: report.database.into(),
+            migrations: report.migrations.into(),
+        }
+    }
 }
 
 impl IntoResponse for HealthStatusResponse {
     fn into_response(self) -> Response {
-        (StatusCode::OK, Json(self)).into_response()
+        (self.status.status_code(), Json(self)).into_response()
+    }
+}
+
+/// Body of `/livez` and `/readyz`, answered with `503 Service Unavailable`
+/// when the probe fails.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ProbeResponse {
+    pub status: HealthStatus,
+    /// Checks that failed, such as `database`, `migrations` or `draining`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failing: Vec<String>,
+}
+
+impl ProbeResponse {
+    pub fn alive() -> Self {
+        Self {
+            status: HealthStatus::Ok,
+            failing: vec![],
+        }
+    }
+}
+
+impl From<HealthReport> for ProbeResponse {
+    fn from(value: HealthReport) -> Self {
+        let failing = value.failing();
+
+        Self {
+            status: failing.is_empty().into(),
+            failing,
+        }
+    }
+}
+
+impl IntoResponse for ProbeResponse {
+    fn into_response(self) -> Response {
+        (self.status.status_code(), Json(self)).into_response()
     }
 }
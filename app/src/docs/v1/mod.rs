@@ -1,2 +1,7 @@
+pub mod calendar;
+pub mod events;
 pub mod health;
+pub mod reminders;
 pub mod todos;
+pub mod views;
+pub mod webhooks;
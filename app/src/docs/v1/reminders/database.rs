@@ -0,0 +1,36 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::{Datetime as SurrealDbDateTime, Thing};
+
+#[derive(Deserialize, Debug)]
+pub struct ReminderModel {
+    pub id: Thing,
+    pub todo_id: Thing,
+    pub subject: String,
+    pub due_date: DateTime<Utc>,
+    pub remind_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ReminderModelInsert {
+    pub id: String,
+    pub todo: Thing,
+    pub remind_at: SurrealDbDateTime,
+}
+
+impl ReminderModelInsert {
+    /// One reminder per offset. The id is made of the todo and the moment it
+    /// fires, so a reminder that was already sent is never scheduled again
+    /// unless its todo moves.
+    pub fn for_todo(todo: &Thing, due_date: DateTime<Utc>, offsets: &[u32]) -> Vec<Self> {
+        offsets
+            .iter()
+            .map(|minutes| due_date - Duration::minutes(*minutes as i64))
+            .map(|remind_at| Self {
+                id: format!("{}_{}", todo.id.to_raw(), remind_at.timestamp()),
+                todo: todo.clone(),
+                remind_at: SurrealDbDateTime(remind_at),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,71 @@
+use std::fmt;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+/// Longest a reminder may fire ahead of its todo's due date.
+pub static MAX_REMINDER_MINUTES: u32 = 4 * 7 * 24 * 60;
+
+/// How long before its due date a todo reminds, written as `30m`, `1h`, `2d`
+/// or `1w`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ReminderOffset {
+    pub minutes: u32,
+}
+
+impl ReminderOffset {
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let unit = value.chars().last()?;
+        let amount = &value[..value.len() - unit.len_utf8()];
+
+        // Bounded so that the multiplication below cannot overflow.
+        if amount.is_empty() || amount.len() > 5 || !amount.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+
+        let amount: u32 = amount.parse().ok()?;
+        let minutes = match unit.to_ascii_lowercase() {
+            'm' => amount,
+            'h' => amount * 60,
+            'd' => amount * 24 * 60,
+            'w' => amount * 7 * 24 * 60,
+            _ => return None,
+        };
+
+        (minutes <= MAX_REMINDER_MINUTES).then_some(Self { minutes })
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::minutes(self.minutes as i64)
+    }
+}
+
+impl fmt::Display for ReminderOffset {
+    /// Uses the largest unit that divides the offset, so `90m` stays `90m`
+    /// while `60m` becomes `1h`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let units = [(7 * 24 * 60, 'w'), (24 * 60, 'd'), (60, 'h')];
+
+        match units
+            .into_iter()
+            .find(|(size, _)| self.minutes > 0 && self.minutes.checked_rem(*size) == Some(0))
+        {
+            Some((size, unit)) => write!(f, "{}{}", self.minutes / size, unit),
+            None => write!(f, "{}m", self.minutes),
+        }
+    }
+}
+
+/// A reminder that is due, as handed to a notifier.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Reminder {
+    /// Stable across retries, so receivers can drop duplicates
+    pub id: String,
+    pub todo_id: Ulid,
+    pub subject: String,
+    pub due_date: DateTime<Utc>,
+    pub remind_at: DateTime<Utc>,
+}
@@ -0,0 +1,5 @@
+pub mod database;
+pub mod domain;
+
+pub use database::*;
+pub use domain::*;
@@ -1,35 +1,176 @@
 use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use surrealdb::sql::{Datetime as SurrealDbDateTime, Thing};
+use ulid::Ulid;
 
-use super::request::UpdateTodoRequest;
+use crate::docs::v1::reminders::ReminderOffset;
 
-#[derive(Serialize, Deserialize, Debug)]
+use super::{request::UpdateTodoRequest, Todo};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TodoModel {
     pub id: Thing,
     pub subject: String,
     pub description: String,
     pub is_done: bool,
     pub due_date: DateTime<Utc>,
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub all_day: bool,
+    /// Minutes before the due date
+    #[serde(default)]
+    pub reminders: Vec<u32>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+impl TryFrom<TodoModel> for Todo {
+    type Error = String;
+
+    fn try_from(model: TodoModel) -> Result<Self, Self::Error> {
+        let id = Ulid::from_string(&model.id.id.to_raw()).map_err(|err| err.to_string())?;
+        let timezone = model
+            .timezone
+            .as_deref()
+            .map(str::parse::<Tz>)
+            .transpose()?;
+
+        Ok(Todo {
+            id,
+            subject: model.subject,
+            description: model.description,
+            is_done: model.is_done,
+            due_date: model.due_date,
+            timezone,
+            all_day: model.all_day,
+            reminders: model
+                .reminders
+                .into_iter()
+                .map(|minutes| ReminderOffset { minutes })
+                .collect(),
+            created_at: model.created_at,
+            updated_at: model.updated_at,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TodoSearchModel {
+    pub id: Thing,
+    pub subject: String,
+    pub description: String,
+    pub is_done: bool,
+    pub due_date: DateTime<Utc>,
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub all_day: bool,
+    /// Minutes before the due date
+    #[serde(default)]
+    pub reminders: Vec<u32>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub score: f64,
+    pub subject_highlight: Option<String>,
+    pub description_highlight: Option<String>,
+}
+
+impl From<TodoSearchModel> for TodoModel {
+    fn from(value: TodoSearchModel) -> Self {
+        Self {
+            id: value.id,
+            subject: value.subject,
+            description: value.description,
+            is_done: value.is_done,
+            due_date: value.due_date,
+            timezone: value.timezone,
+            all_day: value.all_day,
+            reminders: value.reminders,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TodoSuggestionModel {
+    pub id: Thing,
+    pub subject: String,
+    #[serde(default)]
+    pub score: f64,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TodoStatusCountModel {
+    pub is_done: bool,
+    pub count: u64,
+}
+
 #[derive(Serialize)]
 pub struct TodoModelUpdate {
     pub subject: String,
     pub description: String,
     pub is_done: bool,
     pub due_date: SurrealDbDateTime,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    pub all_day: bool,
+    pub reminders: Vec<u32>,
 }
 
 impl TodoModelUpdate {
-    pub fn merge(existing: TodoModel, update: UpdateTodoRequest) -> Self {
+    /// `due_date` and `reminders` are the update's, already resolved by the
+    /// service against the todo's time zone and all-day flag.
+    pub fn merge(
+        existing: TodoModel,
+        update: UpdateTodoRequest,
+        due_date: Option<DateTime<Utc>>,
+        reminders: Option<Vec<ReminderOffset>>,
+    ) -> Self {
         Self {
             subject: update.subject.unwrap_or(existing.subject),
             description: update.description.unwrap_or(existing.description),
             is_done: update.is_done.unwrap_or(existing.is_done),
-            due_date: SurrealDbDateTime(update.due_date.unwrap_or(existing.due_date)),
+            due_date: SurrealDbDateTime(due_date.unwrap_or(existing.due_date)),
+            timezone: update.timezone.or(existing.timezone),
+            all_day: update.all_day.unwrap_or(existing.all_day),
+            reminders: match reminders {
+                Some(reminders) => reminders.iter().map(|r| r.minutes).collect(),
+                None => existing.reminders,
+            },
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct TodoModelInsert {
+    pub id: String,
+    pub subject: String,
+    pub description: String,
+    pub is_done: bool,
+    pub due_date: SurrealDbDateTime,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    pub all_day: bool,
+    pub reminders: Vec<u32>,
+    pub created_at: SurrealDbDateTime,
+    pub updated_at: SurrealDbDateTime,
+}
+
+impl From<Todo> for TodoModelInsert {
+    fn from(value: Todo) -> Self {
+        Self {
+            id: value.id.to_string(),
+            subject: value.subject,
+            description: value.description,
+            is_done: value.is_done,
+            due_date: SurrealDbDateTime(value.due_date),
+            timezone: value.timezone.map(|tz| tz.name().to_string()),
+            all_day: value.all_day,
+            reminders: value.reminders.iter().map(|r| r.minutes).collect(),
+            created_at: SurrealDbDateTime(value.created_at),
+            updated_at: SurrealDbDateTime(value.updated_at),
         }
     }
 }
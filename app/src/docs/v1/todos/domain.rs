@@ -1,14 +1,75 @@
 use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use ulid::Ulid;
 
-#[derive(Serialize, Deserialize, Debug)]
+use crate::{docs::v1::reminders::ReminderOffset, util::QueryExpr};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Todo {
     pub id: Ulid,
     pub subject: String,
     pub description: String,
     pub is_done: bool,
     pub due_date: DateTime<Utc>,
+    /// Zone the due date's wall clock time was set in, if the todo has one
+    pub timezone: Option<Tz>,
+    /// Due by the end of the day in its zone rather than at a time
+    pub all_day: bool,
+    /// Sorted, without duplicates
+    pub reminders: Vec<ReminderOffset>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
+
+#[derive(Debug)]
+pub struct TodoSearchResult {
+    pub todo: Todo,
+    pub score: f64,
+    pub subject_highlight: Option<String>,
+    pub description_highlight: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct TodoSuggestion {
+    pub id: Ulid,
+    pub subject: String,
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct TodoFilter {
+    pub is_done: Option<bool>,
+    pub due_after: Option<DateTime<Utc>>,
+    pub due_before: Option<DateTime<Utc>>,
+    pub query: Option<QueryExpr>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Pagination {
+    pub limit: u32,
+    pub offset: u32,
+}
+
+impl Pagination {
+    /// Requests bound the page, so the offset only saturates past the last
+    /// record rather than wrap around to the first ones.
+    pub fn from_page(page: u32, per_page: u32) -> Self {
+        Self {
+            limit: per_page,
+            offset: page.saturating_sub(1).saturating_mul(per_page),
+        }
+    }
+
+    pub fn page(&self) -> u32 {
+        self.offset / self.limit.max(1) + 1
+    }
+}
+
+#[derive(Debug)]
+pub struct TodoSearchPage {
+    pub results: Vec<TodoSearchResult>,
+    pub pagination: Pagination,
+    pub total: u64,
+    pub open_count: u64,
+    pub done_count: u64,
+}
@@ -0,0 +1,150 @@
+use chrono::{Duration, Utc};
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::util::{calendar_footer, calendar_header, Component};
+
+use super::TodoResponse;
+
+static CSV_COLUMNS: [&str; 7] = [
+    "id",
+    "subject",
+    "description",
+    "isDone",
+    "dueDate",
+    "createdAt",
+    "updatedAt",
+];
+
+#[derive(Deserialize, ToSchema, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Ndjson,
+    Ics,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Ndjson => "application/x-ndjson",
+            ExportFormat::Ics => "text/calendar; charset=utf-8",
+        }
+    }
+
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "todos.csv",
+            ExportFormat::Ndjson => "todos.ndjson",
+            ExportFormat::Ics => "todos.ics",
+        }
+    }
+
+    pub fn header(&self) -> String {
+        match self {
+            ExportFormat::Csv => csv_line(&CSV_COLUMNS),
+            ExportFormat::Ndjson => String::new(),
+            ExportFormat::Ics => calendar_header("Todos"),
+        }
+    }
+
+    pub fn render(&self, todo: &TodoResponse) -> String {
+        match self {
+            ExportFormat::Csv => csv_line(&[
+                &todo.id,
+                &todo.subject,
+                &todo.description,
+                &todo.is_done.to_string(),
+                &todo.due_date.to_rfc3339(),
+                &todo.created_at.to_rfc3339(),
+                &todo.updated_at.to_rfc3339(),
+            ]),
+            ExportFormat::Ndjson => {
+                let mut line = serde_json::to_string(todo).unwrap_or_default();
+                line.push('\n');
+
+                line
+            }
+            ExportFormat::Ics => vtodo(todo).render(),
+        }
+    }
+
+    pub fn footer(&self) -> String {
+        match self {
+            ExportFormat::Ics => calendar_footer(),
+            _ => String::new(),
+        }
+    }
+}
+
+pub fn vtodo(todo: &TodoResponse) -> Component {
+    let status = if todo.is_done {
+        "COMPLETED"
+    } else {
+        "NEEDS-ACTION"
+    };
+
+    let component = Component::new("VTODO")
+        .property("UID", &todo.id)
+        .datetime("DTSTAMP", &todo.updated_at)
+        .datetime("CREATED", &todo.created_at)
+        .datetime("LAST-MODIFIED", &todo.updated_at);
+
+    with_due(component, "DUE", todo)
+        .text("SUMMARY", &todo.subject)
+        .text("DESCRIPTION", &todo.description)
+        .property("STATUS", status)
+}
+
+/// Renders the todo as a 30 minute event at its due date, or an all-day
+/// event on its day, for calendar clients (such as Google Calendar) that
+/// ignore VTODO components.
+pub fn vevent(todo: &TodoResponse) -> Component {
+    let component = Component::new("VEVENT")
+        .property("UID", &format!("{}-due", todo.id))
+        .datetime("DTSTAMP", &todo.updated_at);
+
+    let component = match todo.all_day {
+        true => {
+            let day = todo.due_date.date_naive();
+
+            component
+                .date("DTSTART", &day)
+                .date("DTEND", &(day + Duration::days(1)))
+        }
+        false => {
+            let due_date = todo.due_date.with_timezone(&Utc);
+
+            component
+                .datetime("DTSTART", &due_date)
+                .datetime("DTEND", &(due_date + Duration::minutes(30)))
+        }
+    };
+
+    component
+        .text("SUMMARY", &todo.subject)
+        .text("DESCRIPTION", &todo.description)
+}
+
+/// All-day todos are due on a date in their own zone, the others at an instant.
+fn with_due(component: Component, name: &str, todo: &TodoResponse) -> Component {
+    match todo.all_day {
+        true => component.date(name, &todo.due_date.date_naive()),
+        false => component.datetime(name, &todo.due_date.with_timezone(&Utc)),
+    }
+}
+
+fn csv_line(fields: &[&str]) -> String {
+    let mut writer = csv::Writer::from_writer(vec![]);
+
+    if writer.write_record(fields).is_err() {
+        return String::new();
+    }
+
+    writer
+        .into_inner()
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .unwrap_or_default()
+}
@@ -0,0 +1,246 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+
+use crate::{
+    common::Problem,
+    util::{keep_wall_clock, parse_components, parse_datetime, unescape_text, Property},
+};
+
+use super::{CreateTodoRequest, DueDateInput};
+
+#[derive(Deserialize, ToSchema, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportFormat {
+    Csv,
+    Json,
+    Ics,
+}
+
+impl ImportFormat {
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let mime = content_type.split(';').next()?.trim().to_ascii_lowercase();
+
+        match mime.as_str() {
+            "text/csv" | "application/csv" => Some(ImportFormat::Csv),
+            "application/json" => Some(ImportFormat::Json),
+            "text/calendar" => Some(ImportFormat::Ics),
+            _ => None,
+        }
+    }
+
+    pub fn from_file_name(file_name: &str) -> Option<Self> {
+        let (_, extension) = file_name.rsplit_once('.')?;
+
+        match extension.to_ascii_lowercase().as_str() {
+            "csv" => Some(ImportFormat::Csv),
+            "json" => Some(ImportFormat::Json),
+            "ics" | "ical" => Some(ImportFormat::Ics),
+            _ => None,
+        }
+    }
+
+    /// Splits the payload into rows. A row that cannot even be read comes back
+    /// as the list of issues found with it.
+    pub fn parse(&self, payload: &[u8]) -> Result<Vec<ImportRow>, String> {
+        match self {
+            ImportFormat::Csv => parse_csv(payload),
+            ImportFormat::Json => parse_json(payload),
+            ImportFormat::Ics => parse_ics(payload),
+        }
+    }
+}
+
+pub type ImportRow = Result<ImportedTodo, Vec<String>>;
+
+#[derive(Debug)]
+pub struct ImportedTodo {
+    pub todo: CreateTodoRequest,
+    pub is_done: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ImportRecord {
+    subject: String,
+    description: String,
+    due_date: DateTime<Utc>,
+    #[serde(default)]
+    is_done: bool,
+    timezone: Option<String>,
+    #[serde(default)]
+    all_day: bool,
+    #[serde(default)]
+    reminders: Vec<String>,
+}
+
+impl From<ImportRecord> for ImportedTodo {
+    fn from(value: ImportRecord) -> Self {
+        Self {
+            todo: CreateTodoRequest {
+                subject: value.subject,
+                description: value.description,
+                due_date: DueDateInput::Timestamp(value.due_date),
+                timezone: value.timezone,
+                all_day: value.all_day,
+                reminders: value.reminders,
+            },
+            is_done: value.is_done,
+        }
+    }
+}
+
+#[derive(Deserialize, Validate, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct ImportTodosRequest {
+    /// Validate every row without inserting anything
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Overrides the format detected from the content type or file name
+    pub format: Option<ImportFormat>,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ImportRowProblem {
+    #[schema(example = 2)]
+    pub row: usize,
+    #[serde(flatten)]
+    pub problem: Problem<'static>,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportTodosResponse {
+    #[schema(example = false)]
+    pub dry_run: bool,
+    #[schema(example = 10)]
+    pub total: usize,
+    #[schema(example = 9)]
+    pub imported: usize,
+    #[schema(example = 1)]
+    pub failed: usize,
+    pub errors: Vec<ImportRowProblem>,
+}
+
+impl IntoResponse for ImportTodosResponse {
+    fn into_response(self) -> Response {
+        (StatusCode::OK, Json(self)).into_response()
+    }
+}
+
+fn parse_json(payload: &[u8]) -> Result<Vec<ImportRow>, String> {
+    let values: Vec<serde_json::Value> = serde_json::from_slice(payload)
+        .map_err(|err| format!("body must be a JSON array of todos: {}", err))?;
+
+    Ok(values
+        .into_iter()
+        .map(|value| {
+            serde_json::from_value::<ImportRecord>(value)
+                .map(ImportedTodo::from)
+                .map_err(|err| vec![err.to_string()])
+        })
+        .collect())
+}
+
+fn parse_csv(payload: &[u8]) -> Result<Vec<ImportRow>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(payload);
+
+    reader
+        .headers()
+        .map_err(|err| format!("unable to read CSV header: {}", err))?;
+
+    Ok(reader
+        .deserialize::<ImportRecord>()
+        .map(|record| {
+            record
+                .map(ImportedTodo::from)
+                .map_err(|err| vec![csv_error_message(&err)])
+        })
+        .collect())
+}
+
+fn csv_error_message(err: &csv::Error) -> String {
+    match err.kind() {
+        csv::ErrorKind::Deserialize { err, .. } => match err.field() {
+            Some(field) => format!("column {}: {}", field + 1, err.kind()),
+            None => format!("unable to read row: {}", err.kind()),
+        },
+        _ => err.to_string(),
+    }
+}
+
+fn parse_ics(payload: &[u8]) -> Result<Vec<ImportRow>, String> {
+    let calendar =
+        std::str::from_utf8(payload).map_err(|_| String::from("calendar must be UTF-8 encoded"))?;
+
+    if !calendar.trim_start().starts_with("BEGIN:VCALENDAR") {
+        return Err(String::from("body is not an iCalendar file"));
+    }
+
+    Ok(parse_components(calendar, "VTODO")
+        .iter()
+        .map(|properties| vtodo_to_row(properties))
+        .collect())
+}
+
+fn vtodo_to_row(properties: &[Property]) -> ImportRow {
+    let find = |name: &str| properties.iter().find(|p| p.name == name);
+    let mut issues = vec![];
+
+    let subject = find("SUMMARY").map(|p| unescape_text(&p.value));
+    let description = find("DESCRIPTION").map(|p| unescape_text(&p.value));
+    let due = find("DUE");
+    let due_date = due.and_then(|p| parse_datetime(&p.value));
+    let all_day = due
+        .and_then(|p| p.param("VALUE"))
+        .is_some_and(|value| value.eq_ignore_ascii_case("DATE"));
+    let timezone = due
+        .and_then(|p| p.param("TZID"))
+        .and_then(|tzid| tzid.parse::<Tz>().ok());
+    let is_done = find("STATUS").is_some_and(|p| p.value.eq_ignore_ascii_case("COMPLETED"));
+
+    if subject.is_none() {
+        issues.push(String::from("SUMMARY is required"));
+    }
+
+    if description.is_none() {
+        issues.push(String::from("DESCRIPTION is required"));
+    }
+
+    if due_date.is_none() {
+        issues.push(String::from("DUE is missing or not a valid date"));
+    }
+
+    match (subject, description, due_date) {
+        (Some(subject), Some(description), Some(due_date)) => Ok(ImportedTodo {
+            todo: CreateTodoRequest {
+                subject,
+                description,
+                // A DATE has no zone of its own, it is read as a day wherever
+                // the todo ends up living.
+                due_date: match (all_day, timezone) {
+                    (true, _) => DueDateInput::Phrase(due_date.format("%Y-%m-%d").to_string()),
+                    (false, Some(timezone)) => DueDateInput::Timestamp(
+                        keep_wall_clock(due_date, Tz::UTC, timezone).unwrap_or(due_date),
+                    ),
+                    (false, None) => DueDateInput::Timestamp(due_date),
+                },
+                timezone: timezone.map(|tz| tz.name().to_string()),
+                all_day,
+                reminders: vec![],
+            },
+            is_done,
+        }),
+        _ => Err(issues),
+    }
+}
@@ -1,9 +1,13 @@
 pub mod database;
 pub mod domain;
+pub mod export;
+pub mod import;
 pub mod request;
 pub mod response;
 
 pub use database::*;
 pub use domain::*;
+pub use export::*;
+pub use import::*;
 pub use request::*;
 pub use response::*;
@@ -3,7 +3,37 @@ use serde::{Deserialize, Serialize};
 use utoipa::{IntoParams, ToSchema};
 use validator::Validate;
 
-#[derive(Deserialize, Validate, ToSchema)]
+use super::{ExportFormat, Pagination, TodoFilter};
+
+static DEFAULT_PER_PAGE: u32 = 20;
+static DEFAULT_SUGGEST_LIMIT: u32 = 8;
+
+/// The filter of the parameters every todo listing takes, so listing,
+/// searching and exporting cannot drift apart. The query is parsed by the
+/// service.
+fn filter(
+    is_done: Option<bool>,
+    due_after: Option<DateTime<Utc>>,
+    due_before: Option<DateTime<Utc>>,
+) -> TodoFilter {
+    TodoFilter {
+        is_done,
+        due_after,
+        due_before,
+        query: None,
+    }
+}
+
+/// A due date as sent by clients, resolved by the service against its clock.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum DueDateInput {
+    Timestamp(DateTime<Utc>),
+    /// A phrase such as `tomorrow 5pm` or `next friday`
+    Phrase(String),
+}
+
+#[derive(Deserialize, Validate, Debug, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateTodoRequest {
     #[validate(length(min = 1))]
@@ -12,7 +42,22 @@ pub struct CreateTodoRequest {
     #[validate(length(min = 1))]
     #[schema(example = "Buy groceries from the supermarket for the weekend.")]
     pub description: String,
-    pub due_date: DateTime<Utc>,
+    /// An RFC 3339 timestamp or a phrase such as `tomorrow 5pm`, `next friday` or `in 3 days`
+    #[schema(value_type = String, example = "tomorrow 5pm")]
+    pub due_date: DueDateInput,
+    /// IANA time zone the due date's wall clock time belongs to, the server's
+    /// zone when left out
+    #[schema(example = "Europe/Berlin")]
+    pub timezone: Option<String>,
+    /// Due by the end of the day rather than at a time
+    #[serde(default)]
+    #[schema(example = false)]
+    pub all_day: bool,
+    /// How long before the due date to remind, such as `30m`, `1h`, `2d` or `1w`
+    #[serde(default)]
+    #[validate(length(max = 5))]
+    #[schema(example = json!(["1h", "1d"]))]
+    pub reminders: Vec<String>,
 }
 
 #[derive(Deserialize, Serialize, Validate, Debug, ToSchema)]
@@ -24,14 +69,142 @@ pub struct UpdateTodoRequest {
     pub description: Option<String>,
     #[schema(example = true)]
     pub is_done: Option<bool>,
-    pub due_date: Option<DateTime<Utc>>,
+    /// An RFC 3339 timestamp or a phrase such as `tomorrow 5pm`, `next friday` or `in 3 days`
+    #[schema(value_type = Option<String>, example = "next friday")]
+    pub due_date: Option<DueDateInput>,
+    /// Moving a todo to another zone keeps its wall clock time, so 9am stays 9am
+    #[schema(example = "Europe/Berlin")]
+    pub timezone: Option<String>,
+    #[schema(example = true)]
+    pub all_day: Option<bool>,
+    /// Replaces every reminder, reminders that already fired are not sent again
+    #[validate(length(max = 5))]
+    #[schema(example = json!(["30m"]))]
+    pub reminders: Option<Vec<String>>,
 }
 
 #[derive(Deserialize, Validate, ToSchema, IntoParams)]
+#[serde(rename_all = "camelCase")]
 #[into_params(parameter_in = Query)]
 pub struct SearchTodoRequest {
     #[schema(example = "groceries")]
     #[param(example = "todo")]
     #[validate(length(min = 1, message = "is required!"))]
     pub q: String,
+    #[param(example = false)]
+    pub is_done: Option<bool>,
+    #[schema(value_type = Option<String>, format = DateTime)]
+    #[param(value_type = Option<String>, format = DateTime)]
+    pub due_after: Option<DateTime<Utc>>,
+    #[schema(value_type = Option<String>, format = DateTime)]
+    #[param(value_type = Option<String>, format = DateTime)]
+    pub due_before: Option<DateTime<Utc>>,
+    #[param(example = 1)]
+    #[validate(range(min = 1, max = 10000, message = "must be between 1 and 10000"))]
+    pub page: Option<u32>,
+    #[param(example = 20)]
+    #[validate(range(min = 1, max = 100, message = "must be between 1 and 100"))]
+    pub per_page: Option<u32>,
+}
+
+impl SearchTodoRequest {
+    /// Search results are always paginated.
+    pub fn pagination(&self) -> Pagination {
+        Pagination::from_page(
+            self.page.unwrap_or(1),
+            self.per_page.unwrap_or(DEFAULT_PER_PAGE),
+        )
+    }
+}
+
+impl From<&SearchTodoRequest> for TodoFilter {
+    fn from(value: &SearchTodoRequest) -> Self {
+        filter(value.is_done, value.due_after, value.due_before)
+    }
+}
+
+#[derive(Deserialize, Validate, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct SuggestTodoRequest {
+    /// What the user typed so far, every word is matched as a prefix
+    #[param(example = "groc")]
+    #[validate(length(min = 1, max = 100, message = "must be between 1 and 100 characters"))]
+    pub prefix: String,
+    #[param(example = 8)]
+    #[validate(range(min = 1, max = 20, message = "must be between 1 and 20"))]
+    pub limit: Option<u32>,
+}
+
+impl SuggestTodoRequest {
+    pub fn limit(&self) -> u32 {
+        self.limit.unwrap_or(DEFAULT_SUGGEST_LIMIT)
+    }
+}
+
+#[derive(Deserialize, Validate, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct GetTodosRequest {
+    /// Filter in the query syntax, e.g. `is:open due:<7d "quarterly report"`.
+    /// Todos have no tags, so `tag:` is refused.
+    #[param(example = "is:open due:<7d")]
+    #[validate(length(max = 500, message = "must be at most 500 characters"))]
+    pub query: Option<String>,
+    #[param(example = false)]
+    pub is_done: Option<bool>,
+    #[param(value_type = Option<String>, format = DateTime)]
+    pub due_after: Option<DateTime<Utc>>,
+    #[param(value_type = Option<String>, format = DateTime)]
+    pub due_before: Option<DateTime<Utc>>,
+    #[param(example = 1)]
+    #[validate(range(min = 1, max = 10000, message = "must be between 1 and 10000"))]
+    pub page: Option<u32>,
+    #[param(example = 20)]
+    #[validate(range(min = 1, max = 100, message = "must be between 1 and 100"))]
+    pub per_page: Option<u32>,
+}
+
+impl GetTodosRequest {
+    /// Listing stays unpaginated unless the client asks for a page.
+    pub fn pagination(&self) -> Option<Pagination> {
+        if self.page.is_none() && self.per_page.is_none() {
+            return None;
+        }
+
+        Some(Pagination::from_page(
+            self.page.unwrap_or(1),
+            self.per_page.unwrap_or(DEFAULT_PER_PAGE),
+        ))
+    }
+}
+
+impl From<&GetTodosRequest> for TodoFilter {
+    fn from(value: &GetTodosRequest) -> Self {
+        filter(value.is_done, value.due_after, value.due_before)
+    }
+}
+
+#[derive(Deserialize, Validate, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct ExportTodosRequest {
+    pub format: ExportFormat,
+    /// Filter in the query syntax, e.g. `is:open due:<7d "quarterly report"`.
+    /// Todos have no tags, so `tag:` is refused.
+    #[param(example = "is:open due:<7d")]
+    #[validate(length(max = 500, message = "must be at most 500 characters"))]
+    pub query: Option<String>,
+    #[param(example = false)]
+    pub is_done: Option<bool>,
+    #[param(value_type = Option<String>, format = DateTime)]
+    pub due_after: Option<DateTime<Utc>>,
+    #[param(value_type = Option<String>, format = DateTime)]
+    pub due_before: Option<DateTime<Utc>>,
+}
+
+impl From<&ExportTodosRequest> for TodoFilter {
+    fn from(value: &ExportTodosRequest) -> Self {
+        filter(value.is_done, value.due_after, value.due_before)
+    }
 }
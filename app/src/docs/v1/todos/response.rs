@@ -3,11 +3,12 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, FixedOffset, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
-use super::Todo;
+use super::{Todo, TodoSearchPage, TodoSearchResult, TodoSuggestion};
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -20,11 +21,32 @@ pub struct TodoResponse {
     pub description: String,
     #[schema(example = false)]
     pub is_done: bool,
-    pub due_date: DateTime<Utc>,
+    /// In the zone asked for with the `Time-Zone` header, else the todo's own,
+    /// else UTC. All-day todos are always shown in their own zone so that the
+    /// date stays the same.
+    #[schema(value_type = String, format = DateTime, example = "2023-11-05T17:00:00+01:00")]
+    pub due_date: DateTime<FixedOffset>,
+    #[schema(example = "Europe/Berlin")]
+    pub timezone: Option<String>,
+    #[schema(example = false)]
+    pub all_day: bool,
+    #[schema(example = json!(["1h", "1d"]))]
+    pub reminders: Vec<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+impl TodoResponse {
+    /// Renders the due date in the caller's time zone, see `due_date`.
+    pub fn in_timezone(mut self, timezone: Option<Tz>) -> Self {
+        if let (Some(timezone), false) = (timezone, self.all_day) {
+            self.due_date = self.due_date.with_timezone(&timezone).fixed_offset();
+        }
+
+        self
+    }
+}
+
 impl IntoResponse for TodoResponse {
     fn into_response(self) -> Response {
         (StatusCode::OK, Json(self)).into_response()
@@ -38,9 +60,139 @@ impl From<Todo> for TodoResponse {
             subject: value.subject,
             description: value.description,
             is_done: value.is_done,
-            due_date: value.due_date,
+            due_date: match value.timezone {
+                Some(timezone) => value.due_date.with_timezone(&timezone).fixed_offset(),
+                None => value.due_date.fixed_offset(),
+            },
+            timezone: value.timezone.map(|tz| tz.name().to_string()),
+            all_day: value.all_day,
+            reminders: value.reminders.iter().map(ToString::to_string).collect(),
             created_at: value.created_at,
             updated_at: value.updated_at,
         }
     }
 }
+
+/// HTML with the stored text escaped, the `<mark>` tags around the matched
+/// terms are the only markup in it.
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHighlightsResponse {
+    /// Escaped subject with every matched term wrapped in `<mark>` tags
+    #[schema(example = "Buy <mark>groceries</mark>")]
+    pub subject: Option<String>,
+    /// Escaped description with every matched term wrapped in `<mark>` tags
+    #[schema(example = "Buy <mark>groceries</mark> from the supermarket for the weekend.")]
+    pub description: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResultResponse {
+    #[serde(flatten)]
+    pub todo: TodoResponse,
+    /// BM25 relevance, matches on the subject weigh twice as much as the description
+    #[schema(example = 1.42)]
+    pub score: f64,
+    pub highlights: SearchHighlightsResponse,
+}
+
+impl From<TodoSearchResult> for SearchResultResponse {
+    fn from(value: TodoSearchResult) -> Self {
+        Self {
+            todo: value.todo.into(),
+            score: value.score,
+            highlights: SearchHighlightsResponse {
+                subject: value.subject_highlight,
+                description: value.description_highlight,
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusFacetResponse {
+    #[schema(example = 12)]
+    pub open: u64,
+    #[schema(example = 30)]
+    pub done: u64,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchFacetsResponse {
+    /// Matches per done state, ignoring the `isDone` filter
+    pub status: StatusFacetResponse,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchTodosResponse {
+    pub results: Vec<SearchResultResponse>,
+    /// Number of matches across all pages
+    #[schema(example = 42)]
+    pub total: u64,
+    #[schema(example = 1)]
+    pub page: u32,
+    #[schema(example = 20)]
+    pub per_page: u32,
+    pub facets: SearchFacetsResponse,
+}
+
+impl IntoResponse for SearchTodosResponse {
+    fn into_response(self) -> Response {
+        (StatusCode::OK, Json(self)).into_response()
+    }
+}
+
+impl SearchTodosResponse {
+    pub fn in_timezone(mut self, timezone: Option<Tz>) -> Self {
+        self.results = self
+            .results
+            .into_iter()
+            .map(|mut result| {
+                result.todo = result.todo.in_timezone(timezone);
+
+                result
+            })
+            .collect();
+
+        self
+    }
+}
+
+impl From<TodoSearchPage> for SearchTodosResponse {
+    fn from(value: TodoSearchPage) -> Self {
+        Self {
+            results: value.results.into_iter().map(Into::into).collect(),
+            total: value.total,
+            page: value.pagination.page(),
+            per_page: value.pagination.limit,
+            facets: SearchFacetsResponse {
+                status: StatusFacetResponse {
+                    open: value.open_count,
+                    done: value.done_count,
+                },
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoSuggestionResponse {
+    #[schema(example = "01HDWDYAF9NWCR985TDBZYCDN8")]
+    pub id: String,
+    #[schema(example = "Buy groceries")]
+    pub subject: String,
+}
+
+impl From<TodoSuggestion> for TodoSuggestionResponse {
+    fn from(value: TodoSuggestion) -> Self {
+        Self {
+            id: value.id.to_string(),
+            subject: value.subject,
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::{Datetime as SurrealDbDateTime, Thing};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ViewModel {
+    pub id: Thing,
+    pub name: String,
+    pub query: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+pub struct ViewModelUpdate {
+    pub name: String,
+    pub query: String,
+    pub updated_at: SurrealDbDateTime,
+}
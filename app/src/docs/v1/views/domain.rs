@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+
+#[derive(Debug)]
+pub struct View {
+    pub id: String,
+    pub name: String,
+    pub query: String,
+    pub is_virtual: bool,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Built-in views, their queries are resolved against the clock on every read.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VirtualView {
+    Today,
+    Overdue,
+    Upcoming,
+    Completed,
+}
+
+impl VirtualView {
+    pub fn all() -> [VirtualView; 4] {
+        [
+            VirtualView::Today,
+            VirtualView::Overdue,
+            VirtualView::Upcoming,
+            VirtualView::Completed,
+        ]
+    }
+
+    pub fn from_id(id: &str) -> Option<VirtualView> {
+        Self::all().into_iter().find(|view| view.id() == id)
+    }
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            VirtualView::Today => "today",
+            VirtualView::Overdue => "overdue",
+            VirtualView::Upcoming => "upcoming",
+            VirtualView::Completed => "completed",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            VirtualView::Today => "Today",
+            VirtualView::Overdue => "Overdue",
+            VirtualView::Upcoming => "Upcoming",
+            VirtualView::Completed => "Completed",
+        }
+    }
+
+    pub fn query(&self) -> &'static str {
+        match self {
+            VirtualView::Today => "is:open due:today",
+            VirtualView::Overdue => "is:overdue",
+            VirtualView::Upcoming => "is:open due:>today",
+            VirtualView::Completed => "is:done",
+        }
+    }
+}
+
+impl From<VirtualView> for View {
+    fn from(value: VirtualView) -> Self {
+        Self {
+            id: value.id().to_string(),
+            name: value.name().to_string(),
+            query: value.query().to_string(),
+            is_virtual: true,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+}
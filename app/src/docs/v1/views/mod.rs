@@ -0,0 +1,9 @@
+pub mod database;
+pub mod domain;
+pub mod request;
+pub mod response;
+
+pub use database::*;
+pub use domain::*;
+pub use request::*;
+pub use response::*;
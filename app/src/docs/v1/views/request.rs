@@ -0,0 +1,56 @@
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+
+use crate::docs::v1::todos::Pagination;
+
+static DEFAULT_PER_PAGE: u32 = 20;
+
+#[derive(Deserialize, Validate, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateViewRequest {
+    #[validate(length(min = 1, max = 100))]
+    #[schema(example = "Overdue work")]
+    pub name: String,
+    /// Filter in the query syntax of `GET /v1/todos`
+    #[validate(length(min = 1, max = 500))]
+    #[schema(example = "is:overdue work")]
+    pub query: String,
+}
+
+#[derive(Deserialize, Validate, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateViewRequest {
+    #[validate(length(min = 1, max = 100))]
+    #[schema(example = "This week")]
+    pub name: Option<String>,
+    #[validate(length(min = 1, max = 500))]
+    #[schema(example = "is:open due:>=today due:<7d")]
+    pub query: Option<String>,
+}
+
+#[derive(Deserialize, Validate, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct GetViewTodosRequest {
+    #[param(example = 1)]
+    #[validate(range(min = 1, max = 10000, message = "must be between 1 and 10000"))]
+    pub page: Option<u32>,
+    #[param(example = 20)]
+    #[validate(range(min = 1, max = 100, message = "must be between 1 and 100"))]
+    pub per_page: Option<u32>,
+}
+
+impl GetViewTodosRequest {
+    /// Like listing todos, a view is only paginated when a page is asked for.
+    pub fn pagination(&self) -> Option<Pagination> {
+        if self.page.is_none() && self.per_page.is_none() {
+            return None;
+        }
+
+        Some(Pagination::from_page(
+            self.page.unwrap_or(1),
+            self.per_page.unwrap_or(DEFAULT_PER_PAGE),
+        ))
+    }
+}
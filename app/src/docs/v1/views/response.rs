@@ -0,0 +1,46 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::View;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ViewResponse {
+    /// A ULID for saved views, a name such as `today` for the built-in ones
+    #[schema(example = "01HDWDYAF9NWCR985TDBZYCDN8")]
+    pub id: String,
+    #[schema(example = "Overdue work")]
+    pub name: String,
+    #[schema(example = "is:overdue work")]
+    pub query: String,
+    /// Built-in views are computed on the fly and cannot be changed
+    #[schema(example = false)]
+    pub is_virtual: bool,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl IntoResponse for ViewResponse {
+    fn into_response(self) -> Response {
+        (StatusCode::OK, Json(self)).into_response()
+    }
+}
+
+impl From<View> for ViewResponse {
+    fn from(value: View) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            query: value.query,
+            is_virtual: value.is_virtual,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        }
+    }
+}
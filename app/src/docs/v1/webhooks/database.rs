@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::{Datetime as SurrealDbDateTime, Thing};
+
+use super::DeliveryStatus;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WebhookModel {
+    pub id: Thing,
+    pub url: String,
+    pub events: Vec<String>,
+    pub secret: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+pub struct WebhookModelUpdate {
+    pub url: String,
+    pub events: Vec<String>,
+    pub secret: String,
+    pub updated_at: SurrealDbDateTime,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct WebhookDeliveryModel {
+    pub id: Thing,
+    pub webhook: Thing,
+    pub event: String,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_status_code: Option<u16>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+/// The payload is kept as the exact text that is signed and sent, so every
+/// retry carries the same body.
+#[derive(Serialize, Debug)]
+pub struct WebhookDeliveryModelInsert {
+    /// Derived from the event, a second insert for the same event is ignored
+    pub id: String,
+    pub webhook: Thing,
+    pub event: String,
+    pub payload: String,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    pub next_attempt_at: SurrealDbDateTime,
+    pub created_at: SurrealDbDateTime,
+}
+
+/// A claimed delivery joined with where and how to send it. The webhook
+/// fields are empty when the subscription was deleted in the meantime.
+#[derive(Deserialize, Debug)]
+pub struct PendingDeliveryModel {
+    pub id: Thing,
+    pub event: String,
+    pub payload: String,
+    pub attempts: u32,
+    pub url: Option<String>,
+    pub secret: Option<String>,
+}
+
+/// Outcome of one attempt, written back under the claim it was made with.
+#[derive(Debug)]
+pub struct DeliveryAttemptModel {
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_status_code: Option<u16>,
+    pub last_error: Option<String>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
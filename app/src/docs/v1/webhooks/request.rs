@@ -0,0 +1,44 @@
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+
+use super::DeliveryStatus;
+
+#[derive(Deserialize, Validate, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateWebhookRequest {
+    /// An http or https URL on a public address, redirects are not followed
+    #[validate(url)]
+    #[schema(example = "https://example.com/hooks/todos")]
+    pub url: String,
+    /// Events to receive such as `todo.created`, or `*` for every event
+    #[validate(length(min = 1, max = 10))]
+    #[schema(example = json!(["todo.created", "todo.completed"]))]
+    pub events: Vec<String>,
+    /// Key the deliveries are signed with, a random one is made when left out
+    #[validate(length(min = 16, max = 256))]
+    #[schema(example = "c2b7e1f04a9d4e3b8f6a5c0d1e2f3a4b")]
+    pub secret: Option<String>,
+}
+
+#[derive(Deserialize, Validate, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateWebhookRequest {
+    #[validate(url)]
+    #[schema(example = "https://example.com/hooks/todos")]
+    pub url: Option<String>,
+    #[validate(length(min = 1, max = 10))]
+    #[schema(example = json!(["*"]))]
+    pub events: Option<Vec<String>>,
+    /// Rotates the signing key, deliveries already queued are signed with the new one
+    #[validate(length(min = 16, max = 256))]
+    #[schema(example = "0f1e2d3c4b5a69788796a5b4c3d2e1f0")]
+    pub secret: Option<String>,
+}
+
+#[derive(Deserialize, Validate, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct GetDeliveriesRequest {
+    pub status: Option<DeliveryStatus>,
+}
@@ -0,0 +1,112 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::docs::v1::{events::FieldChange, todos::TodoResponse};
+
+use super::{DeliveryStatus, Webhook, WebhookDelivery};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookResponse {
+    #[schema(example = "01HDWDYAF9NWCR985TDBZYCDN8")]
+    pub id: String,
+    #[schema(example = "https://example.com/hooks/todos")]
+    pub url: String,
+    #[schema(example = json!(["todo.created", "todo.completed"]))]
+    pub events: Vec<String>,
+    /// Only returned when the secret is set, it cannot be read back later
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "c2b7e1f04a9d4e3b8f6a5c0d1e2f3a4b")]
+    pub secret: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl IntoResponse for WebhookResponse {
+    fn into_response(self) -> Response {
+        (StatusCode::OK, Json(self)).into_response()
+    }
+}
+
+impl From<Webhook> for WebhookResponse {
+    fn from(value: Webhook) -> Self {
+        Self {
+            id: value.id,
+            url: value.url,
+            events: value.events,
+            secret: value.secret,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDeliveryResponse {
+    #[schema(example = "x8b3kq0m2v9d7c1f4n6p")]
+    pub id: String,
+    #[schema(example = "01HDWDYAF9NWCR985TDBZYCDN8")]
+    pub webhook_id: String,
+    #[schema(example = "todo.created")]
+    pub event: String,
+    pub status: DeliveryStatus,
+    #[schema(example = 1)]
+    pub attempts: u32,
+    pub next_attempt_at: DateTime<Utc>,
+    /// HTTP status the receiver answered the last attempt with
+    #[schema(example = 503)]
+    pub last_status_code: Option<u16>,
+    #[schema(example = "receiver answered with 503 Service Unavailable")]
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+impl IntoResponse for WebhookDeliveryResponse {
+    fn into_response(self) -> Response {
+        (StatusCode::OK, Json(self)).into_response()
+    }
+}
+
+impl From<WebhookDelivery> for WebhookDeliveryResponse {
+    fn from(value: WebhookDelivery) -> Self {
+        Self {
+            id: value.id,
+            webhook_id: value.webhook_id,
+            event: value.event,
+            status: value.status,
+            attempts: value.attempts,
+            next_attempt_at: value.next_attempt_at,
+            last_status_code: value.last_status_code,
+            last_error: value.last_error,
+            created_at: value.created_at,
+            delivered_at: value.delivered_at,
+        }
+    }
+}
+
+/// Body POSTed to a webhook. It is signed with the webhook's secret, the
+/// `Webhook-Signature` header holds `sha256=` followed by the hex HMAC-SHA256
+/// of the `Webhook-Timestamp` header, a dot and the body.
+#[derive(Serialize, Deserialize, PartialEq, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookPayload {
+    /// Same for every delivery of one event, receivers can use it to drop duplicates
+    #[schema(example = "01HDWDYAF9NWCR985TDBZYCDN8_todo.completed")]
+    pub id: String,
+    #[schema(example = "todo.completed")]
+    pub event: String,
+    pub occurred_at: DateTime<Utc>,
+    /// The todo after the change, or as it was when it got deleted
+    pub todo: TodoResponse,
+    /// Only sent with `todo.updated`
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub changes: Option<Vec<FieldChange>>,
+}
@@ -0,0 +1,171 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware,
+    response::{IntoResponse, Response},
+    routing, Router,
+};
+use ulid::Ulid;
+
+use crate::{
+    common::ApplicationError,
+    docs::v1::calendar::CalendarFeedResponse,
+    resource::v1::todos::TodoRepository,
+    util::{require_api_key, sha256_hex, ApiKey, Clock, IdGenerator},
+};
+
+use super::CalendarService;
+
+pub static CALENDAR_TAG: &str = "Calendar";
+
+pub struct CalendarController<R: TodoRepository, C: Clock, G: IdGenerator<Ulid>> {
+    prefix: Option<String>,
+    service: Option<CalendarService<R, C, G>>,
+    api_key: ApiKey,
+}
+
+impl<R: TodoRepository, C: Clock, G: IdGenerator<Ulid>> Default for CalendarController<R, C, G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: TodoRepository, C: Clock, G: IdGenerator<Ulid>> CalendarController<R, C, G> {
+    pub fn new() -> Self {
+        Self {
+            prefix: None,
+            service: None,
+            api_key: ApiKey::default(),
+        }
+    }
+
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = Some(prefix.to_string());
+
+        self
+    }
+
+    pub fn with_service(mut self, service: CalendarService<R, C, G>) -> Self {
+        self.service = Some(service);
+
+        self
+    }
+
+    /// Rotating the feed token breaks every subscription, so it takes this
+    /// key. Without one it is refused.
+    pub fn with_api_key(mut self, api_key: ApiKey) -> Self {
+        self.api_key = api_key;
+
+        self
+    }
+
+    pub fn build(self) -> Router {
+        let prefix = self.prefix.expect("prefix not set");
+        let service = Arc::new(self.service.expect("service not set"));
+
+        let router = Router::new()
+            .route(
+                "/token",
+                routing::post(rotate_calendar_token).route_layer(middleware::from_fn_with_state(
+                    self.api_key,
+                    require_api_key,
+                )),
+            )
+            .route("/:file", routing::get(get_calendar_feed))
+            .with_state(service);
+
+        Router::new().nest(&prefix, router)
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/calendar/token",
+    params(
+        ("x-api-key" = String, Header, description = "The ADMIN_API_KEY, or send it as a bearer token"),
+    ),
+    responses(
+        (status = StatusCode::OK, description = "Issue a new secret feed URL, the previous one stops working", body = CalendarFeedResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Missing or wrong API key", body = Problem),
+        (status = StatusCode::INTERNAL_SERVER_ERROR, description = "Internal server error", body = Problem),
+    ),
+    tag = CALENDAR_TAG
+)]
+pub async fn rotate_calendar_token<R, C, G>(
+    State(service): State<Arc<CalendarService<R, C, G>>>,
+) -> Result<CalendarFeedResponse, ApplicationError>
+where
+    R: TodoRepository,
+    C: Clock,
+    G: IdGenerator<Ulid>,
+{
+    let token = service.rotate_token().await?;
+
+    Ok(CalendarFeedResponse {
+        url: format!("/v1/calendar/{}.ics", &token),
+        token,
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/calendar/{token}.ics",
+    params(
+        ("token", Path, description = "Secret feed token"),
+        ("If-None-Match" = Option<String>, Header, description = "ETag of a previously fetched feed"),
+    ),
+    responses(
+        (status = StatusCode::OK, description = "Open Todos as VEVENT and VTODO entries at their due date", content_type = "text/calendar", body = String),
+        (status = StatusCode::NOT_MODIFIED, description = "Feed unchanged since the given ETag"),
+        (status = StatusCode::NOT_FOUND, description = "Unknown or rotated token", body = Problem),
+        (status = StatusCode::INTERNAL_SERVER_ERROR, description = "Internal server error", body = Problem),
+    ),
+    tag = CALENDAR_TAG
+)]
+pub async fn get_calendar_feed<R, C, G>(
+    State(service): State<Arc<CalendarService<R, C, G>>>,
+    Path(file): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, ApplicationError>
+where
+    R: TodoRepository,
+    C: Clock,
+    G: IdGenerator<Ulid>,
+{
+    let Some(token) = file.strip_suffix(".ics") else {
+        return Err(ApplicationError::NotFound(String::from("calendar feed")));
+    };
+
+    let feed = service.get_feed(token).await?;
+    let etag = format!("\"{}\"", sha256_hex(feed.as_bytes()));
+
+    let is_fresh = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| {
+            value
+                .split(',')
+                .map(|tag| tag.trim().trim_start_matches("W/"))
+                .any(|tag| tag == etag || tag == "*")
+        });
+
+    if is_fresh {
+        return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response());
+    }
+
+    Ok((
+        StatusCode::OK,
+        [
+            (
+                header::CONTENT_TYPE,
+                String::from("text/calendar; charset=utf-8"),
+            ),
+            (header::CACHE_CONTROL, String::from("private, no-cache")),
+            (header::ETAG, etag),
+        ],
+        feed,
+    )
+        .into_response())
+}
@@ -0,0 +1,7 @@
+pub mod controller;
+pub mod repository;
+pub mod service;
+
+pub use controller::*;
+pub use repository::*;
+pub use service::*;
@@ -0,0 +1,74 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use surrealdb::sql::Datetime;
+
+use crate::{
+    common::{DatabaseDriver, RepositoryError},
+    docs::v1::calendar::CalendarFeedModel,
+};
+
+type RepositoryResult<T> = Result<T, RepositoryError>;
+
+static FEED_ID: &str = "default";
+
+#[derive(Serialize)]
+struct CalendarFeedModelUpdate {
+    token_hash: String,
+    created_at: Datetime,
+}
+
+#[derive(Clone)]
+pub struct CalendarRepository {
+    pub driver: DatabaseDriver,
+}
+
+impl CalendarRepository {
+    pub fn new(driver: DatabaseDriver) -> Self {
+        Self { driver }
+    }
+
+    pub async fn find_by_token_hash(
+        &self,
+        token_hash: &str,
+    ) -> RepositoryResult<CalendarFeedModel> {
+        let mut response = self
+            .driver
+            .client
+            .query("SELECT * FROM calendar_feed WHERE token_hash = $token_hash LIMIT 1")
+            .bind(("token_hash", token_hash))
+            .await?;
+
+        let result: Option<CalendarFeedModel> = response.take(0)?;
+
+        if let Some(feed) = result {
+            return Ok(feed);
+        }
+
+        Err(RepositoryError::NotFound(String::from("calendar feed")))
+    }
+
+    /// Replaces the feed token, which invalidates every previously shared URL.
+    pub async fn save_token_hash(
+        &self,
+        token_hash: String,
+        created_at: DateTime<Utc>,
+    ) -> RepositoryResult<CalendarFeedModel> {
+        let feed: Option<CalendarFeedModel> = self
+            .driver
+            .client
+            .update(("calendar_feed", FEED_ID))
+            .content(CalendarFeedModelUpdate {
+                token_hash,
+                created_at: Datetime(created_at),
+            })
+            .await?;
+
+        if let Some(feed) = feed {
+            return Ok(feed);
+        }
+
+        Err(RepositoryError::InsertError(String::from(
+            "Calendar feed not returned after saving its token",
+        )))
+    }
+}
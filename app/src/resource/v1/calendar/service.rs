@@ -0,0 +1,81 @@
+use ulid::Ulid;
+
+use crate::{
+    common::ApplicationError,
+    docs::v1::todos::{vevent, vtodo, TodoFilter, TodoResponse},
+    resource::v1::todos::{TodoRepository, TodoService},
+    util::{calendar_footer, calendar_header, random_token, sha256_hex, Clock, IdGenerator},
+};
+
+use super::CalendarRepository;
+
+type ServiceResult<T> = Result<T, ApplicationError>;
+
+static TOKEN_BYTES: usize = 32;
+
+pub struct CalendarService<R, C, G>
+where
+    R: TodoRepository,
+    C: Clock,
+    G: IdGenerator<Ulid>,
+{
+    repository: CalendarRepository,
+    todo_service: TodoService<R, C, G>,
+    clock: C,
+}
+
+impl<R, C, G> CalendarService<R, C, G>
+where
+    R: TodoRepository,
+    C: Clock,
+    G: IdGenerator<Ulid>,
+{
+    pub fn new(
+        repository: CalendarRepository,
+        todo_service: TodoService<R, C, G>,
+        clock: C,
+    ) -> Self {
+        Self {
+            repository,
+            todo_service,
+            clock,
+        }
+    }
+
+    /// Issues a new feed token. Only its hash is stored, so the token is
+    /// returned once and cannot be read back later.
+    pub async fn rotate_token(&self) -> ServiceResult<String> {
+        let token = random_token(TOKEN_BYTES);
+
+        self.repository
+            .save_token_hash(sha256_hex(token.as_bytes()), self.clock.now())
+            .await?;
+
+        Ok(token)
+    }
+
+    pub async fn get_feed(&self, token: &str) -> ServiceResult<String> {
+        self.repository
+            .find_by_token_hash(&sha256_hex(token.as_bytes()))
+            .await?;
+
+        let filter = TodoFilter {
+            is_done: Some(false),
+            ..TodoFilter::default()
+        };
+        let todos = self.todo_service.get_todos(&filter, None).await?;
+
+        let mut feed = calendar_header("Todos");
+
+        for todo in todos {
+            let todo = TodoResponse::from(todo);
+
+            feed.push_str(&vevent(&todo).render());
+            feed.push_str(&vtodo(&todo).render());
+        }
+
+        feed.push_str(&calendar_footer());
+
+        Ok(feed)
+    }
+}
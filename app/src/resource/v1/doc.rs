@@ -1,11 +1,18 @@
 use chrono::Utc;
 use utoipa::{OpenApi, ToSchema};
 
-use super::todos;
-use crate::{common::error, docs::v1::todos as todo_doc};
+use super::{calendar, todos, views, webhooks};
+use crate::{
+    common::error,
+    docs::v1::{
+        calendar as calendar_doc, events as event_doc, todos as todo_doc, views as view_doc,
+        webhooks as webhook_doc,
+    },
+};
 
 #[derive(ToSchema)]
 #[schema(example = Utc::now, format = "date-time")]
+#[allow(dead_code)]
 struct DateTime(String);
 
 #[derive(OpenApi)]
@@ -16,7 +23,25 @@ struct DateTime(String);
         todos::get_todo_by_id,
         todos::update_todo,
         todos::delete_todo,
-        todos::search_todo
+        todos::search_todo,
+        todos::suggest_todos,
+        todos::export_todos,
+        todos::import_todos,
+        calendar::rotate_calendar_token,
+        calendar::get_calendar_feed,
+        views::get_views,
+        views::get_view,
+        views::create_view,
+        views::update_view,
+        views::delete_view,
+        views::get_view_todos,
+        webhooks::get_webhooks,
+        webhooks::get_webhook,
+        webhooks::create_webhook,
+        webhooks::update_webhook,
+        webhooks::delete_webhook,
+        webhooks::get_deliveries,
+        webhooks::redeliver
     ),
     components(
         schemas(
@@ -24,12 +49,36 @@ struct DateTime(String);
             todo_doc::CreateTodoRequest,
             todo_doc::UpdateTodoRequest,
             todo_doc::SearchTodoRequest,
+            todo_doc::SearchResultResponse,
+            todo_doc::SearchHighlightsResponse,
+            todo_doc::SearchTodosResponse,
+            todo_doc::SearchFacetsResponse,
+            todo_doc::StatusFacetResponse,
+            todo_doc::TodoSuggestionResponse,
+            todo_doc::ExportFormat,
+            todo_doc::ImportFormat,
+            todo_doc::ImportTodosResponse,
+            todo_doc::ImportRowProblem,
+            calendar_doc::CalendarFeedResponse,
+            view_doc::ViewResponse,
+            view_doc::CreateViewRequest,
+            view_doc::UpdateViewRequest,
+            webhook_doc::WebhookResponse,
+            webhook_doc::CreateWebhookRequest,
+            webhook_doc::UpdateWebhookRequest,
+            webhook_doc::WebhookDeliveryResponse,
+            webhook_doc::DeliveryStatus,
+            webhook_doc::WebhookPayload,
+            event_doc::FieldChange,
             error::Problem,
             self::DateTime,
         )
     ),
     tags(
-        (name = "Todo", description = "Endpoints for manipulating todo resource")
+        (name = "Todo", description = "Endpoints for manipulating todo resource"),
+        (name = "Calendar", description = "Subscribable iCalendar feed of open todos"),
+        (name = "View", description = "Saved and built-in todo queries"),
+        (name = "Webhook", description = "Signed callbacks for todo changes, retried until delivered")
     ),
     info(
         title = "Axum REST API template",
@@ -0,0 +1,74 @@
+use std::{fmt, sync::Arc};
+
+use axum::async_trait;
+use tracing::warn;
+
+use crate::{common::RepositoryError, docs::v1::events::DomainEvent};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubscriberError(pub String);
+
+impl fmt::Display for SubscriberError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<RepositoryError> for SubscriberError {
+    fn from(value: RepositoryError) -> Self {
+        match value {
+            RepositoryError::Connection(err)
+            | RepositoryError::Query(err)
+            | RepositoryError::InsertError(err) => Self(err),
+            RepositoryError::NotFound(id) => Self(format!("{} does not exist", id)),
+        }
+    }
+}
+
+/// Reacts to todo events. An event is published again when any subscriber
+/// failed it, so handling one must be safe to repeat for the same event id.
+#[async_trait]
+pub trait EventSubscriber: Send + Sync + 'static {
+    /// Shown in the logs when the subscriber fails.
+    fn name(&self) -> &'static str;
+
+    async fn handle(&self, event: &DomainEvent) -> Result<(), SubscriberError>;
+}
+
+/// Hands every event to the subscribers in the order they were registered.
+#[derive(Clone, Default)]
+pub struct EventBus {
+    subscribers: Vec<Arc<dyn EventSubscriber>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe(&mut self, subscriber: Arc<dyn EventSubscriber>) {
+        self.subscribers.push(subscriber);
+    }
+
+    /// Returns whether every subscriber handled every event. A failing
+    /// subscriber does not keep the others from seeing the event.
+    pub async fn publish(&self, events: &[DomainEvent]) -> bool {
+        let mut handled = true;
+
+        for event in events {
+            for subscriber in &self.subscribers {
+                if let Err(err) = subscriber.handle(event).await {
+                    warn!(
+                        "{} failed to handle event {}: {}",
+                        subscriber.name(),
+                        event.id,
+                        err
+                    );
+                    handled = false;
+                }
+            }
+        }
+
+        handled
+    }
+}
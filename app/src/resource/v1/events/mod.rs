@@ -0,0 +1,9 @@
+pub mod bus;
+pub mod publisher;
+pub mod relay;
+pub mod repository;
+
+pub use bus::*;
+pub use publisher::*;
+pub use relay::*;
+pub use repository::*;
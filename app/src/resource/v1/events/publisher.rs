@@ -0,0 +1,88 @@
+use chrono::{DateTime, Utc};
+use surrealdb::sql::Thing;
+
+use crate::{
+    common::ApplicationError,
+    docs::v1::{
+        events::{ChangeKind, DomainEvent, OutboxModel, TodoEvent},
+        todos::{Todo, TodoModel},
+    },
+};
+
+use super::{EventBus, OutboxRepository};
+
+type ServiceResult<T> = Result<T, ApplicationError>;
+
+/// Turns recorded changes into events on the bus.
+#[derive(Clone)]
+pub struct EventPublisher {
+    repository: OutboxRepository,
+    bus: EventBus,
+}
+
+impl EventPublisher {
+    pub fn new(repository: OutboxRepository, bus: EventBus) -> Self {
+        Self { repository, bus }
+    }
+
+    /// Publishes the events of a change and marks it processed once every
+    /// subscriber handled them, returning whether it did. Otherwise the
+    /// change stays in the outbox for the [`super::OutboxRelay`] to retry.
+    pub async fn publish(&self, change: &OutboxModel, now: DateTime<Utc>) -> ServiceResult<bool> {
+        let events = events_from_change(change).map_err(|err| {
+            ApplicationError::ServerError(vec![format!(
+                "Outbox change {} cannot be read: {}",
+                change.id, err
+            )])
+        })?;
+
+        self.publish_events(&change.id, &events, now).await
+    }
+
+    pub async fn publish_events(
+        &self,
+        change_id: &Thing,
+        events: &[DomainEvent],
+        now: DateTime<Utc>,
+    ) -> ServiceResult<bool> {
+        if !self.bus.publish(events).await {
+            return Ok(false);
+        }
+
+        self.repository.mark_processed(change_id, now).await?;
+
+        Ok(true)
+    }
+}
+
+/// Event ids are made of the change and the kind of event, so they stay the
+/// same when a change is published again.
+pub fn events_from_change(change: &OutboxModel) -> Result<Vec<DomainEvent>, String> {
+    let events = match change.change {
+        ChangeKind::Created => vec![TodoEvent::TodoCreated {
+            todo: snapshot(&change.after)?,
+        }],
+        ChangeKind::Updated => {
+            TodoEvent::from_update(&snapshot(&change.before)?, snapshot(&change.after)?)
+        }
+        ChangeKind::Deleted => vec![TodoEvent::TodoDeleted {
+            todo: snapshot(&change.before)?,
+        }],
+    };
+
+    Ok(events
+        .into_iter()
+        .map(|event| DomainEvent {
+            id: format!("{}_{}", change.id.id.to_raw(), event.kind().name()),
+            occurred_at: change.occurred_at,
+            event,
+        })
+        .collect())
+}
+
+fn snapshot(model: &Option<TodoModel>) -> Result<Todo, String> {
+    match model {
+        Some(model) => Todo::try_from(model.clone()),
+        None => Err(String::from("the todo snapshot is missing")),
+    }
+}
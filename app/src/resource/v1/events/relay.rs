@@ -0,0 +1,144 @@
+use std::time::Duration;
+
+use chrono::Duration as ChronoDuration;
+use tokio::{
+    task::JoinHandle,
+    time::{interval, MissedTickBehavior},
+};
+use tracing::{error, info};
+
+use crate::{
+    common::ApplicationError,
+    util::{random_token, Clock, Shutdown},
+};
+
+use super::{events_from_change, EventPublisher, OutboxRepository};
+
+type ServiceResult<T> = Result<T, ApplicationError>;
+
+pub static DEFAULT_OUTBOX_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Changes are published by the request that made them. Only the ones still
+/// unpublished this long after are picked up by the relay.
+static RELAY_DELAY_SECONDS: i64 = 60;
+
+/// Changes claimed, and published one after the other, per tick.
+static CLAIM_BATCH_SIZE: usize = 100;
+
+/// How long a claimed change may stay unpublished before it is assumed the
+/// relay holding it stopped, and another one takes it over. Well above the
+/// time a batch takes to publish, so a live relay never loses a claim to
+/// another and both publish it.
+static CLAIM_TIMEOUT_MINUTES: i64 = 5;
+
+/// Published changes are kept this long for troubleshooting.
+static RETENTION_DAYS: i64 = 7;
+
+/// Background task that publishes the changes a request recorded but could
+/// not publish, because a subscriber failed or the server stopped in
+/// between. Together with the outbox this makes every todo change reach
+/// every subscriber at least once.
+pub struct OutboxRelay<C: Clock> {
+    repository: OutboxRepository,
+    clock: C,
+    publisher: EventPublisher,
+    interval: Duration,
+    shutdown: Shutdown,
+}
+
+impl<C: Clock> OutboxRelay<C> {
+    pub fn new(repository: OutboxRepository, clock: C, publisher: EventPublisher) -> Self {
+        Self {
+            repository,
+            clock,
+            publisher,
+            interval: DEFAULT_OUTBOX_INTERVAL,
+            shutdown: Shutdown::new(),
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+
+        self
+    }
+
+    /// Stops the spawned task between ticks once `shutdown` is triggered.
+    pub fn with_shutdown(mut self, shutdown: Shutdown) -> Self {
+        self.shutdown = shutdown;
+
+        self
+    }
+
+    /// Publishes a batch of the changes left unpublished by the clock's now,
+    /// returning how many were. The rest are left for the next ticks.
+    pub async fn tick(&self) -> ServiceResult<usize> {
+        let now = self.clock.now();
+        let claim = random_token(16);
+
+        let changes = self
+            .repository
+            .claim_unprocessed(
+                &claim,
+                now,
+                now - ChronoDuration::seconds(RELAY_DELAY_SECONDS),
+                now - ChronoDuration::minutes(CLAIM_TIMEOUT_MINUTES),
+                CLAIM_BATCH_SIZE,
+            )
+            .await?;
+
+        let mut published = 0;
+
+        for change in changes {
+            let events = match events_from_change(&change) {
+                Ok(events) => events,
+                Err(err) => {
+                    // It will not read any better on the next tick.
+                    error!("Dropping outbox change {}: {}", change.id, err);
+
+                    self.repository
+                        .mark_processed(&change.id, self.clock.now())
+                        .await?;
+                    continue;
+                }
+            };
+
+            let handled = self
+                .publisher
+                .publish_events(&change.id, &events, self.clock.now())
+                .await?;
+
+            match handled {
+                true => published += 1,
+                false => self.repository.release(&change.id, &claim).await?,
+            }
+        }
+
+        self.repository
+            .delete_processed(now - ChronoDuration::days(RETENTION_DAYS))
+            .await?;
+
+        Ok(published)
+    }
+
+    /// Runs [`Self::tick`] on the interval until shut down or aborted.
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticks = interval(self.interval);
+            ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                tokio::select! {
+                    _ = ticks.tick() => {}
+                    _ = self.shutdown.triggered() => break,
+                }
+
+                if let Err(err) = self.tick().await {
+                    error!("Outbox relay tick failed: {:?}", err);
+                }
+            }
+
+            info!("Outbox relay stopped");
+        })
+    }
+}
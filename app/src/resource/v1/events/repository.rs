@@ -0,0 +1,111 @@
+use chrono::{DateTime, Utc};
+use surrealdb::sql::{Datetime, Thing};
+
+use crate::{
+    common::{DatabaseDriver, RepositoryError},
+    docs::v1::events::OutboxModel,
+};
+
+type RepositoryResult<T> = Result<T, RepositoryError>;
+
+/// Reads the outbox the todo repository writes to. Rows are written in the
+/// same transaction as the todo change they record.
+#[derive(Clone)]
+pub struct OutboxRepository {
+    pub driver: DatabaseDriver,
+}
+
+impl OutboxRepository {
+    pub fn new(driver: DatabaseDriver) -> Self {
+        Self { driver }
+    }
+
+    /// Claims up to `limit` of the changes that occurred before
+    /// `ready_before` and were never published, the oldest first. Claims
+    /// older than `stale_before` were left behind by a relay that stopped and
+    /// are taken over.
+    pub async fn claim_unprocessed(
+        &self,
+        claim: &str,
+        now: DateTime<Utc>,
+        ready_before: DateTime<Utc>,
+        stale_before: DateTime<Utc>,
+        limit: usize,
+    ) -> RepositoryResult<Vec<OutboxModel>> {
+        let mut response = self
+            .driver
+            .client
+            .query(
+                r#"
+                BEGIN TRANSACTION;
+                LET $due = (
+                    SELECT id, occurred_at FROM outbox
+                    WHERE processed_at = NONE
+                        AND occurred_at < $ready_before
+                        AND (claimed_at = NONE OR claimed_at < $stale_before)
+                    ORDER BY occurred_at
+                    LIMIT $limit
+                );
+                UPDATE $due.id SET claimed_by = $claim, claimed_at = $now;
+                RETURN (
+                    SELECT * FROM outbox
+                    WHERE claimed_by = $claim AND processed_at = NONE
+                    ORDER BY occurred_at
+                );
+                COMMIT TRANSACTION;
+                "#,
+            )
+            .bind(("claim", claim))
+            .bind(("now", Datetime(now)))
+            .bind(("ready_before", Datetime(ready_before)))
+            .bind(("stale_before", Datetime(stale_before)))
+            .bind(("limit", limit))
+            .await?;
+
+        let result: Vec<OutboxModel> = response.take(0)?;
+
+        Ok(result)
+    }
+
+    pub async fn mark_processed(
+        &self,
+        id: &Thing,
+        processed_at: DateTime<Utc>,
+    ) -> RepositoryResult<()> {
+        self.driver
+            .client
+            .query(
+                "UPDATE $id SET processed_at = $processed_at, claimed_by = NONE, claimed_at = NONE",
+            )
+            .bind(("id", id))
+            .bind(("processed_at", Datetime(processed_at)))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// Gives the change back so that the next tick retries it.
+    pub async fn release(&self, id: &Thing, claim: &str) -> RepositoryResult<()> {
+        self.driver
+            .client
+            .query("UPDATE $id SET claimed_by = NONE, claimed_at = NONE WHERE claimed_by = $claim")
+            .bind(("id", id))
+            .bind(("claim", claim))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    pub async fn delete_processed(&self, processed_before: DateTime<Utc>) -> RepositoryResult<()> {
+        self.driver
+            .client
+            .query("DELETE outbox WHERE processed_at != NONE AND processed_at < $processed_before")
+            .bind(("processed_before", Datetime(processed_before)))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+}
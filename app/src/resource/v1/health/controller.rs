@@ -2,14 +2,19 @@ use std::sync::Arc;
 
 use axum::{extract::State, routing, Router};
 
-use crate::{common::ApplicationError, docs::v1::health::HealthStatusResponse};
+use crate::{
+    common::Application,
+    docs::v1::health::{HealthStatusResponse, ProbeResponse},
+};
 
 use super::service::HealthService;
 
 pub struct HealthController {
     prefix: Option<String>,
     service: Option<HealthService>,
+    app: Option<Application>,
 }
+
 impl Default for HealthController {
     fn default() -> Self {
         Self::new()
@@ -21,6 +26,7 @@ impl HealthController {
         Self {
             prefix: None,
             service: None,
+            app: None,
         }
     }
 
@@ -36,28 +42,44 @@ impl HealthController {
         self
     }
 
+    /// Name and version reported by the detailed health check.
+    pub fn with_app(mut self, app: Application) -> Self {
+        self.app = Some(app);
+
+        self
+    }
+
+    /// The detailed health check under the prefix, and the `/livez` and
+    /// `/readyz` probes at the root.
     pub fn build(self) -> Router {
         let prefix = self.prefix.expect("prefix not set");
         let service = Arc::new(self.service.expect("service not set"));
+        let app = Arc::new(self.app.expect("app not set"));
 
         let router = Router::new()
             .route("/", routing::get(health_status))
+            .with_state((service.clone(), app));
+        let probes = Router::new()
+            .route("/livez", routing::get(liveness))
+            .route("/readyz", routing::get(readiness))
             .with_state(service);
 
-        Router::new().nest(&prefix, router)
+        Router::new().nest(&prefix, router).merge(probes)
     }
 }
 
 async fn health_status(
-    State(service): State<Arc<HealthService>>,
-) -> Result<HealthStatusResponse, ApplicationError> {
-    let database_status = service
-        .check_service_health()
-        .await
-        .map_or(String::from("NOK"), |_| String::from("OK"));
-
-    Ok(HealthStatusResponse {
-        api: String::from("OK"),
-        database: database_status,
-    })
+    State((service, app)): State<(Arc<HealthService>, Arc<Application>)>,
+) -> HealthStatusResponse {
+    HealthStatusResponse::new(service.report().await, &app)
+}
+
+/// Only tells the process is up and serving, restarting it would not help
+/// with a dependency being down.
+async fn liveness() -> ProbeResponse {
+    ProbeResponse::alive()
+}
+
+async fn readiness(State(service): State<Arc<HealthService>>) -> ProbeResponse {
+    service.report().await.into()
 }
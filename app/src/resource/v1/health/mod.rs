@@ -1,7 +1,9 @@
 pub mod controller;
+pub mod readiness;
 pub mod repository;
 pub mod service;
 
 pub use controller::*;
+pub use readiness::*;
 pub use repository::*;
 pub use service::*;
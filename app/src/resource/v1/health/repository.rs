@@ -1,20 +1,54 @@
-use crate::common::{DatabaseDriver, RepositoryError};
+use migration::Migrator;
+
+use crate::{
+    common::{DatabaseDriver, RepositoryError},
+    util::Metrics,
+};
 
 #[derive(Clone)]
 pub struct HealthRepository {
     pub driver: DatabaseDriver,
+    metrics: Option<Metrics>,
 }
 
 impl HealthRepository {
     pub async fn check(&self) -> Result<(), RepositoryError> {
-        self.driver.client.health().await?;
+        let query = self.driver.client.health();
+
+        match &self.metrics {
+            Some(metrics) => metrics.time_query("health", "check", query).await?,
+            None => query.await?,
+        }
 
         Ok(())
     }
+
+    /// Latest migration applied to the database.
+    pub async fn schema_version(&self) -> Result<u32, RepositoryError> {
+        let migrator = Migrator::new(&self.driver.client);
+        let query = migrator.current_version();
+
+        let version = match &self.metrics {
+            Some(metrics) => metrics.time_query("health", "schema_version", query).await,
+            None => query.await,
+        };
+
+        version.map_err(|err| RepositoryError::Query(err.to_string()))
+    }
 }
 
 impl HealthRepository {
     pub fn new(driver: DatabaseDriver) -> Self {
-        Self { driver }
+        Self {
+            driver,
+            metrics: None,
+        }
+    }
+
+    /// Records how long the health check query takes.
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+
+        self
     }
 }
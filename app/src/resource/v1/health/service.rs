@@ -1,19 +1,90 @@
-use crate::common::ApplicationError;
+use std::time::Instant;
 
-use super::repository::HealthRepository;
+use migration::latest_version;
+use tracing::warn;
+
+use crate::docs::v1::health::{DependencyCheck, HealthReport};
+
+use super::{repository::HealthRepository, Readiness};
 
 pub struct HealthService {
     repository: HealthRepository,
+    readiness: Readiness,
+    started_at: Instant,
 }
 
 impl HealthService {
-    pub fn new(repository: HealthRepository) -> Self {
-        Self { repository }
+    pub fn new(repository: HealthRepository, readiness: Readiness) -> Self {
+        Self {
+            repository,
+            readiness,
+            started_at: Instant::now(),
+        }
+    }
+
+    pub async fn report(&self) -> HealthReport {
+        let (database, migrations) = tokio::join!(self.check_database(), self.check_migrations());
+
+        HealthReport {
+            database,
+            migrations,
+            draining: self.readiness.is_draining(),
+            uptime: self.started_at.elapsed(),
+        }
     }
 
-    pub async fn check_service_health(&self) -> Result<(), ApplicationError> {
-        self.repository.check().await?;
+    async fn check_database(&self) -> DependencyCheck {
+        let started = Instant::now();
+        let result = self.repository.check().await;
+        let latency = started.elapsed();
+
+        if let Err(err) = &result {
+            warn!("Database health check failed: {}", err);
+        }
+
+        DependencyCheck {
+            healthy: result.is_ok(),
+            latency,
+            detail: None,
+        }
+    }
+
+    /// Healthy when every migration this binary knows is applied. A newer
+    /// schema is healthy too, as while a deploy rolls out the instances still
+    /// running the previous binary keep serving, it is only pointed out.
+    async fn check_migrations(&self) -> DependencyCheck {
+        let started = Instant::now();
+        let result = self.repository.schema_version().await;
+        let latency = started.elapsed();
+        let expected = latest_version();
+
+        let (healthy, detail) = match result {
+            Ok(version) if version == expected => (true, None),
+            Ok(version) if version > expected => (
+                true,
+                Some(format!(
+                    "Database schema is at version {}, newer than the {} this binary knows",
+                    version, expected
+                )),
+            ),
+            Ok(version) => (
+                false,
+                Some(format!(
+                    "Database schema is at version {} but {} is expected",
+                    version, expected
+                )),
+            ),
+            Err(err) => {
+                warn!("Migration health check failed: {}", err);
+
+                (false, None)
+            }
+        };
 
-        Ok(())
+        DependencyCheck {
+            healthy,
+            latency,
+            detail,
+        }
     }
 }
@@ -1,5 +1,10 @@
+pub mod calendar;
 pub mod doc;
+pub mod events;
 pub mod health;
+pub mod reminders;
 pub mod todos;
+pub mod views;
+pub mod webhooks;
 
 pub use doc::*;
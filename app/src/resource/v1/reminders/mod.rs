@@ -0,0 +1,7 @@
+pub mod notifier;
+pub mod repository;
+pub mod scheduler;
+
+pub use notifier::*;
+pub use repository::*;
+pub use scheduler::*;
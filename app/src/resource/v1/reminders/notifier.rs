@@ -0,0 +1,96 @@
+use std::{fmt, time::Duration};
+
+use axum::async_trait;
+use tracing::info;
+
+use crate::{common::Config, docs::v1::reminders::Reminder};
+
+static WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotifierError(pub String);
+
+impl fmt::Display for NotifierError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Delivers due reminders. A failed delivery is retried on the next tick of
+/// the scheduler.
+#[async_trait]
+pub trait Notifier: Send + Sync + 'static {
+    async fn notify(&self, reminder: &Reminder) -> Result<(), NotifierError>;
+}
+
+/// Writes reminders to the log, used when no webhook is configured.
+#[derive(Clone, Default)]
+pub struct LoggingNotifier;
+
+#[async_trait]
+impl Notifier for LoggingNotifier {
+    async fn notify(&self, reminder: &Reminder) -> Result<(), NotifierError> {
+        info!(
+            reminder_id = %reminder.id,
+            todo_id = %reminder.todo_id,
+            subject = %reminder.subject,
+            due_date = %reminder.due_date.to_rfc3339(),
+            "Reminder is due"
+        );
+
+        Ok(())
+    }
+}
+
+/// POSTs every reminder as JSON to a URL. The reminder id is sent as the
+/// `Idempotency-Key` header so the receiver can drop a redelivery.
+#[derive(Clone)]
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: &str) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .expect("reminder webhook client builds");
+
+        Self {
+            client,
+            url: url.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    async fn notify(&self, reminder: &Reminder) -> Result<(), NotifierError> {
+        let response = self
+            .client
+            .post(&self.url)
+            .header("idempotency-key", &reminder.id)
+            .json(reminder)
+            .send()
+            .await
+            .map_err(|err| NotifierError(err.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(NotifierError(format!(
+                "webhook answered with {}",
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// The webhook notifier when `REMINDER_WEBHOOK_URL` is set, else the logging one.
+pub fn notifier_from_config(config: &Config) -> Box<dyn Notifier> {
+    match &config.reminder_webhook_url {
+        Some(url) => Box::new(WebhookNotifier::new(url)),
+        None => Box::new(LoggingNotifier),
+    }
+}
@@ -0,0 +1,118 @@
+use chrono::{DateTime, Utc};
+use surrealdb::sql::{Datetime, Thing};
+
+use crate::{
+    common::{DatabaseDriver, RepositoryError},
+    docs::v1::reminders::ReminderModel,
+};
+
+type RepositoryResult<T> = Result<T, RepositoryError>;
+
+#[derive(Clone)]
+pub struct ReminderRepository {
+    pub driver: DatabaseDriver,
+}
+
+impl ReminderRepository {
+    pub fn new(driver: DatabaseDriver) -> Self {
+        Self { driver }
+    }
+
+    /// Claims up to `limit` reminders of open todos that are due by `now` and
+    /// not yet sent, the longest overdue first. Claims older than
+    /// `stale_before` were left behind by a scheduler that stopped
+    /// mid-delivery and are taken over.
+    pub async fn claim_due_reminders(
+        &self,
+        claim: &str,
+        now: DateTime<Utc>,
+        stale_before: DateTime<Utc>,
+        limit: usize,
+    ) -> RepositoryResult<Vec<ReminderModel>> {
+        let mut response = self
+            .driver
+            .client
+            .query(
+                r#"
+                BEGIN TRANSACTION;
+                LET $due = (
+                    SELECT id, remind_at FROM reminder
+                    WHERE remind_at <= $now
+                        AND sent_at = NONE
+                        AND todo.is_done = false
+                        AND (claimed_at = NONE OR claimed_at < $stale_before)
+                    ORDER BY remind_at
+                    LIMIT $limit
+                );
+                UPDATE $due.id SET claimed_by = $claim, claimed_at = $now;
+                RETURN (
+                    SELECT
+                        id,
+                        remind_at,
+                        todo AS todo_id,
+                        todo.subject AS subject,
+                        todo.due_date AS due_date
+                    FROM reminder
+                    WHERE claimed_by = $claim AND sent_at = NONE
+                    ORDER BY remind_at
+                );
+                COMMIT TRANSACTION;
+                "#,
+            )
+            .bind(("claim", claim))
+            .bind(("now", Datetime(now)))
+            .bind(("stale_before", Datetime(stale_before)))
+            .bind(("limit", limit))
+            .await?;
+
+        let result: Vec<ReminderModel> = response.take(0)?;
+
+        Ok(result)
+    }
+
+    /// Records the delivery, unless the claim was lost in the meantime.
+    pub async fn mark_sent(
+        &self,
+        id: &Thing,
+        claim: &str,
+        sent_at: DateTime<Utc>,
+    ) -> RepositoryResult<()> {
+        self.driver
+            .client
+            .query("UPDATE $id SET sent_at = $sent_at WHERE claimed_by = $claim")
+            .bind(("id", id))
+            .bind(("claim", claim))
+            .bind(("sent_at", Datetime(sent_at)))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// Gives the reminder back so that the next tick retries it.
+    pub async fn release(&self, id: &Thing, claim: &str) -> RepositoryResult<()> {
+        self.driver
+            .client
+            .query("UPDATE $id SET claimed_by = NONE, claimed_at = NONE WHERE claimed_by = $claim")
+            .bind(("id", id))
+            .bind(("claim", claim))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// Deletes a reminder that can never be delivered, unless the claim was
+    /// lost in the meantime.
+    pub async fn discard(&self, id: &Thing, claim: &str) -> RepositoryResult<()> {
+        self.driver
+            .client
+            .query("DELETE $id WHERE claimed_by = $claim")
+            .bind(("id", id))
+            .bind(("claim", claim))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+}
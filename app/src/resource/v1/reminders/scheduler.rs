@@ -0,0 +1,153 @@
+use std::time::Duration;
+
+use chrono::Duration as ChronoDuration;
+use tokio::{
+    task::JoinHandle,
+    time::{interval, MissedTickBehavior},
+};
+use tracing::{error, info, warn};
+use ulid::Ulid;
+
+use crate::{
+    common::ApplicationError,
+    docs::v1::reminders::{Reminder, ReminderModel},
+    util::{random_token, Clock, Shutdown},
+};
+
+use super::{Notifier, ReminderRepository};
+
+type ServiceResult<T> = Result<T, ApplicationError>;
+
+pub static DEFAULT_REMINDER_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Reminders claimed, and sent one after the other, per tick.
+static CLAIM_BATCH_SIZE: usize = 20;
+
+/// How long a claimed reminder may stay undelivered before it is assumed the
+/// scheduler holding it stopped, and another one takes it over. Well above
+/// the notifier's timeout for every reminder of a batch, so a live scheduler
+/// never loses a claim to another and both notify.
+static CLAIM_TIMEOUT_MINUTES: i64 = 5;
+
+/// Background task that delivers due reminders through a [`Notifier`].
+///
+/// Every tick claims the due reminders in the database before notifying, and
+/// records each delivery, so that a reminder is sent once even when several
+/// servers run or one restarts. Only a crash between the notification and
+/// recording it can repeat a delivery, which is why reminders carry a stable
+/// id for the receiver to deduplicate on.
+pub struct ReminderScheduler<C: Clock> {
+    repository: ReminderRepository,
+    clock: C,
+    notifier: Box<dyn Notifier>,
+    interval: Duration,
+    shutdown: Shutdown,
+}
+
+impl<C: Clock> ReminderScheduler<C> {
+    pub fn new(repository: ReminderRepository, clock: C, notifier: Box<dyn Notifier>) -> Self {
+        Self {
+            repository,
+            clock,
+            notifier,
+            interval: DEFAULT_REMINDER_INTERVAL,
+            shutdown: Shutdown::new(),
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+
+        self
+    }
+
+    /// Stops the spawned task between ticks once `shutdown` is triggered.
+    pub fn with_shutdown(mut self, shutdown: Shutdown) -> Self {
+        self.shutdown = shutdown;
+
+        self
+    }
+
+    /// Delivers a batch of the reminders that are due by the clock's now,
+    /// returning how many were sent. The rest are left for the next ticks.
+    pub async fn tick(&self) -> ServiceResult<usize> {
+        let now = self.clock.now();
+        let claim = random_token(16);
+
+        let due_reminders = self
+            .repository
+            .claim_due_reminders(
+                &claim,
+                now,
+                now - ChronoDuration::minutes(CLAIM_TIMEOUT_MINUTES),
+                CLAIM_BATCH_SIZE,
+            )
+            .await?;
+
+        let mut sent = 0;
+
+        for model in due_reminders {
+            let id = model.id.clone();
+
+            // Claimed again once stale and skipped forever otherwise.
+            let Some(reminder) = model_to_domain(model) else {
+                error!(
+                    "Reminder {} points at an invalid todo id, discarding it",
+                    id
+                );
+
+                self.repository.discard(&id, &claim).await?;
+                continue;
+            };
+
+            match self.notifier.notify(&reminder).await {
+                Ok(()) => {
+                    self.repository
+                        .mark_sent(&id, &claim, self.clock.now())
+                        .await?;
+                    sent += 1;
+                }
+                Err(err) => {
+                    warn!("Reminder {} could not be delivered: {}", reminder.id, err);
+
+                    self.repository.release(&id, &claim).await?;
+                }
+            }
+        }
+
+        Ok(sent)
+    }
+
+    /// Runs [`Self::tick`] on the interval until shut down or aborted.
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticks = interval(self.interval);
+            ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                tokio::select! {
+                    _ = ticks.tick() => {}
+                    _ = self.shutdown.triggered() => break,
+                }
+
+                if let Err(err) = self.tick().await {
+                    error!("Reminder scheduler tick failed: {:?}", err);
+                }
+            }
+
+            info!("Reminder scheduler stopped");
+        })
+    }
+}
+
+fn model_to_domain(model: ReminderModel) -> Option<Reminder> {
+    let todo_id = Ulid::from_string(&model.todo_id.id.to_raw()).ok()?;
+
+    Some(Reminder {
+        id: model.id.id.to_raw(),
+        todo_id,
+        subject: model.subject,
+        due_date: model.due_date,
+        remind_at: model.remind_at,
+    })
+}
@@ -1,14 +1,23 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Path, State},
-    routing, Json, Router,
+    body::{Body, Bytes, StreamBody},
+    extract::{FromRequest, Multipart, Path, State},
+    http::{header, Request, StatusCode},
+    response::{IntoResponse, Response},
+    routing, BoxError, Json, Router,
 };
+use futures::{future, stream, StreamExt};
+use tracing::error;
 use ulid::Ulid;
 
 use crate::{
-    common::{ApplicationError, ValidatedBody, ValidatedQuery},
-    docs::v1::todos::{CreateTodoRequest, SearchTodoRequest, TodoResponse, UpdateTodoRequest},
+    common::{ApplicationError, RequestTimezone, ValidatedBody, ValidatedQuery},
+    docs::v1::todos::{
+        CreateTodoRequest, ExportTodosRequest, GetTodosRequest, ImportFormat, ImportTodosRequest,
+        ImportTodosResponse, SearchTodoRequest, SearchTodosResponse, SuggestTodoRequest,
+        TodoFilter, TodoResponse, TodoSuggestionResponse, UpdateTodoRequest,
+    },
     util::{Clock, IdGenerator},
 };
 
@@ -58,6 +67,9 @@ impl<R: TodoRepository, C: Clock, G: IdGenerator<Ulid>> TodoController<R, C, G>
             .route("/:id", routing::patch(update_todo))
             .route("/:id", routing::delete(delete_todo))
             .route("/search", routing::get(search_todo))
+            .route("/suggest", routing::get(suggest_todos))
+            .route("/export", routing::get(export_todos))
+            .route("/import", routing::post(import_todos))
             .with_state(service);
 
         Router::new().nest(&prefix, router)
@@ -67,8 +79,10 @@ impl<R: TodoRepository, C: Clock, G: IdGenerator<Ulid>> TodoController<R, C, G>
 #[utoipa::path(
     get,
     path = "/v1/todos",
+    params(GetTodosRequest),
     responses(
-        (status = StatusCode::OK, description = "Get all Todos", body = [TodoResponse]),
+        (status = StatusCode::OK, description = "Get all Todos, optionally filtered and paginated", body = [TodoResponse]),
+        (status = StatusCode::BAD_REQUEST, description = "Invalid filter, query or pagination", body = Problem),
         (status = StatusCode::NOT_FOUND, description = "Resource not found", body = Problem),
         (status = StatusCode::INTERNAL_SERVER_ERROR, description = "Internal server error", body = Problem),
     ),
@@ -76,15 +90,27 @@ impl<R: TodoRepository, C: Clock, G: IdGenerator<Ulid>> TodoController<R, C, G>
 )]
 pub async fn get_todos<R, C, G>(
     State(service): State<Arc<TodoService<R, C, G>>>,
+    RequestTimezone(timezone): RequestTimezone,
+    ValidatedQuery(query): ValidatedQuery<GetTodosRequest>,
 ) -> Result<Json<Vec<TodoResponse>>, ApplicationError>
 where
     R: TodoRepository,
     C: Clock,
     G: IdGenerator<Ulid>,
 {
-    let todos = service.get_todos().await?;
+    let mut filter = TodoFilter::from(&query);
+    filter.query = query
+        .query
+        .as_deref()
+        .map(|q| service.parse_query(q, timezone))
+        .transpose()?;
 
-    let result: Vec<TodoResponse> = todos.into_iter().map(|t| t.into()).collect();
+    let todos = service.get_todos(&filter, query.pagination()).await?;
+
+    let result: Vec<TodoResponse> = todos
+        .into_iter()
+        .map(|t| TodoResponse::from(t).in_timezone(timezone))
+        .collect();
 
     Ok(Json(result))
 }
@@ -102,6 +128,7 @@ where
 )]
 pub async fn get_todo_by_id<R, C, G>(
     State(service): State<Arc<TodoService<R, C, G>>>,
+    RequestTimezone(timezone): RequestTimezone,
     Path(todo_id): Path<String>,
 ) -> Result<TodoResponse, ApplicationError>
 where
@@ -111,7 +138,7 @@ where
 {
     let todo = service.get_todo_by_id(&todo_id).await?;
 
-    Ok(todo.into())
+    Ok(TodoResponse::from(todo).in_timezone(timezone))
 }
 
 #[utoipa::path(
@@ -127,6 +154,7 @@ where
 )]
 pub async fn create_todo<R, C, G>(
     State(service): State<Arc<TodoService<R, C, G>>>,
+    RequestTimezone(timezone): RequestTimezone,
     ValidatedBody(data): ValidatedBody<CreateTodoRequest>,
 ) -> Result<TodoResponse, ApplicationError>
 where
@@ -134,9 +162,9 @@ where
     C: Clock,
     G: IdGenerator<Ulid>,
 {
-    let todo = service.create_todo(data).await?;
+    let todo = service.create_todo(data, timezone).await?;
 
-    Ok(todo.into())
+    Ok(TodoResponse::from(todo).in_timezone(timezone))
 }
 
 #[utoipa::path(
@@ -152,6 +180,7 @@ where
 )]
 pub async fn delete_todo<R, C, G>(
     State(service): State<Arc<TodoService<R, C, G>>>,
+    RequestTimezone(timezone): RequestTimezone,
     Path(todo_id): Path<String>,
 ) -> Result<TodoResponse, ApplicationError>
 where
@@ -161,7 +190,7 @@ where
 {
     let todo = service.delete_todo(&todo_id).await?;
 
-    Ok(todo.into())
+    Ok(TodoResponse::from(todo).in_timezone(timezone))
 }
 
 #[utoipa::path(
@@ -178,6 +207,7 @@ where
 )]
 pub async fn update_todo<R, C, G>(
     State(service): State<Arc<TodoService<R, C, G>>>,
+    RequestTimezone(timezone): RequestTimezone,
     Path(todo_id): Path<String>,
     ValidatedBody(update_data): ValidatedBody<UpdateTodoRequest>,
 ) -> Result<TodoResponse, ApplicationError>
@@ -186,9 +216,9 @@ where
     C: Clock,
     G: IdGenerator<Ulid>,
 {
-    let todo = service.update_todo(&todo_id, update_data).await?;
+    let todo = service.update_todo(&todo_id, update_data, timezone).await?;
 
-    Ok(todo.into())
+    Ok(TodoResponse::from(todo).in_timezone(timezone))
 }
 
 #[utoipa::path(
@@ -196,24 +226,187 @@ where
     path = "/v1/todos/search",
     params(SearchTodoRequest),
     responses(
-        (status = StatusCode::OK, description = "Search for Todos based on subject adn description fields", body = [TodoResponse]),
-        (status = StatusCode::NOT_FOUND, description = "Resource not found", body = Problem),
+        (status = StatusCode::OK, description = "Search for Todos based on subject and description fields, best matches first", body = SearchTodosResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Missing search term or invalid filter", body = Problem),
         (status = StatusCode::INTERNAL_SERVER_ERROR, description = "Internal server error", body = Problem),
     ),
     tag = TODO_TAG
 )]
 pub async fn search_todo<R, C, G>(
     State(service): State<Arc<TodoService<R, C, G>>>,
-    ValidatedQuery(SearchTodoRequest { q }): ValidatedQuery<SearchTodoRequest>,
-) -> Result<Json<Vec<TodoResponse>>, ApplicationError>
+    RequestTimezone(timezone): RequestTimezone,
+    ValidatedQuery(query): ValidatedQuery<SearchTodoRequest>,
+) -> Result<SearchTodosResponse, ApplicationError>
 where
     R: TodoRepository,
     C: Clock,
     G: IdGenerator<Ulid>,
 {
-    let todos = service.search_todo(&q).await?;
+    let page = service
+        .search_todo(&query.q, &TodoFilter::from(&query), query.pagination())
+        .await?;
 
-    let result: Vec<TodoResponse> = todos.into_iter().map(|t| t.into()).collect();
+    Ok(SearchTodosResponse::from(page).in_timezone(timezone))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/todos/suggest",
+    params(SuggestTodoRequest),
+    responses(
+        (status = StatusCode::OK, description = "Subjects of the Todos whose words start with the typed prefix, best matches first. Empty when the lookup runs out of time", body = [TodoSuggestionResponse]),
+        (status = StatusCode::BAD_REQUEST, description = "Missing prefix or invalid limit", body = Problem),
+        (status = StatusCode::INTERNAL_SERVER_ERROR, description = "Internal server error", body = Problem),
+    ),
+    tag = TODO_TAG
+)]
+pub async fn suggest_todos<R, C, G>(
+    State(service): State<Arc<TodoService<R, C, G>>>,
+    ValidatedQuery(query): ValidatedQuery<SuggestTodoRequest>,
+) -> Result<Json<Vec<TodoSuggestionResponse>>, ApplicationError>
+where
+    R: TodoRepository,
+    C: Clock,
+    G: IdGenerator<Ulid>,
+{
+    let suggestions = service.suggest_todos(&query.prefix, query.limit()).await?;
+
+    let result: Vec<TodoSuggestionResponse> = suggestions.into_iter().map(Into::into).collect();
 
     Ok(Json(result))
 }
+
+#[utoipa::path(
+    get,
+    path = "/v1/todos/export",
+    params(ExportTodosRequest),
+    responses(
+        (status = StatusCode::OK, description = "Stream every matching Todo in the requested format", content(
+            ("text/csv" = String),
+            ("application/x-ndjson" = String),
+            ("text/calendar" = String),
+        )),
+        (status = StatusCode::BAD_REQUEST, description = "Unknown format, invalid filter or query", body = Problem),
+        (status = StatusCode::INTERNAL_SERVER_ERROR, description = "Internal server error", body = Problem),
+    ),
+    tag = TODO_TAG
+)]
+pub async fn export_todos<R, C, G>(
+    State(service): State<Arc<TodoService<R, C, G>>>,
+    RequestTimezone(timezone): RequestTimezone,
+    ValidatedQuery(query): ValidatedQuery<ExportTodosRequest>,
+) -> Result<Response, ApplicationError>
+where
+    R: TodoRepository,
+    C: Clock,
+    G: IdGenerator<Ulid>,
+{
+    let format = query.format;
+
+    let mut filter = TodoFilter::from(&query);
+    filter.query = query
+        .query
+        .as_deref()
+        .map(|q| service.parse_query(q, timezone))
+        .transpose()?;
+
+    let rows = service.stream_todos(filter).map(move |todo| match todo {
+        Ok(todo) => Ok(Bytes::from(
+            format.render(&TodoResponse::from(todo).in_timezone(timezone)),
+        )),
+        Err(err) => {
+            error!("Todo export interrupted: {:?}", err);
+
+            Err(BoxError::from("export interrupted"))
+        }
+    });
+
+    let body = stream::once(future::ready(Ok(Bytes::from(format.header()))))
+        .chain(rows)
+        .chain(stream::once(future::ready(Ok(Bytes::from(
+            format.footer(),
+        )))));
+
+    let content_disposition = format!("attachment; filename=\"{}\"", format.file_name());
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, content_disposition),
+        ],
+        StreamBody::new(body),
+    )
+        .into_response())
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/todos/import",
+    params(ImportTodosRequest),
+    request_body(content = String, description = "A CSV file, a JSON array of todos or an iCalendar file with VTODO components, sent raw or as the first part of a multipart form", content_type = "text/csv"),
+    responses(
+        (status = StatusCode::OK, description = "Import summary with the problems found in each rejected row", body = ImportTodosResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Unreadable payload or unknown format", body = Problem),
+        (status = StatusCode::INTERNAL_SERVER_ERROR, description = "Internal server error", body = Problem),
+    ),
+    tag = TODO_TAG
+)]
+pub async fn import_todos<R, C, G>(
+    State(service): State<Arc<TodoService<R, C, G>>>,
+    ValidatedQuery(query): ValidatedQuery<ImportTodosRequest>,
+    request: Request<Body>,
+) -> Result<ImportTodosResponse, ApplicationError>
+where
+    R: TodoRepository,
+    C: Clock,
+    G: IdGenerator<Ulid>,
+{
+    let content_type = request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+
+    let (format, payload) = if content_type.starts_with("multipart/form-data") {
+        let mut multipart = Multipart::from_request(request, &()).await?;
+
+        let Some(field) = multipart.next_field().await? else {
+            return Err(ApplicationError::ValidationError(vec![String::from(
+                "multipart body must contain a file",
+            )]));
+        };
+
+        let format = query
+            .format
+            .or_else(|| {
+                field
+                    .content_type()
+                    .and_then(ImportFormat::from_content_type)
+            })
+            .or_else(|| field.file_name().and_then(ImportFormat::from_file_name));
+
+        (format, field.bytes().await?)
+    } else {
+        let format = query
+            .format
+            .or_else(|| ImportFormat::from_content_type(&content_type));
+
+        (format, Bytes::from_request(request, &()).await?)
+    };
+
+    let Some(format) = format else {
+        return Err(ApplicationError::ValidationError(vec![String::from(
+            "unknown import format, send CSV, JSON or iCalendar content or set the format parameter",
+        )]));
+    };
+
+    let rows = format
+        .parse(&payload)
+        .map_err(|issue| ApplicationError::ValidationError(vec![issue]))?;
+
+    let summary = service.import_todos(rows, query.dry_run).await?;
+
+    Ok(summary)
+}
@@ -1,141 +1,601 @@
+use std::{collections::BTreeMap, future::Future};
+
 use axum::async_trait;
-use surrealdb::sql::Datetime;
+use chrono::{DateTime, Utc};
+use surrealdb::sql::{Datetime, Thing, Value};
+use tracing::instrument;
 use ulid::Ulid;
 
 use crate::{
     common::{DatabaseDriver, RepositoryError},
-    docs::v1::todos::{Todo, TodoModel, TodoModelUpdate},
+    docs::v1::events::OutboxModel,
+    docs::v1::reminders::ReminderModelInsert,
+    docs::v1::todos::{
+        Pagination, Todo, TodoFilter, TodoModel, TodoModelInsert, TodoModelUpdate, TodoSearchModel,
+        TodoStatusCountModel, TodoSuggestionModel,
+    },
+    util::{Metrics, QueryExpr, TextField, HIGHLIGHT_END, HIGHLIGHT_START},
 };
 
+type Bindings = BTreeMap<String, Value>;
+
 type RepositoryResult<T> = Result<T, RepositoryError>;
 
+/// Every write records the change in the outbox within the same transaction
+/// and returns it, holding the todo before and after the write.
 #[async_trait]
 pub trait TodoRepository: Send + Sync + 'static {
-    async fn create_todo(&self, todo: Todo) -> RepositoryResult<TodoModel>;
-    async fn get_todos(&self) -> RepositoryResult<Vec<TodoModel>>;
+    async fn create_todo(&self, todo: Todo) -> RepositoryResult<OutboxModel>;
+    async fn create_todos(&self, todos: Vec<Todo>) -> RepositoryResult<Vec<OutboxModel>>;
+    async fn get_todos(
+        &self,
+        filter: &TodoFilter,
+        pagination: Option<Pagination>,
+    ) -> RepositoryResult<Vec<TodoModel>>;
     async fn get_todo_by_id(&self, id: &Ulid) -> RepositoryResult<TodoModel>;
     async fn update_todo(
         &self,
         id: &Ulid,
         updated_todo: TodoModelUpdate,
-    ) -> RepositoryResult<TodoModel>;
-    async fn delete_todo(&self, id: &Ulid) -> RepositoryResult<TodoModel>;
-    async fn search_todo(&self, q: &str) -> RepositoryResult<Vec<TodoModel>>;
+        occurred_at: DateTime<Utc>,
+    ) -> RepositoryResult<OutboxModel>;
+    async fn delete_todo(
+        &self,
+        id: &Ulid,
+        occurred_at: DateTime<Utc>,
+    ) -> RepositoryResult<OutboxModel>;
+    async fn search_todo(
+        &self,
+        q: &str,
+        filter: &TodoFilter,
+        pagination: Pagination,
+    ) -> RepositoryResult<Vec<TodoSearchModel>>;
+    async fn count_search_by_status(
+        &self,
+        q: &str,
+        filter: &TodoFilter,
+    ) -> RepositoryResult<Vec<TodoStatusCountModel>>;
+    async fn suggest_todos(
+        &self,
+        prefix: &str,
+        limit: u32,
+    ) -> RepositoryResult<Vec<TodoSuggestionModel>>;
 }
 
 pub struct TodoRepositoryImpl {
     pub driver: DatabaseDriver,
+    metrics: Option<Metrics>,
 }
 
 #[async_trait]
 impl TodoRepository for TodoRepositoryImpl {
-    async fn get_todos(&self) -> RepositoryResult<Vec<TodoModel>> {
-        let mut response = self.driver.client.query("SELECT * FROM todo").await?;
+    #[instrument(name = "todo_repository.get_todos", skip_all)]
+    async fn get_todos(
+        &self,
+        filter: &TodoFilter,
+        pagination: Option<Pagination>,
+    ) -> RepositoryResult<Vec<TodoModel>> {
+        self.timed("get_todos", async {
+            let mut bindings = Bindings::new();
+            let query = format!(
+                "SELECT * FROM todo {} ORDER BY id {}",
+                where_clause(&filter_conditions(filter, &mut bindings)),
+                limit_clause(pagination)
+            );
+
+            let mut response = self
+                .driver
+                .client
+                .query(query)
+                .bind(("is_done", filter.is_done))
+                .bind(("due_after", filter.due_after.map(Datetime)))
+                .bind(("due_before", filter.due_before.map(Datetime)))
+                .bind(("limit", pagination.map(|p| p.limit)))
+                .bind(("offset", pagination.map(|p| p.offset)))
+                .bind(bindings)
+                .await?;
 
-        let result: Vec<TodoModel> = response.take(0)?;
+            let result: Vec<TodoModel> = response.take(0)?;
 
-        Ok(result)
+            Ok(result)
+        })
+        .await
     }
 
+    #[instrument(name = "todo_repository.get_todo_by_id", skip_all, fields(todo.id = %id))]
     async fn get_todo_by_id(&self, id: &Ulid) -> RepositoryResult<TodoModel> {
-        let result: Option<TodoModel> =
-            self.driver.client.select(("todo", &id.to_string())).await?;
+        self.timed("get_todo_by_id", async {
+            let result: Option<TodoModel> =
+                self.driver.client.select(("todo", &id.to_string())).await?;
 
-        if let Some(todo) = result {
-            return Ok(todo);
-        }
+            if let Some(todo) = result {
+                return Ok(todo);
+            }
 
-        Err(RepositoryError::NotFound(id.to_string()))
+            Err(RepositoryError::NotFound(id.to_string()))
+        })
+        .await
     }
 
-    async fn create_todo(&self, todo: Todo) -> RepositoryResult<TodoModel> {
-        let query = r#"
-            CREATE todo CONTENT {
+    #[instrument(name = "todo_repository.create_todo", skip_all, fields(todo.id = %todo.id))]
+    async fn create_todo(&self, todo: Todo) -> RepositoryResult<OutboxModel> {
+        self.timed("create_todo", async {
+            let query = r#"
+            BEGIN TRANSACTION;
+            LET $todo = (CREATE todo CONTENT {
                 id: $id,
                 subject: $subject,
                 description: $description,
                 due_date: $due_date,
+                timezone: $timezone,
+                all_day: $all_day,
+                reminders: $reminders,
                 is_done: $is_done,
                 created_at: $created_at,
                 updated_at: $updated_at,
-            }
+            });
+            LET $change = (CREATE outbox CONTENT {
+                change: 'created',
+                after: $todo[0],
+                occurred_at: $created_at,
+            });
+            RETURN $change;
+            COMMIT TRANSACTION;
         "#;
-        let mut response = self
-            .driver
-            .client
-            .query(query)
-            .bind(("id", todo.id))
-            .bind(("subject", todo.subject))
-            .bind(("description", todo.description))
-            .bind(("due_date", Datetime(todo.due_date)))
-            .bind(("is_done", Some(todo.is_done)))
-            .bind(("created_at", Datetime(todo.created_at)))
-            .bind(("updated_at", Datetime(todo.updated_at)))
-            .await?;
-
-        let result: Option<TodoModel> = response.take(0)?;
-
-        match result {
-            Some(t) => Ok(t),
-            None => Err(RepositoryError::InsertError(format!(
-                "Todo({}) not returned after inserting into the DB",
-                todo.id
-            ))),
-        }
+            let mut response = self
+                .driver
+                .client
+                .query(query)
+                .bind(("id", todo.id))
+                .bind(("subject", todo.subject))
+                .bind(("description", todo.description))
+                .bind(("due_date", Datetime(todo.due_date)))
+                .bind(("timezone", todo.timezone.map(|tz| tz.name())))
+                .bind(("all_day", todo.all_day))
+                .bind((
+                    "reminders",
+                    todo.reminders
+                        .iter()
+                        .map(|r| r.minutes)
+                        .collect::<Vec<u32>>(),
+                ))
+                .bind(("is_done", Some(todo.is_done)))
+                .bind(("created_at", Datetime(todo.created_at)))
+                .bind(("updated_at", Datetime(todo.updated_at)))
+                .await?;
+
+            let result: Option<OutboxModel> = response.take(0)?;
+
+            match result {
+                Some(change) => {
+                    self.schedule_reminders(&change_snapshots(std::slice::from_ref(&change)))
+                        .await?;
+
+                    Ok(change)
+                }
+                None => Err(RepositoryError::InsertError(format!(
+                    "Todo({}) not returned after inserting into the DB",
+                    todo.id
+                ))),
+            }
+        })
+        .await
     }
 
-    async fn delete_todo(&self, id: &Ulid) -> RepositoryResult<TodoModel> {
-        let result: Option<TodoModel> =
-            self.driver.client.delete(("todo", &id.to_string())).await?;
+    #[instrument(name = "todo_repository.create_todos", skip_all, fields(todo.count = todos.len()))]
+    async fn create_todos(&self, todos: Vec<Todo>) -> RepositoryResult<Vec<OutboxModel>> {
+        self.timed("create_todos", async {
+            let records: Vec<TodoModelInsert> = todos.into_iter().map(Into::into).collect();
 
-        if let Some(todo) = result {
-            return Ok(todo);
-        }
+            let mut response = self
+                .driver
+                .client
+                .query(
+                    r#"
+                BEGIN TRANSACTION;
+                LET $inserted = (INSERT INTO todo $todos);
+                LET $changes = (INSERT INTO outbox (
+                    SELECT VALUE {
+                        change: 'created',
+                        after: $this,
+                        occurred_at: created_at,
+                    } FROM $inserted
+                ));
+                RETURN $changes;
+                COMMIT TRANSACTION;
+                "#,
+                )
+                .bind(("todos", records))
+                .await?;
+
+            let result: Vec<OutboxModel> = response.take(0)?;
+
+            self.schedule_reminders(&change_snapshots(&result)).await?;
+
+            Ok(result)
+        })
+        .await
+    }
+
+    #[instrument(name = "todo_repository.delete_todo", skip_all, fields(todo.id = %id))]
+    async fn delete_todo(
+        &self,
+        id: &Ulid,
+        occurred_at: DateTime<Utc>,
+    ) -> RepositoryResult<OutboxModel> {
+        self.timed("delete_todo", async {
+            let mut response = self
+                .driver
+                .client
+                .query(
+                    r#"
+                BEGIN TRANSACTION;
+                LET $todo = type::thing('todo', $id);
+                LET $deleted = (DELETE $todo RETURN BEFORE);
+                DELETE reminder WHERE todo = $todo;
+                LET $change = IF array::len($deleted) > 0 THEN
+                    (CREATE outbox CONTENT {
+                        change: 'deleted',
+                        before: $deleted[0],
+                        occurred_at: $occurred_at,
+                    })
+                ELSE
+                    []
+                END;
+                RETURN $change;
+                COMMIT TRANSACTION;
+                "#,
+                )
+                .bind(("id", id.to_string()))
+                .bind(("occurred_at", Datetime(occurred_at)))
+                .await?;
+
+            let result: Option<OutboxModel> = response.take(0)?;
 
-        Err(RepositoryError::NotFound(id.to_string()))
+            match result {
+                Some(change) => Ok(change),
+                None => Err(RepositoryError::NotFound(id.to_string())),
+            }
+        })
+        .await
     }
 
+    #[instrument(name = "todo_repository.update_todo", skip_all, fields(todo.id = %id))]
     async fn update_todo(
         &self,
         id: &Ulid,
         updated_todo: TodoModelUpdate,
-    ) -> RepositoryResult<TodoModel> {
-        let todo: Option<TodoModel> = self
-            .driver
-            .client
-            .update(("todo", id.to_string()))
-            .merge(updated_todo)
-            .await?;
+        occurred_at: DateTime<Utc>,
+    ) -> RepositoryResult<OutboxModel> {
+        self.timed("update_todo", async {
+            let mut response = self
+                .driver
+                .client
+                .query(
+                    r#"
+                BEGIN TRANSACTION;
+                LET $todo = type::thing('todo', $id);
+                LET $before = (SELECT * FROM $todo);
+                LET $after = IF array::len($before) > 0 THEN
+                    (UPDATE $todo MERGE $update RETURN AFTER)
+                ELSE
+                    []
+                END;
+                LET $change = IF array::len($after) > 0 THEN
+                    (CREATE outbox CONTENT {
+                        change: 'updated',
+                        before: $before[0],
+                        after: $after[0],
+                        occurred_at: $occurred_at,
+                    })
+                ELSE
+                    []
+                END;
+                RETURN $change;
+                COMMIT TRANSACTION;
+                "#,
+                )
+                .bind(("id", id.to_string()))
+                .bind(("update", updated_todo))
+                .bind(("occurred_at", Datetime(occurred_at)))
+                .await?;
 
-        if let Some(todo) = todo {
-            return Ok(todo);
-        }
+            let result: Option<OutboxModel> = response.take(0)?;
+
+            match result {
+                Some(change) => {
+                    self.schedule_reminders(&change_snapshots(std::slice::from_ref(&change)))
+                        .await?;
 
-        Err(RepositoryError::NotFound(id.to_string()))
+                    Ok(change)
+                }
+                None => Err(RepositoryError::NotFound(id.to_string())),
+            }
+        })
+        .await
     }
 
-    async fn search_todo(&self, search_term: &str) -> RepositoryResult<Vec<TodoModel>> {
-        let query = r#"
-            SELECT *, search::score(1) * 2 + search::score(2) AS score FROM todo
-            WHERE subject @1@ $search_term OR description @2@ $search_term
+    #[instrument(name = "todo_repository.search_todo", skip_all)]
+    async fn search_todo(
+        &self,
+        search_term: &str,
+        filter: &TodoFilter,
+        pagination: Pagination,
+    ) -> RepositoryResult<Vec<TodoSearchModel>> {
+        self.timed("search_todo", async {
+            let mut bindings = Bindings::new();
+            let mut conditions = vec![SEARCH_CONDITION.to_string()];
+            conditions.extend(filter_conditions(filter, &mut bindings));
+
+            let query = format!(
+                r#"
+            SELECT *,
+                search::score(1) * 2 + search::score(2) AS score,
+                search::highlight('{start}', '{end}', 1) AS subject_highlight,
+                search::highlight('{start}', '{end}', 2) AS description_highlight
+            FROM todo
+            {where_clause}
             ORDER BY score DESC
+            LIMIT $limit START $offset
+            "#,
+                start = HIGHLIGHT_START,
+                end = HIGHLIGHT_END,
+                where_clause = where_clause(&conditions)
+            );
+
+            let mut response = self
+                .driver
+                .client
+                .query(query)
+                .bind(("search_term", search_term))
+                .bind(("is_done", filter.is_done))
+                .bind(("due_after", filter.due_after.map(Datetime)))
+                .bind(("due_before", filter.due_before.map(Datetime)))
+                .bind(("limit", pagination.limit))
+                .bind(("offset", pagination.offset))
+                .bind(bindings)
+                .await?;
+
+            let result: Vec<TodoSearchModel> = response.take(0)?;
+
+            Ok(result)
+        })
+        .await
+    }
+
+    #[instrument(name = "todo_repository.count_search_by_status", skip_all)]
+    async fn count_search_by_status(
+        &self,
+        search_term: &str,
+        filter: &TodoFilter,
+    ) -> RepositoryResult<Vec<TodoStatusCountModel>> {
+        self.timed("count_search_by_status", async {
+            let mut bindings = Bindings::new();
+            let mut conditions = vec![SEARCH_CONDITION.to_string()];
+            conditions.extend(filter_conditions(
+                &TodoFilter {
+                    is_done: None,
+                    ..filter.clone()
+                },
+                &mut bindings,
+            ));
+
+            let query = format!(
+                "SELECT is_done, count() AS count FROM todo {} GROUP BY is_done",
+                where_clause(&conditions)
+            );
+
+            let mut response = self
+                .driver
+                .client
+                .query(query)
+                .bind(("search_term", search_term))
+                .bind(("due_after", filter.due_after.map(Datetime)))
+                .bind(("due_before", filter.due_before.map(Datetime)))
+                .bind(bindings)
+                .await?;
+
+            let result: Vec<TodoStatusCountModel> = response.take(0)?;
+
+            Ok(result)
+        })
+        .await
+    }
+
+    #[instrument(name = "todo_repository.suggest_todos", skip_all)]
+    async fn suggest_todos(
+        &self,
+        prefix: &str,
+        limit: u32,
+    ) -> RepositoryResult<Vec<TodoSuggestionModel>> {
+        self.timed("suggest_todos", async {
+            let query = r#"
+            SELECT id, subject, search::score(1) AS score
+            FROM todo
+            WHERE subject_suggest @1@ $prefix
+            ORDER BY score DESC, subject
+            LIMIT $limit
         "#;
 
-        let mut response = self
-            .driver
-            .client
-            .query(query)
-            .bind(("search_term", search_term))
-            .await?;
+            let mut response = self
+                .driver
+                .client
+                .query(query)
+                .bind(("prefix", prefix))
+                .bind(("limit", limit))
+                .await?;
 
-        let result: Vec<TodoModel> = response.take(0)?;
+            let result: Vec<TodoSuggestionModel> = response.take(0)?;
 
-        Ok(result)
+            Ok(result)
+        })
+        .await
     }
 }
 
 impl TodoRepositoryImpl {
     pub fn new(driver: DatabaseDriver) -> Self {
-        Self { driver }
+        Self {
+            driver,
+            metrics: None,
+        }
+    }
+
+    /// Records how long every query takes.
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+
+        self
+    }
+
+    async fn timed<T>(&self, operation: &str, query: impl Future<Output = T>) -> T {
+        match &self.metrics {
+            Some(metrics) => metrics.time_query("todo", operation, query).await,
+            None => query.await,
+        }
+    }
+
+    /// Replaces the pending reminders of the todos with the ones their due
+    /// date and offsets call for now. Sent reminders are kept, so the same
+    /// reminder is never scheduled twice.
+    async fn schedule_reminders(&self, todos: &[TodoModel]) -> RepositoryResult<()> {
+        let ids: Vec<Thing> = todos.iter().map(|todo| todo.id.clone()).collect();
+        let reminders: Vec<ReminderModelInsert> = todos
+            .iter()
+            .filter(|todo| !todo.is_done)
+            .flat_map(|todo| {
+                ReminderModelInsert::for_todo(&todo.id, todo.due_date, &todo.reminders)
+            })
+            .collect();
+
+        self.driver
+            .client
+            .query(
+                r#"
+                BEGIN TRANSACTION;
+                DELETE reminder WHERE todo INSIDE $todos AND sent_at = NONE;
+                INSERT IGNORE INTO reminder $reminders;
+                COMMIT TRANSACTION;
+                "#,
+            )
+            .bind(("todos", ids))
+            .bind(("reminders", reminders))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+}
+
+/// The todos as the changes left them.
+fn change_snapshots(changes: &[OutboxModel]) -> Vec<TodoModel> {
+    changes
+        .iter()
+        .filter_map(|change| change.after.clone())
+        .collect()
+}
+
+static SEARCH_CONDITION: &str = "(subject @1@ $search_term OR description @2@ $search_term)";
+
+fn filter_conditions(filter: &TodoFilter, bindings: &mut Bindings) -> Vec<String> {
+    let mut conditions = vec![];
+
+    if filter.is_done.is_some() {
+        conditions.push(String::from("is_done = $is_done"));
+    }
+
+    if filter.due_after.is_some() {
+        conditions.push(String::from("due_date >= $due_after"));
+    }
+
+    if filter.due_before.is_some() {
+        conditions.push(String::from("due_date < $due_before"));
+    }
+
+    if let Some(query) = &filter.query {
+        conditions.push(query_condition(query, bindings));
+    }
+
+    conditions
+}
+
+/// Translates the query AST into a SurrealQL condition. Every user supplied
+/// value goes through a `$query_<n>` parameter, never into the query text.
+fn query_condition(expr: &QueryExpr, bindings: &mut Bindings) -> String {
+    match expr {
+        QueryExpr::And(terms) => join_conditions(terms, " AND ", "true", bindings),
+        QueryExpr::Or(terms) => join_conditions(terms, " OR ", "false", bindings),
+        QueryExpr::Not(term) => format!("!({})", query_condition(term, bindings)),
+        QueryExpr::IsDone(is_done) => format!("is_done = {}", bind(bindings, *is_done)),
+        QueryExpr::Due { from, until } => {
+            let mut bounds = vec![];
+
+            if let Some(from) = from {
+                bounds.push(format!("due_date >= {}", bind(bindings, Datetime(*from))));
+            }
+
+            if let Some(until) = until {
+                bounds.push(format!("due_date < {}", bind(bindings, Datetime(*until))));
+            }
+
+            match bounds.is_empty() {
+                true => String::from("true"),
+                false => format!("({})", bounds.join(" AND ")),
+            }
+        }
+        QueryExpr::Text { field, value } => {
+            let value = bind(bindings, value.as_str());
+            let contains = |column: &str| {
+                format!("string::contains(string::lowercase({}), {})", column, value)
+            };
+
+            match field {
+                TextField::Subject => contains("subject"),
+                TextField::Description => contains("description"),
+                TextField::Any => {
+                    format!("({} OR {})", contains("subject"), contains("description"))
+                }
+            }
+        }
+    }
+}
+
+fn join_conditions(
+    terms: &[QueryExpr],
+    separator: &str,
+    empty: &str,
+    bindings: &mut Bindings,
+) -> String {
+    if terms.is_empty() {
+        return empty.to_string();
+    }
+
+    let conditions: Vec<String> = terms
+        .iter()
+        .map(|term| query_condition(term, bindings))
+        .collect();
+
+    format!("({})", conditions.join(separator))
+}
+
+fn bind(bindings: &mut Bindings, value: impl Into<Value>) -> String {
+    let name = format!("query_{}", bindings.len());
+    bindings.insert(name.clone(), value.into());
+
+    format!("${}", name)
+}
+
+fn where_clause(conditions: &[String]) -> String {
+    if conditions.is_empty() {
+        return String::new();
+    }
+
+    format!("WHERE {}", conditions.join(" AND "))
+}
+
+fn limit_clause(pagination: Option<Pagination>) -> &'static str {
+    match pagination {
+        Some(_) => "LIMIT $limit START $offset",
+        None => "",
     }
 }
@@ -1,7 +1,26 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use futures::{stream, Stream, TryStreamExt};
+use tokio::time::timeout;
+use tracing::{field, instrument, warn, Span};
+use validator::Validate;
+
 use crate::{
-    common::ApplicationError,
-    docs::v1::todos::{CreateTodoRequest, Todo, TodoModel, TodoModelUpdate, UpdateTodoRequest},
-    util::{Clock, IdGenerator},
+    common::{validation_issues, ApplicationError, Problem},
+    docs::v1::events::OutboxModel,
+    docs::v1::reminders::ReminderOffset,
+    docs::v1::todos::{
+        CreateTodoRequest, DueDateInput, ImportRow, ImportRowProblem, ImportTodosResponse,
+        ImportedTodo, Pagination, Todo, TodoFilter, TodoModel, TodoModelUpdate, TodoSearchModel,
+        TodoSearchPage, TodoSearchResult, TodoSuggestion, TodoSuggestionModel, UpdateTodoRequest,
+    },
+    resource::v1::events::EventPublisher,
+    util::{
+        end_of_local_day, highlight_html, keep_wall_clock, parse_natural_date, parse_query, Clock,
+        IdGenerator, QueryExpr,
+    },
 };
 
 use ulid::Ulid;
@@ -10,6 +29,20 @@ use super::TodoRepository;
 
 type ServiceResult<T> = Result<T, ApplicationError>;
 
+static EXPORT_PAGE_SIZE: u32 = 100;
+static IMPORT_BATCH_SIZE: usize = 100;
+
+/// Longest a suggestion lookup may take before it is given up on.
+pub const SUGGEST_LATENCY_BUDGET: Duration = Duration::from_millis(150);
+
+/// When a todo is due, the zone that is remembered with it and when it
+/// reminds.
+struct Schedule {
+    due_date: DateTime<Utc>,
+    timezone: Option<Tz>,
+    reminders: Vec<ReminderOffset>,
+}
+
 pub struct TodoService<R, C, G>
 where
     R: TodoRepository,
@@ -19,6 +52,8 @@ where
     repository: R,
     clock: C,
     id_generator: G,
+    timezone: Tz,
+    events: Option<EventPublisher>,
 }
 
 impl<T, C, G> TodoService<T, C, G>
@@ -27,16 +62,38 @@ where
     C: Clock,
     G: IdGenerator<Ulid>,
 {
-    pub fn new(repository: T, clock: C, id_generator: G) -> Self {
+    pub fn new(repository: T, clock: C, id_generator: G, timezone: Tz) -> Self {
         Self {
             repository,
             clock,
             id_generator,
+            timezone,
+            events: None,
         }
     }
 
-    pub async fn get_todos(&self) -> ServiceResult<Vec<Todo>> {
-        let todos_model = self.repository.get_todos().await?;
+    /// Publishes the events of every change made through this service. The
+    /// changes are recorded in the outbox either way.
+    pub fn with_events(mut self, events: EventPublisher) -> Self {
+        self.events = Some(events);
+
+        self
+    }
+
+    /// Parses a query typed in the search box, relative dates are resolved
+    /// against the current time and days in the caller's time zone.
+    pub fn parse_query(&self, query: &str, timezone: Option<Tz>) -> ServiceResult<QueryExpr> {
+        parse_query(query, self.clock.now(), timezone.unwrap_or(self.timezone))
+            .map_err(|err| ApplicationError::ValidationError(vec![format!("query: {}", err)]))
+    }
+
+    #[instrument(name = "todo_service.get_todos", skip_all)]
+    pub async fn get_todos(
+        &self,
+        filter: &TodoFilter,
+        pagination: Option<Pagination>,
+    ) -> ServiceResult<Vec<Todo>> {
+        let todos_model = self.repository.get_todos(filter, pagination).await?;
 
         let result: Result<Vec<Todo>, ApplicationError> = todos_model
             .into_iter()
@@ -49,6 +106,36 @@ where
         }
     }
 
+    /// Walks through every todo matching the filter one page at a time, so that
+    /// exports never hold the whole table in memory.
+    pub fn stream_todos(
+        self: Arc<Self>,
+        filter: TodoFilter,
+    ) -> impl Stream<Item = ServiceResult<Todo>> {
+        stream::try_unfold(
+            (self, filter, 0, false),
+            |(service, filter, offset, exhausted)| async move {
+                if exhausted {
+                    return ServiceResult::Ok(None);
+                }
+
+                let pagination = Pagination {
+                    limit: EXPORT_PAGE_SIZE,
+                    offset,
+                };
+                let page = service.get_todos(&filter, Some(pagination)).await?;
+                let exhausted = (page.len() as u32) < EXPORT_PAGE_SIZE;
+
+                Ok(Some((
+                    stream::iter(page.into_iter().map(Ok)),
+                    (service, filter, offset + EXPORT_PAGE_SIZE, exhausted),
+                )))
+            },
+        )
+        .try_flatten()
+    }
+
+    #[instrument(name = "todo_service.get_todo_by_id", skip_all, fields(todo.id = %id))]
     pub async fn get_todo_by_id(&self, id: &str) -> ServiceResult<Todo> {
         let id = self.id_generator.parse(id)?;
 
@@ -57,47 +144,274 @@ where
         self.model_to_domain(todo)
     }
 
-    pub async fn create_todo(&self, todo: CreateTodoRequest) -> ServiceResult<Todo> {
-        let todo = self.request_to_domain(todo);
+    /// `timezone` is the caller's, used for phrases when the todo has none.
+    #[instrument(name = "todo_service.create_todo", skip_all, fields(todo.id = field::Empty))]
+    pub async fn create_todo(
+        &self,
+        todo: CreateTodoRequest,
+        timezone: Option<Tz>,
+    ) -> ServiceResult<Todo> {
+        let schedule = self
+            .resolve_schedule(&todo, timezone)
+            .map_err(|issue| ApplicationError::ValidationError(vec![issue]))?;
+        let todo = self.request_to_domain(todo, schedule);
+        Span::current().record("todo.id", field::display(&todo.id));
 
-        let todo = self.repository.create_todo(todo).await?;
+        let change = self.repository.create_todo(todo).await?;
+        self.publish(&change).await;
 
-        self.model_to_domain(todo)
+        self.change_to_domain(change)
     }
 
-    pub async fn update_todo(&self, id: &str, update: UpdateTodoRequest) -> ServiceResult<Todo> {
+    /// Validates every row and, unless it is a dry run, inserts the valid ones in
+    /// batches. Invalid rows are reported back and never block the others.
+    #[instrument(name = "todo_service.import_todos", skip_all)]
+    pub async fn import_todos(
+        &self,
+        rows: Vec<ImportRow>,
+        dry_run: bool,
+    ) -> ServiceResult<ImportTodosResponse> {
+        let total = rows.len();
+        let mut valid: Vec<(ImportedTodo, Schedule)> = vec![];
+        let mut errors = vec![];
+
+        for (index, row) in rows.into_iter().enumerate() {
+            let row = row
+                .and_then(|imported| match imported.todo.validate() {
+                    Ok(_) => Ok(imported),
+                    Err(err) => Err(validation_issues(&err)),
+                })
+                .and_then(
+                    |imported| match self.resolve_schedule(&imported.todo, None) {
+                        Ok(schedule) => Ok((imported, schedule)),
+                        Err(issue) => Err(vec![issue]),
+                    },
+                );
+
+            match row {
+                Ok(imported) => valid.push(imported),
+                Err(issues) => errors.push(ImportRowProblem {
+                    row: index + 1,
+                    problem: Problem::new("VALIDATION_ERROR", issues),
+                }),
+            }
+        }
+
+        let mut imported = valid.len();
+
+        if !dry_run {
+            imported = 0;
+
+            while !valid.is_empty() {
+                let batch: Vec<Todo> = valid
+                    .drain(..valid.len().min(IMPORT_BATCH_SIZE))
+                    .map(|(row, schedule)| self.imported_to_domain(row, schedule))
+                    .collect();
+
+                let changes = self.repository.create_todos(batch).await?;
+
+                for change in &changes {
+                    self.publish(change).await;
+                }
+
+                imported += changes.len();
+            }
+        }
+
+        Ok(ImportTodosResponse {
+            dry_run,
+            total,
+            imported,
+            failed: errors.len(),
+            errors,
+        })
+    }
+
+    /// Changing only the time zone keeps the wall clock time of the due date,
+    /// changing only the all-day flag keeps its day.
+    #[instrument(name = "todo_service.update_todo", skip_all, fields(todo.id = %id))]
+    pub async fn update_todo(
+        &self,
+        id: &str,
+        mut update: UpdateTodoRequest,
+        timezone: Option<Tz>,
+    ) -> ServiceResult<Todo> {
         let id = self.id_generator.parse(id)?;
+        let new_timezone = update
+            .timezone
+            .as_deref()
+            .map(parse_timezone)
+            .transpose()
+            .map_err(|issue| ApplicationError::ValidationError(vec![issue]))?;
+
         let existing_todo = self.repository.get_todo_by_id(&id).await?;
-        let updated_todo = TodoModelUpdate::merge(existing_todo, update);
+        let existing_timezone = existing_todo
+            .timezone
+            .as_deref()
+            .and_then(|tz| tz.parse::<Tz>().ok());
+        let previous_zone = existing_timezone.or(timezone).unwrap_or(self.timezone);
+        let zone = new_timezone.unwrap_or(previous_zone);
+        let all_day = update.all_day.unwrap_or(existing_todo.all_day);
 
-        let todo = self.repository.update_todo(&id, updated_todo).await?;
+        let due_date = match &update.due_date {
+            Some(due_date) => Some(self.resolve_due_date(due_date, zone, all_day)),
+            None if new_timezone.is_some() || update.all_day.is_some() => Some(
+                keep_wall_clock(existing_todo.due_date, previous_zone, zone)
+                    .map(|due_date| all_day_adjusted(due_date, zone, all_day))
+                    .map_err(|err| format!("timezone: {}", err)),
+            ),
+            None => None,
+        }
+        .transpose()
+        .map_err(|issue| ApplicationError::ValidationError(vec![issue]))?;
+        let reminders = update
+            .reminders
+            .as_deref()
+            .map(parse_reminders)
+            .transpose()
+            .map_err(|issue| ApplicationError::ValidationError(vec![issue]))?;
 
-        self.model_to_domain(todo)
+        update.timezone = match new_timezone {
+            None if all_day && existing_timezone.is_none() => Some(zone),
+            other => other,
+        }
+        .map(|tz| tz.name().to_string());
+
+        let updated_todo = TodoModelUpdate::merge(existing_todo, update, due_date, reminders);
+
+        let change = self
+            .repository
+            .update_todo(&id, updated_todo, self.clock.now())
+            .await?;
+        self.publish(&change).await;
+
+        self.change_to_domain(change)
     }
 
+    #[instrument(name = "todo_service.delete_todo", skip_all, fields(todo.id = %id))]
     pub async fn delete_todo(&self, id: &str) -> ServiceResult<Todo> {
         let id = self.id_generator.parse(id)?;
 
-        let todo = self.repository.delete_todo(&id).await?;
+        let change = self.repository.delete_todo(&id, self.clock.now()).await?;
+        self.publish(&change).await;
 
-        self.model_to_domain(todo)
+        self.change_to_domain(change)
     }
 
-    pub async fn search_todo(&self, q: &str) -> ServiceResult<Vec<Todo>> {
-        let todos_model = self.repository.search_todo(q).await?;
+    #[instrument(name = "todo_service.search_todo", skip_all)]
+    pub async fn search_todo(
+        &self,
+        q: &str,
+        filter: &TodoFilter,
+        pagination: Pagination,
+    ) -> ServiceResult<TodoSearchPage> {
+        let todos_model = self.repository.search_todo(q, filter, pagination).await?;
+        let status_counts = self.repository.count_search_by_status(q, filter).await?;
 
-        let result: Result<Vec<Todo>, ApplicationError> = todos_model
+        let results = todos_model
             .into_iter()
-            .map(|t| self.model_to_domain(t))
-            .collect();
+            .map(|t| self.search_model_to_domain(t))
+            .collect::<ServiceResult<Vec<TodoSearchResult>>>()?;
 
-        match result {
-            Ok(todos) => Ok(todos),
-            Err(msg) => Err(msg),
+        let count_for = |is_done: bool| {
+            status_counts
+                .iter()
+                .filter(|c| c.is_done == is_done)
+                .map(|c| c.count)
+                .sum::<u64>()
+        };
+        let open_count = count_for(false);
+        let done_count = count_for(true);
+
+        let total = match filter.is_done {
+            Some(true) => done_count,
+            Some(false) => open_count,
+            None => open_count + done_count,
+        };
+
+        Ok(TodoSearchPage {
+            results,
+            pagination,
+            total,
+            open_count,
+            done_count,
+        })
+    }
+
+    /// Suggestions are fetched while the user types, so a slow answer is worse
+    /// than none: past [`SUGGEST_LATENCY_BUDGET`] an empty list is returned.
+    #[instrument(name = "todo_service.suggest_todos", skip_all)]
+    pub async fn suggest_todos(
+        &self,
+        prefix: &str,
+        limit: u32,
+    ) -> ServiceResult<Vec<TodoSuggestion>> {
+        let prefix = prefix.trim();
+
+        if prefix.is_empty() {
+            return Ok(vec![]);
         }
+
+        let suggestions = match timeout(
+            SUGGEST_LATENCY_BUDGET,
+            self.repository.suggest_todos(prefix, limit),
+        )
+        .await
+        {
+            Ok(suggestions) => suggestions?,
+            Err(_) => {
+                warn!(
+                    prefix,
+                    budget = ?SUGGEST_LATENCY_BUDGET,
+                    "Todo suggestions exceeded the latency budget"
+                );
+
+                return Ok(vec![]);
+            }
+        };
+
+        suggestions
+            .into_iter()
+            .map(|s| self.suggestion_model_to_domain(s))
+            .collect()
+    }
+
+    /// Works out when a new todo is due. Wall clock times are read in the
+    /// todo's zone, else the caller's, else the server's. All-day todos always
+    /// remember the zone their day was set in.
+    fn resolve_schedule(
+        &self,
+        todo: &CreateTodoRequest,
+        caller_timezone: Option<Tz>,
+    ) -> Result<Schedule, String> {
+        let timezone = todo.timezone.as_deref().map(parse_timezone).transpose()?;
+        let zone = timezone.or(caller_timezone).unwrap_or(self.timezone);
+
+        Ok(Schedule {
+            due_date: self.resolve_due_date(&todo.due_date, zone, todo.all_day)?,
+            timezone: if todo.all_day { Some(zone) } else { timezone },
+            reminders: parse_reminders(&todo.reminders)?,
+        })
+    }
+
+    /// Resolves phrases such as `tomorrow 5pm` against the clock, returning the
+    /// validation issue when the phrase is unclear.
+    fn resolve_due_date(
+        &self,
+        due_date: &DueDateInput,
+        timezone: Tz,
+        all_day: bool,
+    ) -> Result<DateTime<Utc>, String> {
+        let due_date = match due_date {
+            DueDateInput::Timestamp(due_date) => *due_date,
+            DueDateInput::Phrase(phrase) => parse_natural_date(phrase, self.clock.now(), timezone)
+                .map_err(|err| format!("dueDate: `{}` {}", phrase, err))?,
+        };
+
+        Ok(all_day_adjusted(due_date, timezone, all_day))
     }
 
-    fn request_to_domain(&self, data: CreateTodoRequest) -> Todo {
+    fn request_to_domain(&self, data: CreateTodoRequest, schedule: Schedule) -> Todo {
         let creation_date = self.clock.now();
 
         Todo {
@@ -105,26 +419,123 @@ where
             subject: data.subject,
             description: data.description,
             is_done: false,
-            due_date: data.due_date,
+            due_date: schedule.due_date,
+            timezone: schedule.timezone,
+            all_day: data.all_day,
+            reminders: schedule.reminders,
             created_at: creation_date,
             updated_at: creation_date,
         }
     }
 
-    fn model_to_domain(&self, model: TodoModel) -> Result<Todo, ApplicationError> {
+    fn imported_to_domain(&self, data: ImportedTodo, schedule: Schedule) -> Todo {
+        let mut todo = self.request_to_domain(data.todo, schedule);
+        todo.is_done = data.is_done;
+
+        todo
+    }
+
+    fn search_model_to_domain(
+        &self,
+        mut model: TodoSearchModel,
+    ) -> ServiceResult<TodoSearchResult> {
+        let score = model.score;
+        let subject_highlight = model
+            .subject_highlight
+            .take()
+            .as_deref()
+            .map(highlight_html);
+        let description_highlight = model
+            .description_highlight
+            .take()
+            .as_deref()
+            .map(highlight_html);
+
+        Ok(TodoSearchResult {
+            todo: self.model_to_domain(model.into())?,
+            score,
+            subject_highlight,
+            description_highlight,
+        })
+    }
+
+    fn suggestion_model_to_domain(
+        &self,
+        model: TodoSuggestionModel,
+    ) -> ServiceResult<TodoSuggestion> {
         let id = self
             .id_generator
             .parse(&model.id.id.to_string())
             .map_err(|err| ApplicationError::ServerError(vec![err.to_string()]))?;
 
-        Ok(Todo {
+        Ok(TodoSuggestion {
             id,
             subject: model.subject,
-            description: model.description,
-            is_done: model.is_done,
-            due_date: model.due_date,
-            created_at: model.created_at,
-            updated_at: model.updated_at,
         })
     }
+
+    /// The change is saved along with its outbox entry, so events that
+    /// cannot be published now are left to the relay rather than failing the
+    /// request.
+    async fn publish(&self, change: &OutboxModel) {
+        let Some(events) = &self.events else {
+            return;
+        };
+
+        if let Err(err) = events.publish(change, self.clock.now()).await {
+            warn!("Unable to publish change {}: {:?}", change.id, err);
+        }
+    }
+
+    /// The todo after the change, or as it was for a deletion.
+    fn change_to_domain(&self, change: OutboxModel) -> ServiceResult<Todo> {
+        match change.after.or(change.before) {
+            Some(todo) => self.model_to_domain(todo),
+            None => Err(ApplicationError::ServerError(vec![format!(
+                "Outbox change {} holds no todo",
+                change.id
+            )])),
+        }
+    }
+
+    fn model_to_domain(&self, model: TodoModel) -> Result<Todo, ApplicationError> {
+        Todo::try_from(model).map_err(|err| ApplicationError::ServerError(vec![err]))
+    }
+}
+
+/// All-day todos are stored as due by the last second of their local day,
+/// so that they only become overdue once that day is over.
+fn all_day_adjusted(due_date: DateTime<Utc>, timezone: Tz, all_day: bool) -> DateTime<Utc> {
+    match all_day {
+        true => end_of_local_day(due_date, timezone),
+        false => due_date,
+    }
+}
+
+fn parse_timezone(timezone: &str) -> Result<Tz, String> {
+    timezone.parse::<Tz>().map_err(|_| {
+        format!(
+            "timezone: `{}` is not an IANA time zone such as Europe/Berlin",
+            timezone
+        )
+    })
+}
+
+fn parse_reminders(reminders: &[String]) -> Result<Vec<ReminderOffset>, String> {
+    let mut offsets = reminders
+        .iter()
+        .map(|reminder| {
+            ReminderOffset::parse(reminder).ok_or_else(|| {
+                format!(
+                    "reminders: `{}` is not an offset such as 30m, 1h or 2d, up to 4w",
+                    reminder
+                )
+            })
+        })
+        .collect::<Result<Vec<ReminderOffset>, String>>()?;
+
+    offsets.sort();
+    offsets.dedup();
+
+    Ok(offsets)
 }
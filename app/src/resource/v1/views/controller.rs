@@ -0,0 +1,232 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    routing, Json, Router,
+};
+use ulid::Ulid;
+
+use crate::{
+    common::{ApplicationError, RequestTimezone, ValidatedBody, ValidatedQuery},
+    docs::v1::{
+        todos::TodoResponse,
+        views::{CreateViewRequest, GetViewTodosRequest, UpdateViewRequest, ViewResponse},
+    },
+    resource::v1::todos::TodoRepository,
+    util::{Clock, IdGenerator},
+};
+
+use super::ViewService;
+
+pub static VIEW_TAG: &str = "View";
+
+pub struct ViewController<R: TodoRepository, C: Clock, G: IdGenerator<Ulid>> {
+    prefix: Option<String>,
+    service: Option<ViewService<R, C, G>>,
+}
+
+impl<R: TodoRepository, C: Clock, G: IdGenerator<Ulid>> Default for ViewController<R, C, G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: TodoRepository, C: Clock, G: IdGenerator<Ulid>> ViewController<R, C, G> {
+    pub fn new() -> Self {
+        Self {
+            prefix: None,
+            service: None,
+        }
+    }
+
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = Some(prefix.to_string());
+
+        self
+    }
+
+    pub fn with_service(mut self, service: ViewService<R, C, G>) -> Self {
+        self.service = Some(service);
+
+        self
+    }
+
+    pub fn build(self) -> Router {
+        let prefix = self.prefix.expect("prefix not set");
+        let service = Arc::new(self.service.expect("service not set"));
+
+        let router = Router::new()
+            .route("/", routing::get(get_views))
+            .route("/", routing::post(create_view))
+            .route("/:id", routing::get(get_view))
+            .route("/:id", routing::patch(update_view))
+            .route("/:id", routing::delete(delete_view))
+            .route("/:id/todos", routing::get(get_view_todos))
+            .with_state(service);
+
+        Router::new().nest(&prefix, router)
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/views",
+    responses(
+        (status = StatusCode::OK, description = "Built-in views followed by the saved ones", body = [ViewResponse]),
+        (status = StatusCode::INTERNAL_SERVER_ERROR, description = "Internal server error", body = Problem),
+    ),
+    tag = VIEW_TAG
+)]
+pub async fn get_views<R, C, G>(
+    State(service): State<Arc<ViewService<R, C, G>>>,
+) -> Result<Json<Vec<ViewResponse>>, ApplicationError>
+where
+    R: TodoRepository,
+    C: Clock,
+    G: IdGenerator<Ulid>,
+{
+    let views = service.get_views().await?;
+
+    let result: Vec<ViewResponse> = views.into_iter().map(Into::into).collect();
+
+    Ok(Json(result))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/views/{id}",
+    params(("id", Path, example = "today")),
+    responses(
+        (status = StatusCode::OK, description = "Get a View by Id", body = ViewResponse),
+        (status = StatusCode::NOT_FOUND, description = "Resource not found", body = Problem),
+        (status = StatusCode::INTERNAL_SERVER_ERROR, description = "Internal server error", body = Problem),
+    ),
+    tag = VIEW_TAG
+)]
+pub async fn get_view<R, C, G>(
+    State(service): State<Arc<ViewService<R, C, G>>>,
+    Path(view_id): Path<String>,
+) -> Result<ViewResponse, ApplicationError>
+where
+    R: TodoRepository,
+    C: Clock,
+    G: IdGenerator<Ulid>,
+{
+    let view = service.get_view(&view_id).await?;
+
+    Ok(view.into())
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/views",
+    request_body = CreateViewRequest,
+    responses(
+        (status = StatusCode::OK, description = "Save a View", body = ViewResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Invalid name or query", body = Problem),
+        (status = StatusCode::INTERNAL_SERVER_ERROR, description = "Internal server error", body = Problem),
+    ),
+    tag = VIEW_TAG
+)]
+pub async fn create_view<R, C, G>(
+    State(service): State<Arc<ViewService<R, C, G>>>,
+    ValidatedBody(data): ValidatedBody<CreateViewRequest>,
+) -> Result<ViewResponse, ApplicationError>
+where
+    R: TodoRepository,
+    C: Clock,
+    G: IdGenerator<Ulid>,
+{
+    let view = service.create_view(data).await?;
+
+    Ok(view.into())
+}
+
+#[utoipa::path(
+    patch,
+    path = "/v1/views/{id}",
+    params(("id", Path, example = "01HDS25AGAJ88WNXE5KZ3CN8KG")),
+    request_body = UpdateViewRequest,
+    responses(
+        (status = StatusCode::OK, description = "Update a saved View", body = ViewResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Invalid name or query, or a built-in View", body = Problem),
+        (status = StatusCode::NOT_FOUND, description = "Resource not found", body = Problem),
+        (status = StatusCode::INTERNAL_SERVER_ERROR, description = "Internal server error", body = Problem),
+    ),
+    tag = VIEW_TAG
+)]
+pub async fn update_view<R, C, G>(
+    State(service): State<Arc<ViewService<R, C, G>>>,
+    Path(view_id): Path<String>,
+    ValidatedBody(update_data): ValidatedBody<UpdateViewRequest>,
+) -> Result<ViewResponse, ApplicationError>
+where
+    R: TodoRepository,
+    C: Clock,
+    G: IdGenerator<Ulid>,
+{
+    let view = service.update_view(&view_id, update_data).await?;
+
+    Ok(view.into())
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/views/{id}",
+    params(("id", Path, example = "01HDS25AGAJ88WNXE5KZ3CN8KG")),
+    responses(
+        (status = StatusCode::OK, description = "Delete a saved View", body = ViewResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Built-in Views cannot be deleted", body = Problem),
+        (status = StatusCode::NOT_FOUND, description = "Resource not found", body = Problem),
+        (status = StatusCode::INTERNAL_SERVER_ERROR, description = "Internal server error", body = Problem),
+    ),
+    tag = VIEW_TAG
+)]
+pub async fn delete_view<R, C, G>(
+    State(service): State<Arc<ViewService<R, C, G>>>,
+    Path(view_id): Path<String>,
+) -> Result<ViewResponse, ApplicationError>
+where
+    R: TodoRepository,
+    C: Clock,
+    G: IdGenerator<Ulid>,
+{
+    let view = service.delete_view(&view_id).await?;
+
+    Ok(view.into())
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/views/{id}/todos",
+    params(("id", Path, example = "overdue"), GetViewTodosRequest),
+    responses(
+        (status = StatusCode::OK, description = "Todos matching the View's query right now", body = [TodoResponse]),
+        (status = StatusCode::BAD_REQUEST, description = "Invalid pagination", body = Problem),
+        (status = StatusCode::NOT_FOUND, description = "Resource not found", body = Problem),
+        (status = StatusCode::INTERNAL_SERVER_ERROR, description = "Internal server error", body = Problem),
+    ),
+    tag = VIEW_TAG
+)]
+pub async fn get_view_todos<R, C, G>(
+    State(service): State<Arc<ViewService<R, C, G>>>,
+    RequestTimezone(timezone): RequestTimezone,
+    Path(view_id): Path<String>,
+    ValidatedQuery(query): ValidatedQuery<GetViewTodosRequest>,
+) -> Result<Json<Vec<TodoResponse>>, ApplicationError>
+where
+    R: TodoRepository,
+    C: Clock,
+    G: IdGenerator<Ulid>,
+{
+    let todos = service
+        .get_view_todos(&view_id, query.pagination(), timezone)
+        .await?;
+
+    let result: Vec<TodoResponse> = todos
+        .into_iter()
+        .map(|todo| TodoResponse::from(todo).in_timezone(timezone))
+        .collect();
+
+    Ok(Json(result))
+}
@@ -0,0 +1,117 @@
+use chrono::{DateTime, Utc};
+use surrealdb::sql::Datetime;
+use ulid::Ulid;
+
+use crate::{
+    common::{DatabaseDriver, RepositoryError},
+    docs::v1::views::{ViewModel, ViewModelUpdate},
+};
+
+type RepositoryResult<T> = Result<T, RepositoryError>;
+
+#[derive(Clone)]
+pub struct ViewRepository {
+    pub driver: DatabaseDriver,
+}
+
+impl ViewRepository {
+    pub fn new(driver: DatabaseDriver) -> Self {
+        Self { driver }
+    }
+
+    pub async fn get_views(&self) -> RepositoryResult<Vec<ViewModel>> {
+        let mut response = self
+            .driver
+            .client
+            .query("SELECT * FROM saved_view ORDER BY name")
+            .await?;
+
+        let result: Vec<ViewModel> = response.take(0)?;
+
+        Ok(result)
+    }
+
+    pub async fn get_view_by_id(&self, id: &Ulid) -> RepositoryResult<ViewModel> {
+        let result: Option<ViewModel> = self
+            .driver
+            .client
+            .select(("saved_view", &id.to_string()))
+            .await?;
+
+        if let Some(view) = result {
+            return Ok(view);
+        }
+
+        Err(RepositoryError::NotFound(id.to_string()))
+    }
+
+    pub async fn create_view(
+        &self,
+        id: &Ulid,
+        name: String,
+        query: String,
+        created_at: DateTime<Utc>,
+    ) -> RepositoryResult<ViewModel> {
+        let mut response = self
+            .driver
+            .client
+            .query(
+                r#"
+                CREATE type::thing('saved_view', $id) CONTENT {
+                    name: $name,
+                    query: $query,
+                    created_at: $created_at,
+                    updated_at: $created_at,
+                }
+                "#,
+            )
+            .bind(("id", id.to_string()))
+            .bind(("name", name))
+            .bind(("query", query))
+            .bind(("created_at", Datetime(created_at)))
+            .await?;
+
+        let result: Option<ViewModel> = response.take(0)?;
+
+        match result {
+            Some(view) => Ok(view),
+            None => Err(RepositoryError::InsertError(format!(
+                "View({}) not returned after inserting into the DB",
+                id
+            ))),
+        }
+    }
+
+    pub async fn update_view(
+        &self,
+        id: &Ulid,
+        updated_view: ViewModelUpdate,
+    ) -> RepositoryResult<ViewModel> {
+        let view: Option<ViewModel> = self
+            .driver
+            .client
+            .update(("saved_view", id.to_string()))
+            .merge(updated_view)
+            .await?;
+
+        if let Some(view) = view {
+            return Ok(view);
+        }
+
+        Err(RepositoryError::NotFound(id.to_string()))
+    }
+
+    pub async fn delete_view(&self, id: &Ulid) -> RepositoryResult<ViewModel> {
+        let result: Option<ViewModel> = self
+            .driver
+            .client
+            .delete(("saved_view", &id.to_string()))
+            .await?;
+
+        if let Some(view) = result {
+            return Ok(view);
+        }
+
+        Err(RepositoryError::NotFound(id.to_string()))
+    }
+}
@@ -0,0 +1,156 @@
+use chrono_tz::Tz;
+use surrealdb::sql::Datetime;
+use ulid::Ulid;
+
+use crate::{
+    common::ApplicationError,
+    docs::v1::{
+        todos::{Pagination, Todo, TodoFilter},
+        views::{
+            CreateViewRequest, UpdateViewRequest, View, ViewModel, ViewModelUpdate, VirtualView,
+        },
+    },
+    resource::v1::todos::{TodoRepository, TodoService},
+    util::{Clock, IdGenerator},
+};
+
+use super::ViewRepository;
+
+type ServiceResult<T> = Result<T, ApplicationError>;
+
+pub struct ViewService<R, C, G>
+where
+    R: TodoRepository,
+    C: Clock,
+    G: IdGenerator<Ulid>,
+{
+    repository: ViewRepository,
+    todo_service: TodoService<R, C, G>,
+    clock: C,
+    id_generator: G,
+}
+
+impl<R, C, G> ViewService<R, C, G>
+where
+    R: TodoRepository,
+    C: Clock,
+    G: IdGenerator<Ulid>,
+{
+    pub fn new(
+        repository: ViewRepository,
+        todo_service: TodoService<R, C, G>,
+        clock: C,
+        id_generator: G,
+    ) -> Self {
+        Self {
+            repository,
+            todo_service,
+            clock,
+            id_generator,
+        }
+    }
+
+    /// Built-in views first, then the saved ones by name.
+    pub async fn get_views(&self) -> ServiceResult<Vec<View>> {
+        let mut views: Vec<View> = VirtualView::all().into_iter().map(Into::into).collect();
+
+        let saved = self.repository.get_views().await?;
+        views.extend(saved.into_iter().map(model_to_domain));
+
+        Ok(views)
+    }
+
+    pub async fn get_view(&self, id: &str) -> ServiceResult<View> {
+        if let Some(view) = VirtualView::from_id(id) {
+            return Ok(view.into());
+        }
+
+        let id = self.id_generator.parse(id)?;
+        let view = self.repository.get_view_by_id(&id).await?;
+
+        Ok(model_to_domain(view))
+    }
+
+    pub async fn create_view(&self, view: CreateViewRequest) -> ServiceResult<View> {
+        self.todo_service.parse_query(&view.query, None)?;
+
+        let view = self
+            .repository
+            .create_view(
+                &self.id_generator.generate(),
+                view.name,
+                view.query,
+                self.clock.now(),
+            )
+            .await?;
+
+        Ok(model_to_domain(view))
+    }
+
+    pub async fn update_view(&self, id: &str, update: UpdateViewRequest) -> ServiceResult<View> {
+        let id = self.saved_view_id(id)?;
+
+        if let Some(query) = &update.query {
+            self.todo_service.parse_query(query, None)?;
+        }
+
+        let existing_view = self.repository.get_view_by_id(&id).await?;
+        let updated_view = ViewModelUpdate {
+            name: update.name.unwrap_or(existing_view.name),
+            query: update.query.unwrap_or(existing_view.query),
+            updated_at: Datetime(self.clock.now()),
+        };
+
+        let view = self.repository.update_view(&id, updated_view).await?;
+
+        Ok(model_to_domain(view))
+    }
+
+    pub async fn delete_view(&self, id: &str) -> ServiceResult<View> {
+        let id = self.saved_view_id(id)?;
+
+        let view = self.repository.delete_view(&id).await?;
+
+        Ok(model_to_domain(view))
+    }
+
+    /// The view's query is parsed on every read, so relative dates such as
+    /// `due:<7d` always count from now and `today` is the caller's today.
+    pub async fn get_view_todos(
+        &self,
+        id: &str,
+        pagination: Option<Pagination>,
+        timezone: Option<Tz>,
+    ) -> ServiceResult<Vec<Todo>> {
+        let view = self.get_view(id).await?;
+
+        let filter = TodoFilter {
+            query: Some(self.todo_service.parse_query(&view.query, timezone)?),
+            ..TodoFilter::default()
+        };
+
+        self.todo_service.get_todos(&filter, pagination).await
+    }
+
+    fn saved_view_id(&self, id: &str) -> ServiceResult<Ulid> {
+        if let Some(view) = VirtualView::from_id(id) {
+            return Err(ApplicationError::ValidationError(vec![format!(
+                "{} is a built-in view and cannot be changed",
+                view.name()
+            )]));
+        }
+
+        Ok(self.id_generator.parse(id)?)
+    }
+}
+
+fn model_to_domain(model: ViewModel) -> View {
+    View {
+        id: model.id.id.to_raw(),
+        name: model.name,
+        query: model.query,
+        is_virtual: false,
+        created_at: Some(model.created_at),
+        updated_at: Some(model.updated_at),
+    }
+}
@@ -0,0 +1,287 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    middleware, routing, Json, Router,
+};
+use ulid::Ulid;
+
+use crate::{
+    common::{ApplicationError, ValidatedBody, ValidatedQuery},
+    docs::v1::webhooks::{
+        CreateWebhookRequest, GetDeliveriesRequest, UpdateWebhookRequest, WebhookDeliveryResponse,
+        WebhookResponse,
+    },
+    util::{require_api_key, ApiKey, Clock, IdGenerator},
+};
+
+use super::WebhookService;
+
+pub static WEBHOOK_TAG: &str = "Webhook";
+
+pub struct WebhookController<C: Clock, G: IdGenerator<Ulid>> {
+    prefix: Option<String>,
+    service: Option<WebhookService<C, G>>,
+    api_key: ApiKey,
+}
+
+impl<C: Clock, G: IdGenerator<Ulid>> Default for WebhookController<C, G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Clock, G: IdGenerator<Ulid>> WebhookController<C, G> {
+    pub fn new() -> Self {
+        Self {
+            prefix: None,
+            service: None,
+            api_key: ApiKey::default(),
+        }
+    }
+
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = Some(prefix.to_string());
+
+        self
+    }
+
+    pub fn with_service(mut self, service: WebhookService<C, G>) -> Self {
+        self.service = Some(service);
+
+        self
+    }
+
+    /// Webhooks receive a signed copy of every change and show what was
+    /// delivered, so every route takes this key. Without one they are all
+    /// refused.
+    pub fn with_api_key(mut self, api_key: ApiKey) -> Self {
+        self.api_key = api_key;
+
+        self
+    }
+
+    pub fn build(self) -> Router {
+        let prefix = self.prefix.expect("prefix not set");
+        let service = Arc::new(self.service.expect("service not set"));
+
+        let router = Router::new()
+            .route("/", routing::get(get_webhooks))
+            .route("/", routing::post(create_webhook))
+            .route("/:id", routing::get(get_webhook))
+            .route("/:id", routing::patch(update_webhook))
+            .route("/:id", routing::delete(delete_webhook))
+            .route("/:id/deliveries", routing::get(get_deliveries))
+            .route(
+                "/:id/deliveries/:delivery_id/redeliver",
+                routing::post(redeliver),
+            )
+            .route_layer(middleware::from_fn_with_state(
+                self.api_key,
+                require_api_key,
+            ))
+            .with_state(service);
+
+        Router::new().nest(&prefix, router)
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/webhooks",
+    params(("x-api-key" = String, Header, description = "The ADMIN_API_KEY, or send it as a bearer token")),
+    responses(
+        (status = StatusCode::OK, description = "Every webhook, without their secrets", body = [WebhookResponse]),
+        (status = StatusCode::UNAUTHORIZED, description = "Missing or wrong API key", body = Problem),
+        (status = StatusCode::INTERNAL_SERVER_ERROR, description = "Internal server error", body = Problem),
+    ),
+    tag = WEBHOOK_TAG
+)]
+pub async fn get_webhooks<C, G>(
+    State(service): State<Arc<WebhookService<C, G>>>,
+) -> Result<Json<Vec<WebhookResponse>>, ApplicationError>
+where
+    C: Clock,
+    G: IdGenerator<Ulid>,
+{
+    let webhooks = service.get_webhooks().await?;
+
+    let result: Vec<WebhookResponse> = webhooks.into_iter().map(Into::into).collect();
+
+    Ok(Json(result))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/webhooks/{id}",
+    params(
+        ("id", Path, example = "01HDS25AGAJ88WNXE5KZ3CN8KG"),
+        ("x-api-key" = String, Header, description = "The ADMIN_API_KEY, or send it as a bearer token"),
+    ),
+    responses(
+        (status = StatusCode::OK, description = "Get a Webhook by Id", body = WebhookResponse),
+        (status = StatusCode::NOT_FOUND, description = "Resource not found", body = Problem),
+        (status = StatusCode::UNAUTHORIZED, description = "Missing or wrong API key", body = Problem),
+        (status = StatusCode::INTERNAL_SERVER_ERROR, description = "Internal server error", body = Problem),
+    ),
+    tag = WEBHOOK_TAG
+)]
+pub async fn get_webhook<C, G>(
+    State(service): State<Arc<WebhookService<C, G>>>,
+    Path(webhook_id): Path<String>,
+) -> Result<WebhookResponse, ApplicationError>
+where
+    C: Clock,
+    G: IdGenerator<Ulid>,
+{
+    let webhook = service.get_webhook(&webhook_id).await?;
+
+    Ok(webhook.into())
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/webhooks",
+    params(("x-api-key" = String, Header, description = "The ADMIN_API_KEY, or send it as a bearer token")),
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = StatusCode::OK, description = "Subscribe a Webhook, the only response holding its secret", body = WebhookResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Invalid URL, events or secret", body = Problem),
+        (status = StatusCode::UNAUTHORIZED, description = "Missing or wrong API key", body = Problem),
+        (status = StatusCode::INTERNAL_SERVER_ERROR, description = "Internal server error", body = Problem),
+    ),
+    tag = WEBHOOK_TAG
+)]
+pub async fn create_webhook<C, G>(
+    State(service): State<Arc<WebhookService<C, G>>>,
+    ValidatedBody(data): ValidatedBody<CreateWebhookRequest>,
+) -> Result<WebhookResponse, ApplicationError>
+where
+    C: Clock,
+    G: IdGenerator<Ulid>,
+{
+    let webhook = service.create_webhook(data).await?;
+
+    Ok(webhook.into())
+}
+
+#[utoipa::path(
+    patch,
+    path = "/v1/webhooks/{id}",
+    params(
+        ("id", Path, example = "01HDS25AGAJ88WNXE5KZ3CN8KG"),
+        ("x-api-key" = String, Header, description = "The ADMIN_API_KEY, or send it as a bearer token"),
+    ),
+    request_body = UpdateWebhookRequest,
+    responses(
+        (status = StatusCode::OK, description = "Update a Webhook", body = WebhookResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Invalid URL, events or secret", body = Problem),
+        (status = StatusCode::NOT_FOUND, description = "Resource not found", body = Problem),
+        (status = StatusCode::UNAUTHORIZED, description = "Missing or wrong API key", body = Problem),
+        (status = StatusCode::INTERNAL_SERVER_ERROR, description = "Internal server error", body = Problem),
+    ),
+    tag = WEBHOOK_TAG
+)]
+pub async fn update_webhook<C, G>(
+    State(service): State<Arc<WebhookService<C, G>>>,
+    Path(webhook_id): Path<String>,
+    ValidatedBody(update_data): ValidatedBody<UpdateWebhookRequest>,
+) -> Result<WebhookResponse, ApplicationError>
+where
+    C: Clock,
+    G: IdGenerator<Ulid>,
+{
+    let webhook = service.update_webhook(&webhook_id, update_data).await?;
+
+    Ok(webhook.into())
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/webhooks/{id}",
+    params(
+        ("id", Path, example = "01HDS25AGAJ88WNXE5KZ3CN8KG"),
+        ("x-api-key" = String, Header, description = "The ADMIN_API_KEY, or send it as a bearer token"),
+    ),
+    responses(
+        (status = StatusCode::OK, description = "Delete a Webhook and drop its pending deliveries", body = WebhookResponse),
+        (status = StatusCode::NOT_FOUND, description = "Resource not found", body = Problem),
+        (status = StatusCode::UNAUTHORIZED, description = "Missing or wrong API key", body = Problem),
+        (status = StatusCode::INTERNAL_SERVER_ERROR, description = "Internal server error", body = Problem),
+    ),
+    tag = WEBHOOK_TAG
+)]
+pub async fn delete_webhook<C, G>(
+    State(service): State<Arc<WebhookService<C, G>>>,
+    Path(webhook_id): Path<String>,
+) -> Result<WebhookResponse, ApplicationError>
+where
+    C: Clock,
+    G: IdGenerator<Ulid>,
+{
+    let webhook = service.delete_webhook(&webhook_id).await?;
+
+    Ok(webhook.into())
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/webhooks/{id}/deliveries",
+    params(
+        ("id", Path, example = "01HDS25AGAJ88WNXE5KZ3CN8KG"),
+        ("x-api-key" = String, Header, description = "The ADMIN_API_KEY, or send it as a bearer token"),
+        GetDeliveriesRequest,
+    ),
+    responses(
+        (status = StatusCode::OK, description = "The latest deliveries of a Webhook", body = [WebhookDeliveryResponse]),
+        (status = StatusCode::BAD_REQUEST, description = "Unknown status", body = Problem),
+        (status = StatusCode::NOT_FOUND, description = "Resource not found", body = Problem),
+        (status = StatusCode::UNAUTHORIZED, description = "Missing or wrong API key", body = Problem),
+        (status = StatusCode::INTERNAL_SERVER_ERROR, description = "Internal server error", body = Problem),
+    ),
+    tag = WEBHOOK_TAG
+)]
+pub async fn get_deliveries<C, G>(
+    State(service): State<Arc<WebhookService<C, G>>>,
+    Path(webhook_id): Path<String>,
+    ValidatedQuery(query): ValidatedQuery<GetDeliveriesRequest>,
+) -> Result<Json<Vec<WebhookDeliveryResponse>>, ApplicationError>
+where
+    C: Clock,
+    G: IdGenerator<Ulid>,
+{
+    let deliveries = service.get_deliveries(&webhook_id, query.status).await?;
+
+    let result: Vec<WebhookDeliveryResponse> = deliveries.into_iter().map(Into::into).collect();
+
+    Ok(Json(result))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/webhooks/{id}/deliveries/{delivery_id}/redeliver",
+    params(
+        ("id", Path, example = "01HDS25AGAJ88WNXE5KZ3CN8KG"),
+        ("delivery_id", Path, example = "x8b3kq0m2v9d7c1f4n6p"),
+        ("x-api-key" = String, Header, description = "The ADMIN_API_KEY, or send it as a bearer token"),
+    ),
+    responses(
+        (status = StatusCode::OK, description = "Queue a delivery again, dead ones included", body = WebhookDeliveryResponse),
+        (status = StatusCode::NOT_FOUND, description = "Resource not found", body = Problem),
+        (status = StatusCode::UNAUTHORIZED, description = "Missing or wrong API key", body = Problem),
+        (status = StatusCode::INTERNAL_SERVER_ERROR, description = "Internal server error", body = Problem),
+    ),
+    tag = WEBHOOK_TAG
+)]
+pub async fn redeliver<C, G>(
+    State(service): State<Arc<WebhookService<C, G>>>,
+    Path((webhook_id, delivery_id)): Path<(String, String)>,
+) -> Result<WebhookDeliveryResponse, ApplicationError>
+where
+    C: Clock,
+    G: IdGenerator<Ulid>,
+{
+    let delivery = service.redeliver(&webhook_id, &delivery_id).await?;
+
+    Ok(delivery.into())
+}
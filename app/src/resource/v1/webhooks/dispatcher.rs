@@ -0,0 +1,295 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use futures::future::join_all;
+use reqwest::redirect::Policy;
+use tokio::{
+    task::JoinHandle,
+    time::{interval, MissedTickBehavior},
+};
+use tracing::{error, info, warn};
+
+use crate::{
+    common::ApplicationError,
+    docs::v1::webhooks::{DeliveryAttemptModel, DeliveryStatus, PendingDeliveryModel},
+    util::{check_public_url, hmac_sha256_hex, random_token, Clock, PublicResolver, Shutdown},
+};
+
+use super::WebhookRepository;
+
+type ServiceResult<T> = Result<T, ApplicationError>;
+
+pub static DEFAULT_WEBHOOK_INTERVAL: Duration = Duration::from_secs(10);
+pub static DEFAULT_MAX_ATTEMPTS: u32 = 8;
+
+pub static ID_HEADER: &str = "webhook-id";
+pub static EVENT_HEADER: &str = "webhook-event";
+pub static TIMESTAMP_HEADER: &str = "webhook-timestamp";
+pub static SIGNATURE_HEADER: &str = "webhook-signature";
+
+static DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Deliveries claimed, and sent side by side, per tick.
+static CLAIM_BATCH_SIZE: usize = 20;
+
+/// How long a claimed delivery may stay unanswered before it is assumed the
+/// dispatcher holding it stopped, and another one takes it over. Well above
+/// the [`DELIVERY_TIMEOUT`] of every delivery in a batch one after the other,
+/// so a live dispatcher never loses a claim to another and both send it.
+static CLAIM_TIMEOUT_MINUTES: i64 = 5;
+
+static FIRST_RETRY_SECONDS: i64 = 30;
+static MAX_RETRY_SECONDS: i64 = 6 * 60 * 60;
+
+/// Value of the signature header for a payload sent at `timestamp`, in Unix
+/// seconds. Receivers recompute it with their copy of the secret.
+pub fn sign(secret: &str, timestamp: i64, payload: &str) -> String {
+    let signed = format!("{}.{}", timestamp, payload);
+
+    format!(
+        "sha256={}",
+        hmac_sha256_hex(secret.as_bytes(), signed.as_bytes())
+    )
+}
+
+/// Wait before the next attempt once `attempts` attempts failed, doubling
+/// from 30 seconds up to 6 hours.
+pub fn retry_delay(attempts: u32) -> ChronoDuration {
+    let factor = 2i64.saturating_pow(attempts.saturating_sub(1));
+
+    ChronoDuration::seconds(
+        FIRST_RETRY_SECONDS
+            .saturating_mul(factor)
+            .min(MAX_RETRY_SECONDS),
+    )
+}
+
+/// Redirects are not followed, they could lead anywhere the URL was not
+/// allowed to. Unless private URLs are allowed, names are only connected to
+/// at their public addresses.
+fn delivery_client(allow_private_urls: bool) -> reqwest::Client {
+    let builder = reqwest::Client::builder()
+        .timeout(DELIVERY_TIMEOUT)
+        .redirect(Policy::none());
+
+    let builder = match allow_private_urls {
+        true => builder,
+        false => builder.dns_resolver(Arc::new(PublicResolver)),
+    };
+
+    builder.build().expect("webhook delivery client builds")
+}
+
+struct DeliveryFailure {
+    status_code: Option<u16>,
+    error: String,
+}
+
+/// Background task that sends queued webhook deliveries.
+///
+/// Deliveries are claimed in the database before they are sent, so several
+/// servers never send the same attempt twice. A delivery that fails is
+/// retried with exponential backoff and, after the last attempt, left in the
+/// dead state until it is redelivered by hand.
+pub struct WebhookDispatcher<C: Clock> {
+    repository: WebhookRepository,
+    clock: C,
+    client: reqwest::Client,
+    allow_private_urls: bool,
+    interval: Duration,
+    shutdown: Shutdown,
+    max_attempts: u32,
+}
+
+impl<C: Clock> WebhookDispatcher<C> {
+    pub fn new(repository: WebhookRepository, clock: C) -> Self {
+        Self {
+            repository,
+            clock,
+            client: delivery_client(false),
+            allow_private_urls: false,
+            interval: DEFAULT_WEBHOOK_INTERVAL,
+            shutdown: Shutdown::new(),
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+        }
+    }
+
+    /// Delivers to loopback and private network addresses too, for local
+    /// development.
+    pub fn with_private_urls(mut self, allowed: bool) -> Self {
+        self.client = delivery_client(allowed);
+        self.allow_private_urls = allowed;
+
+        self
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+
+        self
+    }
+
+    /// Stops the spawned task between ticks once `shutdown` is triggered.
+    pub fn with_shutdown(mut self, shutdown: Shutdown) -> Self {
+        self.shutdown = shutdown;
+
+        self
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+
+        self
+    }
+
+    /// Attempts a batch of the deliveries due by the clock's now, all at
+    /// once, returning how many were delivered.
+    pub async fn tick(&self) -> ServiceResult<usize> {
+        let now = self.clock.now();
+        let claim = random_token(16);
+
+        let due_deliveries = self
+            .repository
+            .claim_due_deliveries(
+                &claim,
+                now,
+                now - ChronoDuration::minutes(CLAIM_TIMEOUT_MINUTES),
+                CLAIM_BATCH_SIZE,
+            )
+            .await?;
+
+        let attempts = join_all(
+            due_deliveries
+                .iter()
+                .map(|delivery| self.attempt(delivery, &claim)),
+        )
+        .await;
+
+        attempts.into_iter().try_fold(
+            0,
+            |delivered, attempt| Ok(delivered + usize::from(attempt?)),
+        )
+    }
+
+    /// Sends a claimed delivery and records the attempt, returning whether it
+    /// was delivered.
+    async fn attempt(&self, delivery: &PendingDeliveryModel, claim: &str) -> ServiceResult<bool> {
+        let attempts = delivery.attempts + 1;
+        let attempted_at = self.clock.now();
+
+        let (delivered, attempt) = match self.send(delivery, attempted_at).await {
+            Ok(status_code) => (
+                true,
+                DeliveryAttemptModel {
+                    status: DeliveryStatus::Delivered,
+                    attempts,
+                    next_attempt_at: attempted_at,
+                    last_status_code: Some(status_code),
+                    last_error: None,
+                    delivered_at: Some(attempted_at),
+                },
+            ),
+            Err(failure) => {
+                let status = if delivery.url.is_none() || attempts >= self.max_attempts {
+                    DeliveryStatus::Dead
+                } else {
+                    DeliveryStatus::Pending
+                };
+
+                warn!(
+                    "Webhook delivery {} failed on attempt {}: {}",
+                    delivery.id, attempts, failure.error
+                );
+
+                (
+                    false,
+                    DeliveryAttemptModel {
+                        status,
+                        attempts,
+                        next_attempt_at: attempted_at + retry_delay(attempts),
+                        last_status_code: failure.status_code,
+                        last_error: Some(failure.error),
+                        delivered_at: None,
+                    },
+                )
+            }
+        };
+
+        self.repository
+            .record_attempt(&delivery.id, claim, attempt)
+            .await?;
+
+        Ok(delivered)
+    }
+
+    /// Runs [`Self::tick`] on the interval until shut down or aborted.
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticks = interval(self.interval);
+            ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                tokio::select! {
+                    _ = ticks.tick() => {}
+                    _ = self.shutdown.triggered() => break,
+                }
+
+                if let Err(err) = self.tick().await {
+                    error!("Webhook dispatcher tick failed: {:?}", err);
+                }
+            }
+
+            info!("Webhook dispatcher stopped");
+        })
+    }
+
+    async fn send(
+        &self,
+        delivery: &PendingDeliveryModel,
+        attempted_at: DateTime<Utc>,
+    ) -> Result<u16, DeliveryFailure> {
+        let (Some(url), Some(secret)) = (&delivery.url, &delivery.secret) else {
+            return Err(DeliveryFailure {
+                status_code: None,
+                error: String::from("the webhook no longer exists"),
+            });
+        };
+        // Webhooks registered before addresses were checked may still point
+        // inside, and addresses are not resolved, so not filtered, by the client.
+        if !self.allow_private_urls {
+            check_public_url(url).map_err(|error| DeliveryFailure {
+                status_code: None,
+                error,
+            })?;
+        }
+
+        let timestamp = attempted_at.timestamp();
+
+        let response = self
+            .client
+            .post(url)
+            .header("content-type", "application/json")
+            .header(ID_HEADER, delivery.id.id.to_raw())
+            .header(EVENT_HEADER, &delivery.event)
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(SIGNATURE_HEADER, sign(secret, timestamp, &delivery.payload))
+            .body(delivery.payload.clone())
+            .send()
+            .await
+            .map_err(|err| DeliveryFailure {
+                status_code: None,
+                error: err.to_string(),
+            })?;
+
+        let status = response.status();
+
+        if !status.is_success() {
+            return Err(DeliveryFailure {
+                status_code: Some(status.as_u16()),
+                error: format!("receiver answered with {}", status),
+            });
+        }
+
+        Ok(status.as_u16())
+    }
+}
@@ -0,0 +1,11 @@
+pub mod controller;
+pub mod dispatcher;
+pub mod repository;
+pub mod service;
+pub mod subscriber;
+
+pub use controller::*;
+pub use dispatcher::*;
+pub use repository::*;
+pub use service::*;
+pub use subscriber::*;
@@ -0,0 +1,327 @@
+use chrono::{DateTime, Utc};
+use surrealdb::sql::{Datetime, Thing};
+use ulid::Ulid;
+
+use crate::{
+    common::{DatabaseDriver, RepositoryError},
+    docs::v1::webhooks::{
+        DeliveryAttemptModel, DeliveryStatus, PendingDeliveryModel, WebhookDeliveryModel,
+        WebhookDeliveryModelInsert, WebhookModel, WebhookModelUpdate,
+    },
+};
+
+type RepositoryResult<T> = Result<T, RepositoryError>;
+
+/// Most recent deliveries listed for a webhook.
+static DELIVERIES_LIMIT: u32 = 100;
+
+#[derive(Clone)]
+pub struct WebhookRepository {
+    pub driver: DatabaseDriver,
+}
+
+impl WebhookRepository {
+    pub fn new(driver: DatabaseDriver) -> Self {
+        Self { driver }
+    }
+
+    pub async fn get_webhooks(&self) -> RepositoryResult<Vec<WebhookModel>> {
+        let mut response = self
+            .driver
+            .client
+            .query("SELECT * FROM webhook ORDER BY created_at")
+            .await?;
+
+        let result: Vec<WebhookModel> = response.take(0)?;
+
+        Ok(result)
+    }
+
+    pub async fn get_webhook_by_id(&self, id: &Ulid) -> RepositoryResult<WebhookModel> {
+        let result: Option<WebhookModel> = self
+            .driver
+            .client
+            .select(("webhook", &id.to_string()))
+            .await?;
+
+        if let Some(webhook) = result {
+            return Ok(webhook);
+        }
+
+        Err(RepositoryError::NotFound(id.to_string()))
+    }
+
+    /// Webhooks subscribed to the event, directly or through `*`.
+    pub async fn get_subscribers(&self, event: &str) -> RepositoryResult<Vec<WebhookModel>> {
+        let mut response = self
+            .driver
+            .client
+            .query("SELECT * FROM webhook WHERE events CONTAINS $event OR events CONTAINS '*'")
+            .bind(("event", event))
+            .await?;
+
+        let result: Vec<WebhookModel> = response.take(0)?;
+
+        Ok(result)
+    }
+
+    pub async fn create_webhook(
+        &self,
+        id: &Ulid,
+        url: String,
+        events: Vec<String>,
+        secret: String,
+        created_at: DateTime<Utc>,
+    ) -> RepositoryResult<WebhookModel> {
+        let mut response = self
+            .driver
+            .client
+            .query(
+                r#"
+                CREATE type::thing('webhook', $id) CONTENT {
+                    url: $url,
+                    events: $events,
+                    secret: $secret,
+                    created_at: $created_at,
+                    updated_at: $created_at,
+                }
+                "#,
+            )
+            .bind(("id", id.to_string()))
+            .bind(("url", url))
+            .bind(("events", events))
+            .bind(("secret", secret))
+            .bind(("created_at", Datetime(created_at)))
+            .await?;
+
+        let result: Option<WebhookModel> = response.take(0)?;
+
+        match result {
+            Some(webhook) => Ok(webhook),
+            None => Err(RepositoryError::InsertError(format!(
+                "Webhook({}) not returned after inserting into the DB",
+                id
+            ))),
+        }
+    }
+
+    pub async fn update_webhook(
+        &self,
+        id: &Ulid,
+        updated_webhook: WebhookModelUpdate,
+    ) -> RepositoryResult<WebhookModel> {
+        let webhook: Option<WebhookModel> = self
+            .driver
+            .client
+            .update(("webhook", id.to_string()))
+            .merge(updated_webhook)
+            .await?;
+
+        if let Some(webhook) = webhook {
+            return Ok(webhook);
+        }
+
+        Err(RepositoryError::NotFound(id.to_string()))
+    }
+
+    /// Deletes the webhook along with its deliveries, pending ones included.
+    pub async fn delete_webhook(&self, id: &Ulid) -> RepositoryResult<WebhookModel> {
+        let result: Option<WebhookModel> = self
+            .driver
+            .client
+            .delete(("webhook", &id.to_string()))
+            .await?;
+
+        if let Some(webhook) = result {
+            self.driver
+                .client
+                .query("DELETE webhook_delivery WHERE webhook = $webhook")
+                .bind(("webhook", &webhook.id))
+                .await?
+                .check()?;
+
+            return Ok(webhook);
+        }
+
+        Err(RepositoryError::NotFound(id.to_string()))
+    }
+
+    pub async fn enqueue_deliveries(
+        &self,
+        deliveries: Vec<WebhookDeliveryModelInsert>,
+    ) -> RepositoryResult<()> {
+        if deliveries.is_empty() {
+            return Ok(());
+        }
+
+        self.driver
+            .client
+            .query("INSERT IGNORE INTO webhook_delivery $deliveries")
+            .bind(("deliveries", deliveries))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+
+    /// Latest deliveries of a webhook first.
+    pub async fn get_deliveries(
+        &self,
+        webhook_id: &Ulid,
+        status: Option<DeliveryStatus>,
+    ) -> RepositoryResult<Vec<WebhookDeliveryModel>> {
+        let status_condition = match status {
+            Some(_) => "AND status = $status",
+            None => "",
+        };
+
+        let mut response = self
+            .driver
+            .client
+            .query(format!(
+                r#"
+                SELECT * FROM webhook_delivery
+                WHERE webhook = type::thing('webhook', $webhook) {}
+                ORDER BY created_at DESC
+                LIMIT {}
+                "#,
+                status_condition, DELIVERIES_LIMIT
+            ))
+            .bind(("webhook", webhook_id.to_string()))
+            .bind(("status", status))
+            .await?;
+
+        let result: Vec<WebhookDeliveryModel> = response.take(0)?;
+
+        Ok(result)
+    }
+
+    /// Queues the delivery again with a fresh set of attempts, whatever state
+    /// it is in.
+    pub async fn redeliver(
+        &self,
+        webhook_id: &Ulid,
+        delivery_id: &str,
+        now: DateTime<Utc>,
+    ) -> RepositoryResult<WebhookDeliveryModel> {
+        let mut response = self
+            .driver
+            .client
+            .query(
+                r#"
+                UPDATE type::thing('webhook_delivery', $id) SET
+                    status = 'pending',
+                    attempts = 0,
+                    next_attempt_at = $now,
+                    last_status_code = NONE,
+                    last_error = NONE,
+                    delivered_at = NONE,
+                    claimed_by = NONE,
+                    claimed_at = NONE
+                WHERE webhook = type::thing('webhook', $webhook)
+                "#,
+            )
+            .bind(("id", delivery_id))
+            .bind(("webhook", webhook_id.to_string()))
+            .bind(("now", Datetime(now)))
+            .await?;
+
+        let result: Option<WebhookDeliveryModel> = response.take(0)?;
+
+        match result {
+            Some(delivery) => Ok(delivery),
+            None => Err(RepositoryError::NotFound(delivery_id.to_string())),
+        }
+    }
+
+    /// Claims up to `limit` pending deliveries whose next attempt is due by
+    /// `now`, the longest overdue first. Claims older than `stale_before` were
+    /// left behind by a dispatcher that stopped mid-delivery and are taken
+    /// over.
+    pub async fn claim_due_deliveries(
+        &self,
+        claim: &str,
+        now: DateTime<Utc>,
+        stale_before: DateTime<Utc>,
+        limit: usize,
+    ) -> RepositoryResult<Vec<PendingDeliveryModel>> {
+        let mut response = self
+            .driver
+            .client
+            .query(
+                r#"
+                BEGIN TRANSACTION;
+                LET $due = (
+                    SELECT id, next_attempt_at FROM webhook_delivery
+                    WHERE status = 'pending'
+                        AND next_attempt_at <= $now
+                        AND (claimed_at = NONE OR claimed_at < $stale_before)
+                    ORDER BY next_attempt_at
+                    LIMIT $limit
+                );
+                UPDATE $due.id SET claimed_by = $claim, claimed_at = $now;
+                RETURN (
+                    SELECT
+                        id,
+                        event,
+                        payload,
+                        attempts,
+                        next_attempt_at,
+                        webhook.url AS url,
+                        webhook.secret AS secret
+                    FROM webhook_delivery
+                    WHERE claimed_by = $claim AND status = 'pending'
+                    ORDER BY next_attempt_at
+                );
+                COMMIT TRANSACTION;
+                "#,
+            )
+            .bind(("claim", claim))
+            .bind(("now", Datetime(now)))
+            .bind(("stale_before", Datetime(stale_before)))
+            .bind(("limit", limit))
+            .await?;
+
+        let result: Vec<PendingDeliveryModel> = response.take(0)?;
+
+        Ok(result)
+    }
+
+    /// Records an attempt and gives up the claim, unless the claim was lost
+    /// in the meantime.
+    pub async fn record_attempt(
+        &self,
+        id: &Thing,
+        claim: &str,
+        attempt: DeliveryAttemptModel,
+    ) -> RepositoryResult<()> {
+        self.driver
+            .client
+            .query(
+                r#"
+                UPDATE $id SET
+                    status = $status,
+                    attempts = $attempts,
+                    next_attempt_at = $next_attempt_at,
+                    last_status_code = $last_status_code,
+                    last_error = $last_error,
+                    delivered_at = $delivered_at,
+                    claimed_by = NONE,
+                    claimed_at = NONE
+                WHERE claimed_by = $claim
+                "#,
+            )
+            .bind(("id", id))
+            .bind(("claim", claim))
+            .bind(("status", attempt.status))
+            .bind(("attempts", attempt.attempts))
+            .bind(("next_attempt_at", Datetime(attempt.next_attempt_at)))
+            .bind(("last_status_code", attempt.last_status_code))
+            .bind(("last_error", attempt.last_error))
+            .bind(("delivered_at", attempt.delivered_at.map(Datetime)))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+}
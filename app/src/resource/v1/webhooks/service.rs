@@ -0,0 +1,220 @@
+use surrealdb::sql::Datetime;
+use ulid::Ulid;
+
+use crate::{
+    common::ApplicationError,
+    docs::v1::{
+        events::EventKind,
+        webhooks::{
+            CreateWebhookRequest, DeliveryStatus, UpdateWebhookRequest, Webhook, WebhookDelivery,
+            WebhookDeliveryModel, WebhookModel, WebhookModelUpdate, ANY_EVENT,
+        },
+    },
+    util::{check_public_url, random_token, Clock, IdGenerator},
+};
+
+use super::WebhookRepository;
+
+type ServiceResult<T> = Result<T, ApplicationError>;
+
+static SECRET_BYTES: usize = 32;
+
+pub struct WebhookService<C, G>
+where
+    C: Clock,
+    G: IdGenerator<Ulid>,
+{
+    repository: WebhookRepository,
+    clock: C,
+    id_generator: G,
+    allow_private_urls: bool,
+}
+
+impl<C, G> WebhookService<C, G>
+where
+    C: Clock,
+    G: IdGenerator<Ulid>,
+{
+    pub fn new(repository: WebhookRepository, clock: C, id_generator: G) -> Self {
+        Self {
+            repository,
+            clock,
+            id_generator,
+            allow_private_urls: false,
+        }
+    }
+
+    /// Accepts URLs on loopback and private network addresses, for local
+    /// development.
+    pub fn with_private_urls(mut self, allowed: bool) -> Self {
+        self.allow_private_urls = allowed;
+
+        self
+    }
+
+    pub async fn get_webhooks(&self) -> ServiceResult<Vec<Webhook>> {
+        let webhooks = self.repository.get_webhooks().await?;
+
+        Ok(webhooks.into_iter().map(model_to_domain).collect())
+    }
+
+    pub async fn get_webhook(&self, id: &str) -> ServiceResult<Webhook> {
+        let id = self.id_generator.parse(id)?;
+        let webhook = self.repository.get_webhook_by_id(&id).await?;
+
+        Ok(model_to_domain(webhook))
+    }
+
+    /// The secret is returned this once, a random one is made when none is
+    /// given.
+    pub async fn create_webhook(&self, webhook: CreateWebhookRequest) -> ServiceResult<Webhook> {
+        let issues = [self.check_url(&webhook.url), check_events(&webhook.events)].concat();
+
+        if !issues.is_empty() {
+            return Err(ApplicationError::ValidationError(issues));
+        }
+
+        let secret = webhook.secret.unwrap_or_else(|| random_token(SECRET_BYTES));
+
+        let model = self
+            .repository
+            .create_webhook(
+                &self.id_generator.generate(),
+                webhook.url,
+                webhook.events,
+                secret.clone(),
+                self.clock.now(),
+            )
+            .await?;
+
+        Ok(Webhook {
+            secret: Some(secret),
+            ..model_to_domain(model)
+        })
+    }
+
+    pub async fn update_webhook(
+        &self,
+        id: &str,
+        update: UpdateWebhookRequest,
+    ) -> ServiceResult<Webhook> {
+        let id = self.id_generator.parse(id)?;
+
+        let issues = [
+            update
+                .url
+                .as_deref()
+                .map(|url| self.check_url(url))
+                .unwrap_or_default(),
+            update
+                .events
+                .as_deref()
+                .map(check_events)
+                .unwrap_or_default(),
+        ]
+        .concat();
+
+        if !issues.is_empty() {
+            return Err(ApplicationError::ValidationError(issues));
+        }
+
+        let existing_webhook = self.repository.get_webhook_by_id(&id).await?;
+        let updated_webhook = WebhookModelUpdate {
+            url: update.url.unwrap_or(existing_webhook.url),
+            events: update.events.unwrap_or(existing_webhook.events),
+            secret: update.secret.clone().unwrap_or(existing_webhook.secret),
+            updated_at: Datetime(self.clock.now()),
+        };
+
+        let webhook = self.repository.update_webhook(&id, updated_webhook).await?;
+
+        Ok(Webhook {
+            secret: update.secret,
+            ..model_to_domain(webhook)
+        })
+    }
+
+    pub async fn delete_webhook(&self, id: &str) -> ServiceResult<Webhook> {
+        let id = self.id_generator.parse(id)?;
+
+        let webhook = self.repository.delete_webhook(&id).await?;
+
+        Ok(model_to_domain(webhook))
+    }
+
+    pub async fn get_deliveries(
+        &self,
+        id: &str,
+        status: Option<DeliveryStatus>,
+    ) -> ServiceResult<Vec<WebhookDelivery>> {
+        let id = self.id_generator.parse(id)?;
+        self.repository.get_webhook_by_id(&id).await?;
+
+        let deliveries = self.repository.get_deliveries(&id, status).await?;
+
+        Ok(deliveries.into_iter().map(delivery_to_domain).collect())
+    }
+
+    /// Sends a delivery again on the dispatcher's next tick, with the same
+    /// body and a fresh round of retries. Dead deliveries are revived this way.
+    pub async fn redeliver(&self, id: &str, delivery_id: &str) -> ServiceResult<WebhookDelivery> {
+        let id = self.id_generator.parse(id)?;
+
+        let delivery = self
+            .repository
+            .redeliver(&id, delivery_id, self.clock.now())
+            .await?;
+
+        Ok(delivery_to_domain(delivery))
+    }
+
+    fn check_url(&self, url: &str) -> Vec<String> {
+        if self.allow_private_urls && (url.starts_with("https://") || url.starts_with("http://")) {
+            return vec![];
+        }
+
+        match check_public_url(url) {
+            Ok(()) => vec![],
+            Err(issue) => vec![format!("url: {}", issue)],
+        }
+    }
+}
+
+fn check_events(events: &[String]) -> Vec<String> {
+    events
+        .iter()
+        .filter(|event| *event != ANY_EVENT && EventKind::from_name(event).is_none())
+        .map(|event| {
+            format!(
+                "events: `{}` is not an event such as todo.created, or {} for every event",
+                event, ANY_EVENT
+            )
+        })
+        .collect()
+}
+
+fn model_to_domain(model: WebhookModel) -> Webhook {
+    Webhook {
+        id: model.id.id.to_raw(),
+        url: model.url,
+        events: model.events,
+        secret: None,
+        created_at: model.created_at,
+        updated_at: model.updated_at,
+    }
+}
+
+fn delivery_to_domain(model: WebhookDeliveryModel) -> WebhookDelivery {
+    WebhookDelivery {
+        id: model.id.id.to_raw(),
+        webhook_id: model.webhook.id.to_raw(),
+        event: model.event,
+        status: model.status,
+        attempts: model.attempts,
+        next_attempt_at: model.next_attempt_at,
+        last_status_code: model.last_status_code,
+        last_error: model.last_error,
+        created_at: model.created_at,
+        delivered_at: model.delivered_at,
+    }
+}
@@ -0,0 +1,75 @@
+use axum::async_trait;
+use surrealdb::sql::Datetime;
+
+use crate::{
+    docs::v1::{
+        events::{DomainEvent, TodoEvent},
+        todos::TodoResponse,
+        webhooks::{DeliveryStatus, WebhookDeliveryModelInsert, WebhookPayload},
+    },
+    resource::v1::events::{EventSubscriber, SubscriberError},
+};
+
+use super::WebhookRepository;
+
+/// Queues one delivery per subscribed webhook for every todo event. The
+/// [`super::WebhookDispatcher`] sends them in the background.
+#[derive(Clone)]
+pub struct WebhookSubscriber {
+    repository: WebhookRepository,
+}
+
+impl WebhookSubscriber {
+    pub fn new(repository: WebhookRepository) -> Self {
+        Self { repository }
+    }
+}
+
+#[async_trait]
+impl EventSubscriber for WebhookSubscriber {
+    fn name(&self) -> &'static str {
+        "webhooks"
+    }
+
+    /// Delivery ids are made of the event and the webhook, so an event that
+    /// is published again is not queued twice.
+    async fn handle(&self, event: &DomainEvent) -> Result<(), SubscriberError> {
+        let kind = event.event.kind();
+        let subscribers = self.repository.get_subscribers(kind.name()).await?;
+
+        if subscribers.is_empty() {
+            return Ok(());
+        }
+
+        let payload = WebhookPayload {
+            id: event.id.clone(),
+            event: kind.name().to_string(),
+            occurred_at: event.occurred_at,
+            todo: TodoResponse::from(event.event.todo().clone()),
+            changes: match &event.event {
+                TodoEvent::TodoUpdated { changes, .. } => Some(changes.clone()),
+                _ => None,
+            },
+        };
+        let payload =
+            serde_json::to_string(&payload).map_err(|err| SubscriberError(err.to_string()))?;
+
+        let deliveries = subscribers
+            .iter()
+            .map(|webhook| WebhookDeliveryModelInsert {
+                id: format!("{}_{}", event.id, webhook.id.id.to_raw()),
+                webhook: webhook.id.clone(),
+                event: kind.name().to_string(),
+                payload: payload.clone(),
+                status: DeliveryStatus::Pending,
+                attempts: 0,
+                next_attempt_at: Datetime(event.occurred_at),
+                created_at: Datetime(event.occurred_at),
+            })
+            .collect();
+
+        self.repository.enqueue_deliveries(deliveries).await?;
+
+        Ok(())
+    }
+}
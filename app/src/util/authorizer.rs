@@ -1 +1,68 @@
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
 
+use crate::common::ApplicationError;
+
+use super::sha256_hex;
+
+pub static API_KEY_HEADER: &str = "x-api-key";
+
+/// The key callers send to use the routes that need one. Only its digest is
+/// kept, and while none is configured those routes refuse every caller.
+#[derive(Clone, Debug, Default)]
+pub struct ApiKey {
+    digest: Option<String>,
+}
+
+impl ApiKey {
+    pub fn new(key: Option<&str>) -> Self {
+        Self {
+            digest: key
+                .filter(|key| !key.is_empty())
+                .map(|key| sha256_hex(key.as_bytes())),
+        }
+    }
+
+    /// Digests are compared rather than the keys, so the time taken does not
+    /// tell how much of a guess was right.
+    pub fn accepts(&self, presented: &str) -> bool {
+        self.digest
+            .as_deref()
+            .is_some_and(|digest| digest == sha256_hex(presented.as_bytes()))
+    }
+}
+
+/// The `x-api-key` header, or else the token of a bearer `Authorization`.
+pub fn api_key(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .or_else(|| {
+            headers
+                .get(header::AUTHORIZATION)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.strip_prefix("Bearer "))
+        })
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+}
+
+/// Middleware answering `401 Unauthorized` unless the request carries the
+/// configured [`ApiKey`].
+pub async fn require_api_key<B>(
+    State(key): State<ApiKey>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    match api_key(request.headers()) {
+        Some(presented) if key.accepts(presented) => next.run(request).await,
+        _ => ApplicationError::Unauthorized(String::from(
+            "Send the API key in x-api-key or as a bearer token.",
+        ))
+        .into_response(),
+    }
+}
@@ -0,0 +1,79 @@
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+
+use axum::{
+    extract::State,
+    http::Request,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::common::ApplicationError;
+
+/// Consecutive failures after which the breaker opens.
+pub static DEFAULT_FAILURE_THRESHOLD: u32 = 3;
+
+/// Opens after a number of failures in a row and closes on the next success.
+/// Whoever records them, the database monitor for one, is also what probes
+/// whether the dependency is back. Clones share the same state.
+#[derive(Clone, Debug)]
+pub struct CircuitBreaker {
+    failures: Arc<AtomicU32>,
+    threshold: u32,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(DEFAULT_FAILURE_THRESHOLD)
+    }
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: u32) -> Self {
+        Self {
+            failures: Arc::new(AtomicU32::new(0)),
+            threshold: threshold.max(1),
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.failures.load(Ordering::SeqCst) >= self.threshold
+    }
+
+    /// Closes the breaker, returning whether it was open.
+    pub fn record_success(&self) -> bool {
+        self.failures.swap(0, Ordering::SeqCst) >= self.threshold
+    }
+
+    /// Returns whether this failure opened the breaker.
+    pub fn record_failure(&self) -> bool {
+        let failures = self
+            .failures
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |failures| {
+                Some(failures.saturating_add(1))
+            })
+            .unwrap_or_default();
+
+        failures + 1 == self.threshold
+    }
+}
+
+/// Middleware answering `503 Service Unavailable` right away while the
+/// database breaker is open, rather than letting requests wait on a
+/// connection that is down.
+pub async fn reject_while_open<B>(
+    State(breaker): State<CircuitBreaker>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    if breaker.is_open() {
+        return ApplicationError::Unavailable(String::from(
+            "The database is unreachable, try again shortly.",
+        ))
+        .into_response();
+    }
+
+    next.run(request).await
+}
@@ -0,0 +1,55 @@
+/// Marks the database puts around every matched term. Private use characters
+/// cannot be typed into a todo by accident, unlike markup.
+pub static HIGHLIGHT_START: char = '\u{E000}';
+pub static HIGHLIGHT_END: char = '\u{E001}';
+
+/// Turns text highlighted with [`HIGHLIGHT_START`] and [`HIGHLIGHT_END`] into
+/// HTML safe to render: the text is escaped and only the matches are wrapped
+/// in `<mark>` tags.
+pub fn highlight_html(highlighted: &str) -> String {
+    let mut html = String::with_capacity(highlighted.len());
+
+    for c in highlighted.chars() {
+        match c {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#x27;"),
+            c if c == HIGHLIGHT_START => html.push_str("<mark>"),
+            c if c == HIGHLIGHT_END => html.push_str("</mark>"),
+            c => html.push(c),
+        }
+    }
+
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_matches_in_mark_tags() {
+        assert_eq!(
+            highlight_html("buy \u{E000}milk\u{E001} today"),
+            "buy <mark>milk</mark> today"
+        );
+    }
+
+    #[test]
+    fn escapes_markup_in_the_text() {
+        assert_eq!(
+            highlight_html("<img src=x onerror=\"alert('\u{E000}x\u{E001}')\"> & co"),
+            "&lt;img src=x onerror=&quot;alert(&#x27;<mark>x</mark>&#x27;)&quot;&gt; &amp; co"
+        );
+    }
+
+    #[test]
+    fn leaves_typed_mark_tags_escaped() {
+        assert_eq!(
+            highlight_html("<mark>x</mark>"),
+            "&lt;mark&gt;x&lt;/mark&gt;"
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{DefaultBodyLimit, State},
+    http::{header, HeaderName, HeaderValue, Method, Request},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    Router,
+};
+use tower_http::{
+    compression::CompressionLayer,
+    cors::{AllowOrigin, Any, CorsLayer},
+};
+use tracing::warn;
+
+use crate::common::{ApplicationError, HttpConfig, ANY_ORIGIN, TIME_ZONE_HEADER};
+
+use super::{
+    API_KEY_HEADER, RATE_LIMIT_LIMIT, RATE_LIMIT_POLICY, RATE_LIMIT_REMAINING, RATE_LIMIT_RESET,
+    REQUEST_ID_HEADER,
+};
+
+/// Nothing in an API response is meant to be run or framed.
+static API_CONTENT_SECURITY_POLICY: &str = "default-src 'none'; frame-ancestors 'none'";
+
+/// The RapiDoc page loads its script from unpkg and styles itself inline.
+pub static DOCS_CONTENT_SECURITY_POLICY: &str = "default-src 'self'; \
+    script-src 'self' https://unpkg.com; style-src 'self' 'unsafe-inline'; \
+    img-src 'self' data:; font-src 'self' data:; frame-ancestors 'none'";
+
+/// Wraps every route of `app` in the middleware turned on in `config`, from
+/// the innermost: body size limit, timeout, security headers, compression and
+/// CORS, so preflight requests are answered before anything else runs.
+pub fn with_http_layers(app: Router, config: &HttpConfig) -> Router {
+    let app = match config.body_limit_bytes {
+        0 => app.layer(DefaultBodyLimit::disable()),
+        limit => app.layer(DefaultBodyLimit::max(limit)),
+    };
+
+    let app = match config.request_timeout_seconds {
+        0 => app,
+        seconds => app.layer(middleware::from_fn_with_state(
+            Duration::from_secs(seconds),
+            time_out_requests,
+        )),
+    };
+
+    let app = if config.security_headers_enabled {
+        app.layer(middleware::from_fn_with_state(
+            SecurityHeaders::new(config),
+            set_security_headers,
+        ))
+    } else {
+        app
+    };
+
+    // Brotli and gzip only, as most clients accept both.
+    let app = if config.compression_enabled {
+        app.layer(CompressionLayer::new().no_deflate().no_zstd())
+    } else {
+        app
+    };
+
+    if config.cors_enabled {
+        app.layer(cors_layer(config))
+    } else {
+        app
+    }
+}
+
+/// Allows the configured origins and methods, with the headers the API reads
+/// and the ones it answers that a browser script may want to see.
+pub fn cors_layer(config: &HttpConfig) -> CorsLayer {
+    let origins = if config
+        .cors_allowed_origins
+        .iter()
+        .any(|origin| origin == ANY_ORIGIN)
+    {
+        AllowOrigin::from(Any)
+    } else {
+        AllowOrigin::list(config.cors_allowed_origins.iter().filter_map(|origin| {
+            HeaderValue::from_str(origin)
+                .map_err(|_| warn!("CORS origin {} is not a header value, skipped", origin))
+                .ok()
+        }))
+    };
+    let methods = config
+        .cors_allowed_methods
+        .iter()
+        .filter_map(|method| {
+            Method::from_bytes(method.as_bytes())
+                .map_err(|_| warn!("CORS method {} is not an HTTP method, skipped", method))
+                .ok()
+        })
+        .collect::<Vec<_>>();
+
+    CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods(methods)
+        .allow_headers([
+            header::AUTHORIZATION,
+            header::CONTENT_TYPE,
+            HeaderName::from_static(API_KEY_HEADER),
+            HeaderName::from_static(REQUEST_ID_HEADER),
+            HeaderName::from_static(TIME_ZONE_HEADER),
+        ])
+        .expose_headers([
+            header::RETRY_AFTER,
+            HeaderName::from_static(REQUEST_ID_HEADER),
+            RATE_LIMIT_LIMIT.clone(),
+            RATE_LIMIT_REMAINING.clone(),
+            RATE_LIMIT_RESET.clone(),
+            RATE_LIMIT_POLICY.clone(),
+        ])
+        .allow_credentials(config.cors_allow_credentials)
+}
+
+#[derive(Clone)]
+pub struct SecurityHeaders {
+    strict_transport_security: Option<HeaderValue>,
+}
+
+impl SecurityHeaders {
+    pub fn new(config: &HttpConfig) -> Self {
+        let strict_transport_security = match config.hsts_max_age_seconds {
+            0 => None,
+            max_age => Some(
+                HeaderValue::from_str(&format!("max-age={}; includeSubDomains", max_age))
+                    .expect("HSTS is a valid header value"),
+            ),
+        };
+
+        Self {
+            strict_transport_security,
+        }
+    }
+}
+
+/// Middleware adding the security headers a route did not set itself, the
+/// docs answer with their own content security policy.
+pub async fn set_security_headers<B>(
+    State(security): State<SecurityHeaders>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+
+    if let Some(hsts) = security.strict_transport_security {
+        headers
+            .entry(header::STRICT_TRANSPORT_SECURITY)
+            .or_insert(hsts);
+    }
+    headers
+        .entry(header::X_CONTENT_TYPE_OPTIONS)
+        .or_insert(HeaderValue::from_static("nosniff"));
+    headers
+        .entry(header::X_FRAME_OPTIONS)
+        .or_insert(HeaderValue::from_static("DENY"));
+    headers
+        .entry(header::REFERRER_POLICY)
+        .or_insert(HeaderValue::from_static("no-referrer"));
+    headers
+        .entry(header::CONTENT_SECURITY_POLICY)
+        .or_insert(HeaderValue::from_static(API_CONTENT_SECURITY_POLICY));
+
+    response
+}
+
+/// Middleware answering `503 Service Unavailable` when a request takes longer
+/// than `timeout` to start answering. A streamed body, such as an export, is
+/// not cut off once it started.
+pub async fn time_out_requests<B>(
+    State(timeout): State<Duration>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    match tokio::time::timeout(timeout, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => ApplicationError::RequestTimeout(timeout.as_secs()).into_response(),
+    }
+}
@@ -0,0 +1,224 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+static PRODUCT_ID: &str = "-//ebukaume//rust-template//EN";
+static MAX_LINE_OCTETS: usize = 75;
+
+pub struct Component {
+    name: &'static str,
+    properties: Vec<String>,
+}
+
+impl Component {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            properties: vec![],
+        }
+    }
+
+    pub fn property(mut self, name: &str, value: &str) -> Self {
+        self.properties.push(format!("{}:{}", name, value));
+
+        self
+    }
+
+    pub fn text(self, name: &str, value: &str) -> Self {
+        self.property(name, &escape_text(value))
+    }
+
+    pub fn datetime(self, name: &str, value: &DateTime<Utc>) -> Self {
+        self.property(name, &format_datetime(value))
+    }
+
+    pub fn date(self, name: &str, value: &NaiveDate) -> Self {
+        self.property(
+            &format!("{};VALUE=DATE", name),
+            &value.format("%Y%m%d").to_string(),
+        )
+    }
+
+    pub fn render(&self) -> String {
+        let mut output = fold_line(&format!("BEGIN:{}", self.name));
+
+        for property in &self.properties {
+            output.push_str(&fold_line(property));
+        }
+
+        output.push_str(&fold_line(&format!("END:{}", self.name)));
+
+        output
+    }
+}
+
+pub fn calendar_header(name: &str) -> String {
+    [
+        fold_line("BEGIN:VCALENDAR"),
+        fold_line("VERSION:2.0"),
+        fold_line(&format!("PRODID:{}", PRODUCT_ID)),
+        fold_line("CALSCALE:GREGORIAN"),
+        fold_line(&format!("X-WR-CALNAME:{}", escape_text(name))),
+    ]
+    .concat()
+}
+
+pub fn calendar_footer() -> String {
+    fold_line("END:VCALENDAR")
+}
+
+pub fn format_datetime(value: &DateTime<Utc>) -> String {
+    value.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+pub fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Splits a content line into CRLF terminated chunks of at most 75 octets,
+/// continuation lines starting with a single space as RFC 5545 requires.
+pub fn fold_line(line: &str) -> String {
+    let mut output = String::with_capacity(line.len() + 2);
+    let mut width = 0;
+
+    for c in line.chars() {
+        if width + c.len_utf8() > MAX_LINE_OCTETS {
+            output.push_str("\r\n ");
+            width = 1;
+        }
+
+        output.push(c);
+        width += c.len_utf8();
+    }
+
+    output.push_str("\r\n");
+
+    output
+}
+
+#[derive(Debug, Clone)]
+pub struct Property {
+    pub name: String,
+    pub params: Vec<(String, String)>,
+    pub value: String,
+}
+
+impl Property {
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Returns the properties of every `name` component in the calendar, in the
+/// order they appear. Nested components (such as VALARM) are skipped.
+pub fn parse_components(input: &str, name: &str) -> Vec<Vec<Property>> {
+    let mut components = vec![];
+    let mut current: Option<Vec<Property>> = None;
+    let mut depth = 0;
+
+    for line in unfold_lines(input) {
+        let Some(property) = parse_property(&line) else {
+            continue;
+        };
+
+        match property.name.as_str() {
+            "BEGIN" if property.value.eq_ignore_ascii_case(name) && current.is_none() => {
+                current = Some(vec![]);
+            }
+            "BEGIN" if current.is_some() => depth += 1,
+            "END" if depth > 0 => depth -= 1,
+            "END" if property.value.eq_ignore_ascii_case(name) => {
+                if let Some(component) = current.take() {
+                    components.push(component);
+                }
+            }
+            _ if depth == 0 => {
+                if let Some(component) = current.as_mut() {
+                    component.push(property);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    components
+}
+
+pub fn unescape_text(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') | Some('N') => output.push('\n'),
+            Some(other) => output.push(other),
+            None => output.push('\\'),
+        }
+    }
+
+    output
+}
+
+/// Parses DATE and DATE-TIME values. Floating and TZID qualified times are
+/// read as UTC.
+pub fn parse_datetime(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim_end_matches('Z');
+
+    if let Ok(datetime) = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S") {
+        return Some(datetime.and_utc());
+    }
+
+    NaiveDate::parse_from_str(value, "%Y%m%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|datetime| datetime.and_utc())
+}
+
+fn unfold_lines(input: &str) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+
+    for line in input.split('\n') {
+        let line = line.trim_end_matches('\r');
+
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(previous)) => previous.push_str(continuation),
+            _ if line.is_empty() => {}
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    lines
+}
+
+fn parse_property(line: &str) -> Option<Property> {
+    let (head, value) = line.split_once(':')?;
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_ascii_uppercase();
+
+    let params = parts
+        .filter_map(|param| param.split_once('='))
+        .map(|(key, value)| {
+            (
+                key.to_ascii_uppercase(),
+                value.trim_matches('"').to_string(),
+            )
+        })
+        .collect();
+
+    Some(Property {
+        name,
+        params,
+        value: value.to_string(),
+    })
+}
@@ -0,0 +1,189 @@
+use std::{
+    future::IntoFuture,
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{MatchedPath, State},
+    http::{header, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    routing, Router,
+};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
+    TextEncoder, TEXT_FORMAT,
+};
+
+/// Request and database metrics of one app, rendered in the Prometheus text
+/// format. Clones share the same registry.
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_request_duration: HistogramVec,
+    http_requests_in_flight: IntGaugeVec,
+    db_query_duration: HistogramVec,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new();
+
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests handled"),
+            &["method", "path", "status"],
+        )
+        .expect("valid http_requests_total metric");
+        let http_request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "Time taken to handle an HTTP request",
+            ),
+            &["method", "path"],
+        )
+        .expect("valid http_request_duration_seconds metric");
+        let http_requests_in_flight = IntGaugeVec::new(
+            Opts::new(
+                "http_requests_in_flight",
+                "HTTP requests currently being handled",
+            ),
+            &["method", "path"],
+        )
+        .expect("valid http_requests_in_flight metric");
+        let db_query_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "db_query_duration_seconds",
+                "Time taken by a database query",
+            )
+            .buckets(vec![
+                0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0,
+            ]),
+            &["repository", "operation"],
+        )
+        .expect("valid db_query_duration_seconds metric");
+
+        for collector in [
+            Box::new(http_requests.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(http_request_duration.clone()),
+            Box::new(http_requests_in_flight.clone()),
+            Box::new(db_query_duration.clone()),
+        ] {
+            registry
+                .register(collector)
+                .expect("metric registered once");
+        }
+
+        Self {
+            registry,
+            http_requests,
+            http_request_duration,
+            http_requests_in_flight,
+            db_query_duration,
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let mut buffer = vec![];
+
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("metrics encode as text");
+
+        String::from_utf8(buffer).expect("metrics text is UTF-8")
+    }
+
+    pub fn observe_query(&self, repository: &str, operation: &str, elapsed: Duration) {
+        self.db_query_duration
+            .with_label_values(&[repository, operation])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    pub async fn time_query<T>(
+        &self,
+        repository: &str,
+        operation: &str,
+        query: impl IntoFuture<Output = T>,
+    ) -> T {
+        let started = Instant::now();
+        let result = query.await;
+
+        self.observe_query(repository, operation, started.elapsed());
+
+        result
+    }
+}
+
+/// Middleware counting and timing requests by their matched route, such as
+/// `/v1/todos/:id`, so ids do not make a new series each. It only sees
+/// requests that matched a route, so it is added with `Router::route_layer`.
+pub async fn track_requests<B>(
+    State(metrics): State<Metrics>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let Some(path) = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_owned())
+    else {
+        return next.run(request).await;
+    };
+    let method = request.method().to_string();
+
+    let in_flight = InFlight::start(
+        metrics
+            .http_requests_in_flight
+            .with_label_values(&[&method, &path]),
+    );
+    let started = Instant::now();
+
+    let response = next.run(request).await;
+
+    drop(in_flight);
+    metrics
+        .http_request_duration
+        .with_label_values(&[&method, &path])
+        .observe(started.elapsed().as_secs_f64());
+    metrics
+        .http_requests
+        .with_label_values(&[&method, &path, response.status().as_str()])
+        .inc();
+
+    response
+}
+
+/// Counts a request in flight until dropped, which also covers a client
+/// hanging up before the response.
+struct InFlight(IntGauge);
+
+impl InFlight {
+    fn start(gauge: IntGauge) -> Self {
+        gauge.inc();
+
+        Self(gauge)
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
+}
+
+/// Serves `GET /metrics`, on the app itself or on a separate admin port.
+pub fn metrics_router(metrics: Metrics) -> Router {
+    Router::new()
+        .route("/metrics", routing::get(render_metrics))
+        .with_state(metrics)
+}
+
+async fn render_metrics(State(metrics): State<Metrics>) -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, TEXT_FORMAT)], metrics.render())
+}
@@ -1,15 +1,39 @@
 pub mod authorizer;
+pub mod circuit_breaker;
 pub mod clock;
+pub mod crypto;
+pub mod highlight;
+pub mod http;
+pub mod icalendar;
 pub mod id_generator;
+pub mod metrics;
+pub mod natural_date;
+pub mod network;
 pub mod parser;
+pub mod rate_limit;
+pub mod rate_limit_store;
+pub mod redaction;
+pub mod request_id;
+pub mod shutdown;
 pub mod telemetry;
 pub mod tracing;
 pub mod validator;
 
 pub use authorizer::*;
+pub use circuit_breaker::*;
 pub use clock::*;
+pub use crypto::*;
+pub use highlight::*;
+pub use http::*;
+pub use icalendar::*;
 pub use id_generator::*;
+pub use metrics::*;
+pub use natural_date::*;
+pub use network::*;
 pub use parser::*;
-pub use telemetry::*;
+pub use rate_limit::*;
+pub use rate_limit_store::*;
+pub use redaction::*;
+pub use request_id::*;
+pub use shutdown::*;
 pub use tracing::*;
-pub use validator::*;
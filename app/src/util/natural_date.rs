@@ -0,0 +1,468 @@
+use std::fmt;
+
+use chrono::{
+    DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc,
+    Weekday,
+};
+use chrono_tz::Tz;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NaturalDateError(pub String);
+
+impl fmt::Display for NaturalDateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+type DateResult<T> = Result<T, NaturalDateError>;
+
+fn error<T>(message: String) -> DateResult<T> {
+    Err(NaturalDateError(message))
+}
+
+/// Phrases without a time are due by the end of that day.
+fn end_of_day() -> NaiveTime {
+    NaiveTime::from_hms_opt(23, 59, 59).unwrap_or(NaiveTime::MIN)
+}
+
+/// Resolves an RFC 3339 timestamp or a phrase such as `tomorrow 5pm`,
+/// `next friday`, `in 3 days` or `2023-11-10 17:30` against `now`, reading
+/// wall clock times in `timezone`.
+///
+/// Anything that could mean two different moments is rejected rather than
+/// guessed: bare hours like `5`, `midnight`, a bare weekday that is today,
+/// a time of day that has already passed and local times repeated or skipped
+/// by a daylight saving change.
+pub fn parse_natural_date(
+    input: &str,
+    now: DateTime<Utc>,
+    timezone: Tz,
+) -> DateResult<DateTime<Utc>> {
+    let input = input.trim();
+
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(input) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+
+    let words: Vec<String> = input
+        .to_lowercase()
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|word| !word.is_empty())
+        .map(String::from)
+        .collect();
+
+    if words.is_empty() {
+        return error(String::from("must not be empty"));
+    }
+
+    let local_now = now.with_timezone(&timezone);
+    let today = local_now.date_naive();
+
+    let mut phrase = Phrase::default();
+    let mut i = 0;
+
+    while i < words.len() {
+        let word = words[i].as_str();
+        let next = words.get(i + 1).map(String::as_str);
+
+        match word {
+            "at" | "on" | "by" => {}
+            "today" => phrase.set_date(today, word)?,
+            "tonight" => {
+                phrase.set_date(today, word)?;
+                phrase.default_time = NaiveTime::from_hms_opt(20, 0, 0);
+            }
+            "tomorrow" => phrase.set_date(today + Duration::days(1), word)?,
+            "yesterday" => phrase.set_date(today - Duration::days(1), word)?,
+            "noon" | "midday" => phrase.set_time(NaiveTime::from_hms_opt(12, 0, 0), word)?,
+            "midnight" => {
+                return error(String::from(
+                    "`midnight` is ambiguous, use 00:00 or 23:59 instead",
+                ))
+            }
+            "next" | "this" => {
+                let Some(target) = next else {
+                    return error(format!("`{}` must be followed by a weekday or week", word));
+                };
+
+                let date = if target == "week" {
+                    match word {
+                        "next" => start_of_week(today) + Duration::weeks(1),
+                        _ => return error(String::from("`this week` is not a date, name a day")),
+                    }
+                } else if let Some(weekday) = parse_weekday(target) {
+                    let days_ahead = days_until(today, weekday);
+
+                    match (word, days_ahead) {
+                        ("next", 0) => today + Duration::weeks(1),
+                        _ => today + Duration::days(days_ahead),
+                    }
+                } else {
+                    return error(format!(
+                        "`{} {}` is not understood, expected a weekday or week",
+                        word, target
+                    ));
+                };
+
+                phrase.set_date(date, &format!("{} {}", word, target))?;
+                i += 1;
+            }
+            "in" => {
+                let (Some(amount), Some(unit)) = (next, words.get(i + 2)) else {
+                    return error(String::from(
+                        "`in` must be followed by an amount and a unit, such as in 3 days",
+                    ));
+                };
+
+                let amount: i64 = match amount {
+                    "a" | "an" | "one" => 1,
+                    _ => match amount.parse() {
+                        Ok(amount) if (0..=9999).contains(&amount) => amount,
+                        _ => return error(format!("`{}` is not a number of {}", amount, unit)),
+                    },
+                };
+
+                phrase.set_offset(
+                    match unit.trim_end_matches('s') {
+                        "min" | "minute" => Duration::minutes(amount),
+                        "hour" | "hr" => Duration::hours(amount),
+                        "day" => Duration::days(amount),
+                        "week" => Duration::weeks(amount),
+                        _ => {
+                            return error(format!(
+                                "`{}` is not a unit, use minutes, hours, days or weeks",
+                                unit
+                            ))
+                        }
+                    },
+                    unit.starts_with("min") || unit.starts_with('h'),
+                )?;
+                i += 2;
+            }
+            _ => {
+                if let Some(weekday) = parse_weekday(word) {
+                    let days_ahead = days_until(today, weekday);
+
+                    if days_ahead == 0 {
+                        return error(format!(
+                            "`{}` could mean today or next week, say today or next {}",
+                            word, word
+                        ));
+                    }
+
+                    phrase.set_date(today + Duration::days(days_ahead), word)?;
+                } else if let Ok(date) = NaiveDate::parse_from_str(word, "%Y-%m-%d") {
+                    phrase.set_date(date, word)?;
+                } else if let Some((time, consumed)) = parse_time(word, next)? {
+                    phrase.set_time(Some(time), word)?;
+                    i += consumed;
+                } else {
+                    return error(format!("`{}` is not a date or time", word));
+                }
+            }
+        }
+
+        i += 1;
+    }
+
+    phrase.resolve(now, timezone)
+}
+
+#[derive(Default)]
+struct Phrase {
+    date: Option<NaiveDate>,
+    time: Option<NaiveTime>,
+    default_time: Option<NaiveTime>,
+    offset: Option<Duration>,
+    /// Offsets in minutes or hours point at an instant, not at a day.
+    exact_offset: bool,
+}
+
+impl Phrase {
+    fn set_date(&mut self, date: NaiveDate, word: &str) -> DateResult<()> {
+        if self.date.is_some() || self.offset.is_some() {
+            return error(format!("`{}` names a second date", word));
+        }
+
+        self.date = Some(date);
+
+        Ok(())
+    }
+
+    fn set_time(&mut self, time: Option<NaiveTime>, word: &str) -> DateResult<()> {
+        if self.time.is_some() || self.exact_offset {
+            return error(format!("`{}` names a second time", word));
+        }
+
+        self.time = time;
+
+        Ok(())
+    }
+
+    fn set_offset(&mut self, offset: Duration, exact: bool) -> DateResult<()> {
+        if self.date.is_some() || self.offset.is_some() || (exact && self.time.is_some()) {
+            return error(String::from(
+                "`in ...` cannot be combined with another date",
+            ));
+        }
+
+        self.offset = Some(offset);
+        self.exact_offset = exact;
+
+        Ok(())
+    }
+
+    fn resolve(self, now: DateTime<Utc>, timezone: Tz) -> DateResult<DateTime<Utc>> {
+        let local_now = now.with_timezone(&timezone);
+
+        if let Some(offset) = self.offset {
+            if self.exact_offset {
+                return Ok(now + offset);
+            }
+
+            // Days and weeks keep the wall clock time across daylight saving changes.
+            let shifted = local_now.naive_local() + offset;
+            let time = self.time.unwrap_or(shifted.time());
+
+            return to_utc(shifted.date().and_time(time), timezone);
+        }
+
+        let date = match (self.date, self.time) {
+            (Some(date), _) => date,
+            (None, Some(time)) => {
+                let today = local_now.date_naive();
+
+                if today.and_time(time) < local_now.naive_local() {
+                    return error(format!(
+                        "{} has already passed today, say today or tomorrow",
+                        time.format("%H:%M")
+                    ));
+                }
+
+                today
+            }
+            (None, None) => return error(String::from("does not name a date or time")),
+        };
+
+        let time = self.time.or(self.default_time).unwrap_or_else(end_of_day);
+
+        to_utc(date.and_time(time), timezone)
+    }
+}
+
+fn to_utc(local: NaiveDateTime, timezone: Tz) -> DateResult<DateTime<Utc>> {
+    match timezone.from_local_datetime(&local) {
+        LocalResult::Single(moment) => Ok(moment.with_timezone(&Utc)),
+        LocalResult::Ambiguous(_, _) => error(format!(
+            "{} happens twice in {} because of a daylight saving change, send an RFC 3339 timestamp instead",
+            local.format("%Y-%m-%d %H:%M"),
+            timezone
+        )),
+        LocalResult::None => error(format!(
+            "{} does not exist in {} because of a daylight saving change",
+            local.format("%Y-%m-%d %H:%M"),
+            timezone
+        )),
+    }
+}
+
+/// First instant of `day` in `timezone`. Where a daylight saving change skips
+/// midnight the day starts at the first local time that exists.
+pub fn start_of_local_day(day: NaiveDate, timezone: Tz) -> DateTime<Utc> {
+    let midnight = day.and_time(NaiveTime::MIN);
+
+    (0..=2)
+        .find_map(|hours| {
+            timezone
+                .from_local_datetime(&(midnight + Duration::hours(hours)))
+                .earliest()
+        })
+        .map(|start| start.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&midnight))
+}
+
+/// The end of the local day `instant` falls on in `timezone`, which is when
+/// an all-day todo on that day is due.
+pub fn end_of_local_day(instant: DateTime<Utc>, timezone: Tz) -> DateTime<Utc> {
+    let day = instant.with_timezone(&timezone).date_naive();
+
+    timezone
+        .from_local_datetime(&day.and_time(end_of_day()))
+        .latest()
+        .map(|end| end.with_timezone(&Utc))
+        .unwrap_or(instant)
+}
+
+/// Moves `instant` to the same wall clock time in another time zone, so that
+/// 9am in `from` becomes 9am in `to`.
+pub fn keep_wall_clock(instant: DateTime<Utc>, from: Tz, to: Tz) -> DateResult<DateTime<Utc>> {
+    to_utc(instant.with_timezone(&from).naive_local(), to)
+}
+
+/// Reads `5pm`, `5:30pm`, `5 pm` or `17:30`, returning how many extra words
+/// were consumed. Bare hours such as `5` are refused as they could be either
+/// half of the day.
+fn parse_time(word: &str, next: Option<&str>) -> DateResult<Option<(NaiveTime, usize)>> {
+    let (clock, meridiem, consumed) = if let Some(clock) = word.strip_suffix("am") {
+        (clock, Some(false), 0)
+    } else if let Some(clock) = word.strip_suffix("pm") {
+        (clock, Some(true), 0)
+    } else {
+        match next {
+            Some("am") => (word, Some(false), 1),
+            Some("pm") => (word, Some(true), 1),
+            _ => (word, None, 0),
+        }
+    };
+
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) => (hour, minute),
+        None => (clock, "00"),
+    };
+
+    if hour.is_empty()
+        || hour.len() > 2
+        || minute.len() != 2
+        || !hour
+            .chars()
+            .chain(minute.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return Ok(None);
+    }
+
+    let (Ok(hour), Ok(minute)) = (hour.parse::<u32>(), minute.parse::<u32>()) else {
+        return Ok(None);
+    };
+
+    let hour = match meridiem {
+        Some(is_pm) if (1..=12).contains(&hour) => hour % 12 + if is_pm { 12 } else { 0 },
+        Some(_) => return error(format!("`{}` is not a valid 12-hour time", word)),
+        None if !clock.contains(':') => {
+            return error(format!(
+                "`{}` is ambiguous, add am or pm or use a 24-hour time such as 17:00",
+                word
+            ))
+        }
+        None => hour,
+    };
+
+    match NaiveTime::from_hms_opt(hour, minute, 0) {
+        Some(time) => Ok(Some((time, consumed))),
+        None => error(format!("`{}` is not a valid time", word)),
+    }
+}
+
+fn parse_weekday(word: &str) -> Option<Weekday> {
+    match word {
+        "monday" | "mon" => Some(Weekday::Mon),
+        "tuesday" | "tue" | "tues" => Some(Weekday::Tue),
+        "wednesday" | "wed" => Some(Weekday::Wed),
+        "thursday" | "thu" | "thurs" => Some(Weekday::Thu),
+        "friday" | "fri" => Some(Weekday::Fri),
+        "saturday" | "sat" => Some(Weekday::Sat),
+        "sunday" | "sun" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// Days from `today` until the next `weekday`, 0 when today is that weekday.
+fn days_until(today: NaiveDate, weekday: Weekday) -> i64 {
+    let today = today.weekday().num_days_from_monday() as i64;
+    let target = weekday.num_days_from_monday() as i64;
+
+    (target - today).rem_euclid(7)
+}
+
+fn start_of_week(day: NaiveDate) -> NaiveDate {
+    day - Duration::days(day.weekday().num_days_from_monday() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono_tz::Europe::Berlin;
+
+    use super::*;
+
+    /// Friday 2023-03-24 10:00 in Berlin, two days before clocks go forward.
+    fn now() -> DateTime<Utc> {
+        utc("2023-03-24T09:00:00Z")
+    }
+
+    fn utc(timestamp: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(timestamp)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn parse(input: &str) -> DateResult<DateTime<Utc>> {
+        parse_natural_date(input, now(), Berlin)
+    }
+
+    fn error_of(input: &str) -> String {
+        parse(input).unwrap_err().0
+    }
+
+    #[test]
+    fn passes_timestamps_through() {
+        assert_eq!(
+            parse("2023-04-01T08:00:00+02:00").unwrap(),
+            utc("2023-04-01T06:00:00Z")
+        );
+    }
+
+    #[test]
+    fn resolves_relative_phrases() {
+        assert_eq!(parse("tomorrow 5pm").unwrap(), utc("2023-03-25T16:00:00Z"));
+        assert_eq!(parse("tonight").unwrap(), utc("2023-03-24T19:00:00Z"));
+        assert_eq!(parse("today").unwrap(), utc("2023-03-24T22:59:59Z"));
+        assert_eq!(parse("in 2 hours").unwrap(), utc("2023-03-24T11:00:00Z"));
+        assert_eq!(parse("in an hour").unwrap(), utc("2023-03-24T10:00:00Z"));
+    }
+
+    #[test]
+    fn keeps_the_wall_clock_over_days_across_a_daylight_saving_change() {
+        assert_eq!(parse("in 3 days").unwrap(), utc("2023-03-27T08:00:00Z"));
+        assert_eq!(parse("in 1 week").unwrap(), utc("2023-03-31T08:00:00Z"));
+    }
+
+    #[test]
+    fn resolves_weekdays() {
+        assert_eq!(parse("sunday").unwrap(), utc("2023-03-26T21:59:59Z"));
+        assert_eq!(parse("mon 9am").unwrap(), utc("2023-03-27T07:00:00Z"));
+        assert_eq!(parse("next friday").unwrap(), utc("2023-03-31T21:59:59Z"));
+        assert_eq!(parse("this saturday").unwrap(), utc("2023-03-25T22:59:59Z"));
+        assert_eq!(parse("next week").unwrap(), utc("2023-03-27T21:59:59Z"));
+    }
+
+    #[test]
+    fn reads_times_of_day() {
+        assert_eq!(parse("17:30").unwrap(), utc("2023-03-24T16:30:00Z"));
+        assert_eq!(parse("5:30 pm").unwrap(), utc("2023-03-24T16:30:00Z"));
+        assert_eq!(parse("at noon").unwrap(), utc("2023-03-24T11:00:00Z"));
+        assert_eq!(
+            parse("2023-04-10 12am").unwrap(),
+            utc("2023-04-09T22:00:00Z")
+        );
+    }
+
+    #[test]
+    fn refuses_local_times_skipped_or_repeated_by_daylight_saving() {
+        assert!(error_of("sunday 2:30am").contains("does not exist in Europe/Berlin"));
+        assert!(error_of("2023-10-29 2:30am").contains("happens twice in Europe/Berlin"));
+    }
+
+    #[test]
+    fn refuses_ambiguous_phrases() {
+        assert!(error_of("friday").contains("could mean today or next week"));
+        assert!(error_of("tomorrow 5").contains("is ambiguous"));
+        assert!(error_of("midnight").contains("is ambiguous"));
+        assert!(error_of("9am").contains("has already passed today"));
+        assert!(error_of("tomorrow monday").contains("names a second date"));
+        assert!(error_of("in 3 days tomorrow").contains("names a second date"));
+        assert!(error_of("13pm").contains("not a valid 12-hour time"));
+        assert!(error_of("soonish").contains("is not a date or time"));
+        assert_eq!(error_of("  "), "must not be empty");
+    }
+}
@@ -0,0 +1,167 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use url::{Host, Url};
+
+/// Whether `ip` can be reached over the internet. Loopback, private, link
+/// local (cloud metadata endpoints among them), shared, documentation and
+/// reserved ranges are not.
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => is_public_ipv6(ip),
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || a == 0
+        // Shared address space of carrier-grade NAT, 100.64.0.0/10
+        || (a == 100 && (64..128).contains(&b))
+        // Benchmarking, 198.18.0.0/15
+        || (a == 198 && (18..20).contains(&b))
+        // Reserved, 240.0.0.0/4
+        || a >= 240)
+}
+
+fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+    if let Some(mapped) = ip.to_ipv4_mapped() {
+        return is_public_ipv4(mapped);
+    }
+
+    let segments = ip.segments();
+
+    // NAT64 addresses, 64:ff9b::/96, reach the IPv4 address they end with.
+    if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+        let [.., a, b, c, d] = ip.octets();
+
+        return is_public_ipv4(Ipv4Addr::new(a, b, c, d));
+    }
+
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // Unique local, fc00::/7
+        || (segments[0] & 0xfe00) == 0xfc00
+        // Link local, fe80::/10
+        || (segments[0] & 0xffc0) == 0xfe80
+        // Documentation, 2001:db8::/32
+        || (segments[0] == 0x2001 && segments[1] == 0xdb8))
+}
+
+/// Checks that `url` is an http or https URL of a host on the internet, as
+/// far as can be told without resolving it. Names are checked again by
+/// [`PublicResolver`] whenever they are connected to.
+pub fn check_public_url(url: &str) -> Result<(), String> {
+    let url = Url::parse(url).map_err(|_| format!("`{}` is not a URL", url))?;
+
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("`{}` is not an http or https URL", url));
+    }
+
+    let public = match url.host() {
+        None => false,
+        Some(Host::Ipv4(ip)) => is_public_ipv4(ip),
+        Some(Host::Ipv6(ip)) => is_public_ipv6(ip),
+        Some(Host::Domain(domain)) => {
+            let domain = domain.trim_end_matches('.').to_lowercase();
+
+            domain != "localhost" && !domain.ends_with(".localhost")
+        }
+    };
+
+    match public {
+        true => Ok(()),
+        false => Err(format!("`{}` is not a public address", url)),
+    }
+}
+
+/// Resolves names like the system does and keeps only the public addresses,
+/// so a name cannot be pointed at the internal network once it was checked.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str().to_owned();
+            let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|address| is_public_ip(address.ip()))
+                .collect();
+
+            if addresses.is_empty() {
+                return Err(format!("{} has no public address", host).into());
+            }
+
+            let addresses: Addrs = Box::new(addresses.into_iter());
+
+            Ok(addresses)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn public(ip: &str) -> bool {
+        is_public_ip(ip.parse().unwrap())
+    }
+
+    #[test]
+    fn refuses_internal_addresses() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.100.100.200",
+            "0.0.0.0",
+            "::1",
+            "::",
+            "fd00:ec2::254",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "64:ff9b::a9fe:a9fe",
+        ] {
+            assert!(!public(ip), "{} is internal", ip);
+        }
+    }
+
+    #[test]
+    fn accepts_internet_addresses() {
+        for ip in ["93.184.216.34", "1.1.1.1", "2606:4700:4700::1111"] {
+            assert!(public(ip), "{} is public", ip);
+        }
+    }
+
+    #[test]
+    fn checks_the_url_host() {
+        assert!(check_public_url("https://hooks.example.com/todos").is_ok());
+        assert!(check_public_url("https://93.184.216.34/hook").is_ok());
+        assert!(check_public_url("ftp://hooks.example.com").is_err());
+        assert!(check_public_url("http://localhost:8080/hook").is_err());
+        assert!(check_public_url("http://127.0.0.1:8080/hook").is_err());
+        assert!(check_public_url("http://[::1]/hook").is_err());
+        assert!(check_public_url("http://169.254.169.254/latest/meta-data").is_err());
+        assert!(check_public_url("http://2130706433/hook").is_err());
+    }
+
+    #[tokio::test]
+    async fn resolves_no_internal_address() {
+        let name = "localhost".parse().unwrap();
+
+        assert!(PublicResolver.resolve(name).await.is_err());
+    }
+}
@@ -1 +1,741 @@
+use std::fmt;
 
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
+
+use super::start_of_local_day;
+
+/// Filter parsed from the single search box syntax, for example
+/// `is:open due:<7d -subject:"quarterly report" (groceries OR shopping)`.
+///
+/// Terms next to each other must all match, `OR` combines alternatives and
+/// binds looser than the implicit `AND`, `-` or `NOT` negates the next term.
+///
+/// Todos have no tags, so `tag:` is refused rather than matching nothing.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryExpr {
+    And(Vec<QueryExpr>),
+    Or(Vec<QueryExpr>),
+    Not(Box<QueryExpr>),
+    IsDone(bool),
+    /// Due date within `[from, until)`, a missing bound is open.
+    Due {
+        from: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    },
+    /// Case-insensitive substring match, the value is already lowercase.
+    Text {
+        field: TextField,
+        value: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextField {
+    Any,
+    Subject,
+    Description,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryParseError {
+    pub message: String,
+    /// 1-based character position of the offending token
+    pub column: usize,
+    pub token: String,
+}
+
+impl QueryParseError {
+    fn new(message: &str, column: usize, token: &str) -> Self {
+        Self {
+            message: message.to_string(),
+            column,
+            token: token.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for QueryParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.token.is_empty() {
+            write!(f, "{} at column {}", self.message, self.column)
+        } else {
+            write!(
+                f,
+                "{} at column {} near `{}`",
+                self.message, self.column, self.token
+            )
+        }
+    }
+}
+
+type ParseResult<T> = Result<T, QueryParseError>;
+
+/// Parentheses and negations nested deeper than this are refused, so a
+/// crafted query cannot exhaust the stack.
+pub static MAX_QUERY_DEPTH: usize = 32;
+
+/// Parses the query, resolving relative dates such as `7d` or `today`
+/// against `now`. Days start at local midnight in `timezone`, so a day
+/// spanning a daylight saving change is 23 or 25 hours long.
+pub fn parse_query(input: &str, now: DateTime<Utc>, timezone: Tz) -> ParseResult<QueryExpr> {
+    let tokens = tokenize(input)?;
+
+    if tokens.is_empty() {
+        return Ok(QueryExpr::And(vec![]));
+    }
+
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+        depth: 0,
+        end_column: input.chars().count() + 1,
+        now,
+        timezone,
+    };
+
+    let expr = parser.parse_or()?;
+
+    match parser.peek() {
+        None => Ok(expr),
+        Some(token) => Err(QueryParseError::new(
+            "unmatched `)`",
+            token.column,
+            &token.text,
+        )),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    OpenParen,
+    CloseParen,
+    Not,
+    Or,
+    Term {
+        qualifier: Option<String>,
+        value: String,
+        value_column: usize,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    column: usize,
+    text: String,
+}
+
+fn tokenize(input: &str) -> ParseResult<Vec<Token>> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i + 1;
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let single = |kind| Token {
+            kind,
+            column,
+            text: c.to_string(),
+        };
+
+        match c {
+            '(' => {
+                tokens.push(single(TokenKind::OpenParen));
+                i += 1;
+            }
+            ')' => {
+                tokens.push(single(TokenKind::CloseParen));
+                i += 1;
+            }
+            '-' if chars
+                .get(i + 1)
+                .is_some_and(|next| !next.is_whitespace() && *next != ')') =>
+            {
+                tokens.push(single(TokenKind::Not));
+                i += 1;
+            }
+            '"' => {
+                let (value, end) = read_phrase(&chars, i)?;
+
+                tokens.push(Token {
+                    kind: TokenKind::Term {
+                        qualifier: None,
+                        value,
+                        value_column: column + 1,
+                    },
+                    column,
+                    text: chars[i..end].iter().collect(),
+                });
+                i = end;
+            }
+            _ => {
+                let mut end = i;
+                while end < chars.len() && !is_word_boundary(chars[end]) {
+                    end += 1;
+                }
+
+                let word: String = chars[i..end].iter().collect();
+
+                let kind = match word.as_str() {
+                    "OR" => TokenKind::Or,
+                    "NOT" => TokenKind::Not,
+                    "AND" => {
+                        i = end;
+                        continue;
+                    }
+                    _ => match word.split_once(':') {
+                        None => TokenKind::Term {
+                            qualifier: None,
+                            value: word.clone(),
+                            value_column: column,
+                        },
+                        Some((qualifier, value)) => {
+                            let value_column = column + qualifier.chars().count() + 1;
+                            let mut value = value.to_string();
+
+                            if value.is_empty() && chars.get(end) == Some(&'"') {
+                                let (phrase, phrase_end) = read_phrase(&chars, end)?;
+                                value = phrase;
+                                end = phrase_end;
+                            }
+
+                            if qualifier.is_empty() {
+                                return Err(QueryParseError::new(
+                                    "missing qualifier before `:`",
+                                    column,
+                                    &word,
+                                ));
+                            }
+
+                            if value.is_empty() {
+                                return Err(QueryParseError::new(
+                                    "missing value after `:`",
+                                    value_column,
+                                    &word,
+                                ));
+                            }
+
+                            TokenKind::Term {
+                                qualifier: Some(qualifier.to_lowercase()),
+                                value,
+                                value_column,
+                            }
+                        }
+                    },
+                };
+
+                tokens.push(Token {
+                    kind,
+                    column,
+                    text: chars[i..end].iter().collect(),
+                });
+                i = end;
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn is_word_boundary(c: char) -> bool {
+    c.is_whitespace() || c == '(' || c == ')' || c == '"'
+}
+
+/// Reads a double quoted phrase starting at `start`, returning its content and
+/// the index right after the closing quote.
+fn read_phrase(chars: &[char], start: usize) -> ParseResult<(String, usize)> {
+    let Some(length) = chars[start + 1..].iter().position(|c| *c == '"') else {
+        let rest: String = chars[start..].iter().collect();
+
+        return Err(QueryParseError::new("unterminated quote", start + 1, &rest));
+    };
+
+    let end = start + 1 + length;
+    let phrase: String = chars[start + 1..end].iter().collect();
+
+    if phrase.trim().is_empty() {
+        return Err(QueryParseError::new("empty phrase", start + 1, "\"\""));
+    }
+
+    Ok((phrase, end + 1))
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    depth: usize,
+    end_column: usize,
+    now: DateTime<Utc>,
+    timezone: Tz,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+
+        token
+    }
+
+    fn expected_filter(&self, after: &Token) -> QueryParseError {
+        match self.peek() {
+            Some(token) => QueryParseError::new(
+                &format!("expected a filter after `{}`", after.text),
+                token.column,
+                &token.text,
+            ),
+            None => QueryParseError::new(
+                &format!("expected a filter after `{}`", after.text),
+                self.end_column,
+                "",
+            ),
+        }
+    }
+
+    /// Runs `parse` one level deeper than the current one.
+    fn nested<T>(
+        &mut self,
+        token: &Token,
+        parse: impl FnOnce(&mut Self) -> ParseResult<T>,
+    ) -> ParseResult<T> {
+        if self.depth >= MAX_QUERY_DEPTH {
+            return Err(QueryParseError::new(
+                &format!("nested deeper than {} levels", MAX_QUERY_DEPTH),
+                token.column,
+                &token.text,
+            ));
+        }
+
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+
+        result
+    }
+
+    fn parse_or(&mut self) -> ParseResult<QueryExpr> {
+        let mut alternatives = vec![self.parse_and()?];
+
+        while let Some(token) = self.peek().cloned() {
+            if token.kind != TokenKind::Or {
+                break;
+            }
+
+            self.next();
+
+            if !self.starts_term() {
+                return Err(self.expected_filter(&token));
+            }
+
+            alternatives.push(self.parse_and()?);
+        }
+
+        Ok(single_or(alternatives, QueryExpr::Or))
+    }
+
+    fn parse_and(&mut self) -> ParseResult<QueryExpr> {
+        let mut terms = vec![];
+
+        while self.starts_term() {
+            terms.push(self.parse_unary()?);
+        }
+
+        if terms.is_empty() {
+            return Err(match self.peek() {
+                Some(token) => QueryParseError::new("expected a filter", token.column, &token.text),
+                None => QueryParseError::new("expected a filter", self.end_column, ""),
+            });
+        }
+
+        Ok(single_or(terms, QueryExpr::And))
+    }
+
+    fn starts_term(&self) -> bool {
+        self.peek()
+            .is_some_and(|token| !matches!(token.kind, TokenKind::Or | TokenKind::CloseParen))
+    }
+
+    fn parse_unary(&mut self) -> ParseResult<QueryExpr> {
+        let Some(token) = self.next() else {
+            return Err(QueryParseError::new(
+                "expected a filter",
+                self.end_column,
+                "",
+            ));
+        };
+
+        match token.kind {
+            TokenKind::Not => {
+                if !self.starts_term() {
+                    return Err(self.expected_filter(&token));
+                }
+
+                let expr = self.nested(&token, Self::parse_unary)?;
+
+                Ok(QueryExpr::Not(Box::new(expr)))
+            }
+            TokenKind::OpenParen => {
+                let expr = self.nested(&token, Self::parse_or)?;
+
+                match self.next() {
+                    Some(Token {
+                        kind: TokenKind::CloseParen,
+                        ..
+                    }) => Ok(expr),
+                    _ => Err(QueryParseError::new("missing `)`", token.column, "(")),
+                }
+            }
+            TokenKind::Term {
+                qualifier,
+                value,
+                value_column,
+            } => self.parse_term(&token.text, token.column, qualifier, value, value_column),
+            TokenKind::Or | TokenKind::CloseParen => Err(QueryParseError::new(
+                "expected a filter",
+                token.column,
+                &token.text,
+            )),
+        }
+    }
+
+    fn parse_term(
+        &self,
+        text: &str,
+        column: usize,
+        qualifier: Option<String>,
+        value: String,
+        value_column: usize,
+    ) -> ParseResult<QueryExpr> {
+        let Some(qualifier) = qualifier else {
+            return Ok(QueryExpr::Text {
+                field: TextField::Any,
+                value: value.to_lowercase(),
+            });
+        };
+
+        match qualifier.as_str() {
+            "is" => match value.to_lowercase().as_str() {
+                "open" => Ok(QueryExpr::IsDone(false)),
+                "done" | "completed" => Ok(QueryExpr::IsDone(true)),
+                "overdue" => Ok(QueryExpr::And(vec![
+                    QueryExpr::IsDone(false),
+                    QueryExpr::Due {
+                        from: None,
+                        until: Some(self.now),
+                    },
+                ])),
+                _ => Err(QueryParseError::new(
+                    "expected open, done or overdue",
+                    value_column,
+                    &value,
+                )),
+            },
+            "due" => self.parse_due(&value, value_column),
+            "subject" => Ok(QueryExpr::Text {
+                field: TextField::Subject,
+                value: value.to_lowercase(),
+            }),
+            "description" => Ok(QueryExpr::Text {
+                field: TextField::Description,
+                value: value.to_lowercase(),
+            }),
+            "tag" => Err(QueryParseError::new(
+                "todos have no tags, `tag:` is not supported",
+                column,
+                text,
+            )),
+            _ => Err(QueryParseError::new(
+                "unknown qualifier, expected is, due, subject or description",
+                column,
+                &qualifier,
+            )),
+        }
+    }
+
+    fn parse_due(&self, value: &str, column: usize) -> ParseResult<QueryExpr> {
+        let (comparison, date) = [
+            ("<=", Comparison::AtMost),
+            (">=", Comparison::AtLeast),
+            ("<", Comparison::Before),
+            (">", Comparison::After),
+            ("=", Comparison::On),
+        ]
+        .into_iter()
+        .find_map(|(operator, comparison)| {
+            value.strip_prefix(operator).map(|date| (comparison, date))
+        })
+        .unwrap_or((Comparison::On, value));
+
+        let date_column = column + value.chars().count() - date.chars().count();
+
+        if date.is_empty() {
+            return Err(QueryParseError::new("missing date", date_column, value));
+        }
+
+        let Some(moment) = self.resolve_date(date) else {
+            return Err(QueryParseError::new(
+                "expected a date such as 7d, -2w, today, 2023-11-04 or an RFC 3339 timestamp",
+                date_column,
+                date,
+            ));
+        };
+
+        let start_of = |day: NaiveDate| start_of_local_day(day, self.timezone);
+        let end_of = |day: NaiveDate| start_of_local_day(day + Duration::days(1), self.timezone);
+
+        let (from, until) = match (comparison, moment) {
+            (Comparison::Before, Moment::Day(day)) => (None, Some(start_of(day))),
+            (Comparison::AtMost, Moment::Day(day)) => (None, Some(end_of(day))),
+            (Comparison::After, Moment::Day(day)) => (Some(end_of(day)), None),
+            (Comparison::AtLeast, Moment::Day(day)) => (Some(start_of(day)), None),
+            (Comparison::Before | Comparison::AtMost, Moment::Instant(instant)) => {
+                (None, Some(instant))
+            }
+            (Comparison::After | Comparison::AtLeast, Moment::Instant(instant)) => {
+                (Some(instant), None)
+            }
+            (Comparison::On, Moment::Day(day)) => (Some(start_of(day)), Some(end_of(day))),
+            (Comparison::On, Moment::Instant(instant)) => {
+                let day = instant.with_timezone(&self.timezone).date_naive();
+
+                (Some(start_of(day)), Some(end_of(day)))
+            }
+        };
+
+        Ok(QueryExpr::Due { from, until })
+    }
+
+    fn resolve_date(&self, date: &str) -> Option<Moment> {
+        let today = self.now.with_timezone(&self.timezone).date_naive();
+
+        match date.to_lowercase().as_str() {
+            "today" => return Some(Moment::Day(today)),
+            "tomorrow" => return Some(Moment::Day(today + Duration::days(1))),
+            "yesterday" => return Some(Moment::Day(today - Duration::days(1))),
+            _ => {}
+        }
+
+        if let Some(offset) = relative_offset(date) {
+            return self.now.checked_add_signed(offset).map(Moment::Instant);
+        }
+
+        if let Ok(day) = NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+            return Some(Moment::Day(day));
+        }
+
+        DateTime::parse_from_rfc3339(date)
+            .ok()
+            .map(|instant| Moment::Instant(instant.with_timezone(&Utc)))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Comparison {
+    Before,
+    AtMost,
+    After,
+    AtLeast,
+    On,
+}
+
+/// A whole local day is compared by its bounds, an instant as is.
+#[derive(Debug, Clone, Copy)]
+enum Moment {
+    Day(NaiveDate),
+    Instant(DateTime<Utc>),
+}
+
+/// Offsets like `7d`, `+12h` or `-2w`.
+fn relative_offset(value: &str) -> Option<Duration> {
+    let (sign, rest) = match value.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, value.strip_prefix('+').unwrap_or(value)),
+    };
+
+    let unit = rest.chars().last()?;
+    let amount = &rest[..rest.len() - unit.len_utf8()];
+
+    // Bounded so that the durations below cannot overflow.
+    if amount.is_empty() || amount.len() > 5 || !amount.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let amount: i64 = sign * amount.parse::<i64>().ok()?;
+
+    match unit {
+        'h' => Some(Duration::hours(amount)),
+        'd' => Some(Duration::days(amount)),
+        'w' => Some(Duration::weeks(amount)),
+        _ => None,
+    }
+}
+
+fn single_or(mut items: Vec<QueryExpr>, combine: fn(Vec<QueryExpr>) -> QueryExpr) -> QueryExpr {
+    if items.len() == 1 {
+        return items.remove(0);
+    }
+
+    combine(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2023-11-04T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn parse(input: &str) -> ParseResult<QueryExpr> {
+        parse_query(input, now(), chrono_tz::UTC)
+    }
+
+    fn text(value: &str) -> QueryExpr {
+        QueryExpr::Text {
+            field: TextField::Any,
+            value: value.to_string(),
+        }
+    }
+
+    fn at(timestamp: &str) -> Option<DateTime<Utc>> {
+        Some(
+            DateTime::parse_from_rfc3339(timestamp)
+                .unwrap()
+                .with_timezone(&Utc),
+        )
+    }
+
+    #[test]
+    fn or_binds_looser_than_and() {
+        assert_eq!(
+            parse("milk eggs OR bread").unwrap(),
+            QueryExpr::Or(vec![
+                QueryExpr::And(vec![text("milk"), text("eggs")]),
+                text("bread"),
+            ])
+        );
+        assert_eq!(
+            parse("milk AND (eggs OR bread)").unwrap(),
+            QueryExpr::And(vec![
+                text("milk"),
+                QueryExpr::Or(vec![text("eggs"), text("bread")]),
+            ])
+        );
+    }
+
+    #[test]
+    fn reads_quoted_phrases() {
+        assert_eq!(
+            parse(r#""Quarterly Report" subject:"Team sync""#).unwrap(),
+            QueryExpr::And(vec![
+                text("quarterly report"),
+                QueryExpr::Text {
+                    field: TextField::Subject,
+                    value: String::from("team sync"),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn negates_the_next_term() {
+        assert_eq!(
+            parse("-is:done NOT (milk OR eggs)").unwrap(),
+            QueryExpr::And(vec![
+                QueryExpr::Not(Box::new(QueryExpr::IsDone(true))),
+                QueryExpr::Not(Box::new(QueryExpr::Or(vec![text("milk"), text("eggs")]))),
+            ])
+        );
+        assert_eq!(parse("pre-order").unwrap(), text("pre-order"));
+    }
+
+    #[test]
+    fn resolves_due_dates_in_the_time_zone() {
+        assert_eq!(
+            parse("due:<=today").unwrap(),
+            QueryExpr::Due {
+                from: None,
+                until: at("2023-11-05T00:00:00Z"),
+            }
+        );
+        assert_eq!(
+            parse_query("due:2023-10-29", now(), chrono_tz::Europe::Berlin).unwrap(),
+            QueryExpr::Due {
+                from: at("2023-10-28T22:00:00Z"),
+                until: at("2023-10-29T23:00:00Z"),
+            }
+        );
+        assert_eq!(
+            parse("due:>-2w").unwrap(),
+            QueryExpr::Due {
+                from: at("2023-10-21T12:00:00Z"),
+                until: None,
+            }
+        );
+    }
+
+    #[test]
+    fn refuses_unbalanced_parentheses() {
+        let err = parse("(milk OR eggs").unwrap_err();
+        assert_eq!(err.message, "missing `)`");
+        assert_eq!(err.column, 1);
+
+        let err = parse("milk) eggs").unwrap_err();
+        assert_eq!(err.message, "unmatched `)`");
+        assert_eq!(err.column, 5);
+    }
+
+    #[test]
+    fn points_at_the_offending_column() {
+        let err = parse("milk due:soon").unwrap_err();
+        assert_eq!(err.column, 10);
+        assert_eq!(err.token, "soon");
+
+        let err = parse(r#"milk "eggs"#).unwrap_err();
+        assert_eq!(err.message, "unterminated quote");
+        assert_eq!(err.column, 6);
+
+        let err = parse("milk OR").unwrap_err();
+        assert_eq!(err.to_string(), "expected a filter after `OR` at column 8");
+
+        let err = parse("is:pending").unwrap_err();
+        assert_eq!(err.column, 4);
+        assert_eq!(err.token, "pending");
+    }
+
+    #[test]
+    fn refuses_tags() {
+        let err = parse(r#"is:open tag:work "quarterly report""#).unwrap_err();
+        assert_eq!(err.message, "todos have no tags, `tag:` is not supported");
+        assert_eq!(err.column, 9);
+        assert_eq!(err.token, "tag:work");
+    }
+
+    #[test]
+    fn refuses_queries_nested_too_deeply() {
+        let nested = |depth: usize| format!("{}milk{}", "(".repeat(depth), ")".repeat(depth));
+
+        assert!(parse(&nested(MAX_QUERY_DEPTH)).is_ok());
+
+        let err = parse(&nested(MAX_QUERY_DEPTH + 1)).unwrap_err();
+        assert_eq!(err.column, MAX_QUERY_DEPTH + 1);
+        assert_eq!(err.token, "(");
+
+        let err = parse(&format!("{}milk", "NOT ".repeat(100_000))).unwrap_err();
+        assert_eq!(err.column, MAX_QUERY_DEPTH * 4 + 1);
+    }
+}
@@ -0,0 +1,207 @@
+use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{HeaderName, HeaderValue, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tracing::warn;
+
+use crate::common::{ApplicationError, RateLimitConfig, RateLimitRule, RateLimitScope};
+
+use super::{api_key, sha256_hex, Clock, Decision, Quota, RateLimitStore};
+
+/// Group whose rules apply to the groups without a rule of their own.
+pub static DEFAULT_RATE_LIMIT_GROUP: &str = "default";
+
+pub static RATE_LIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+pub static RATE_LIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+pub static RATE_LIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+pub static RATE_LIMIT_POLICY: HeaderName = HeaderName::from_static("ratelimit-policy");
+
+/// Token bucket quotas per client IP and API key, each route group with its
+/// own. Clones share the same store.
+#[derive(Clone)]
+pub struct RateLimiter {
+    store: Arc<dyn RateLimitStore>,
+    clock: Arc<dyn Clock>,
+    rules: Arc<Vec<RateLimitRule>>,
+    client_ip_header: Option<HeaderName>,
+    trusted_hops: usize,
+}
+
+impl RateLimiter {
+    pub fn new<S: RateLimitStore, C: Clock>(store: S, clock: C, config: &RateLimitConfig) -> Self {
+        let client_ip_header = config.client_ip_header.as_deref().and_then(|name| {
+            HeaderName::try_from(name)
+                .map_err(|_| warn!("{} is not a header name, using the peer address", name))
+                .ok()
+        });
+
+        Self {
+            store: Arc::new(store),
+            clock: Arc::new(clock),
+            rules: Arc::new(config.rules.clone()),
+            client_ip_header,
+            trusted_hops: config.trusted_hops,
+        }
+    }
+
+    /// The quotas of `group`, to limit its routes with [`enforce_rate_limit`].
+    pub fn group(&self, group: &str) -> RateLimitGroup {
+        let mut quotas = HashMap::new();
+
+        for name in [DEFAULT_RATE_LIMIT_GROUP, group] {
+            for rule in self.rules.iter().filter(|rule| rule.group == name) {
+                quotas.insert(rule.scope, Quota::new(rule.requests, rule.period_seconds));
+            }
+        }
+
+        RateLimitGroup {
+            limiter: self.clone(),
+            name: Arc::from(group),
+            quotas: Arc::new(quotas),
+        }
+    }
+
+    fn client_ip<B>(&self, request: &Request<B>) -> Option<String> {
+        // Every proxy appends the address it was reached from, so the entries
+        // on the left are whatever the client sent. The client is the one the
+        // outermost trusted proxy saw, `trusted_hops` in from the right.
+        let forwarded = self.client_ip_header.as_ref().and_then(|name| {
+            request
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.split(',').nth_back(self.trusted_hops))
+                .map(|ip| ip.trim().to_owned())
+                .filter(|ip| !ip.is_empty())
+        });
+
+        forwarded.or_else(|| {
+            request
+                .extensions()
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(address)| address.ip().to_string())
+        })
+    }
+}
+
+/// The quotas of one route group, by scope.
+#[derive(Clone)]
+pub struct RateLimitGroup {
+    limiter: RateLimiter,
+    name: Arc<str>,
+    quotas: Arc<HashMap<RateLimitScope, Quota>>,
+}
+
+impl RateLimitGroup {
+    /// Takes a token from every bucket the request falls in and returns the
+    /// decision with the least left. Once a bucket refuses, the tokens taken
+    /// from the others are put back and its decision is returned, so a
+    /// refused request does not count against any quota.
+    pub async fn check<B>(&self, request: &Request<B>) -> Option<Decision> {
+        let now = self.limiter.clock.now();
+        let mut tightest: Option<Decision> = None;
+        let mut taken = Vec::new();
+
+        for (scope, quota) in self.quotas.iter() {
+            let Some(client) = self.client(*scope, request) else {
+                continue;
+            };
+            let key = format!("{}:{}:{}", self.name, scope_name(*scope), client);
+
+            // A store that is down lets requests through rather than fail them.
+            let decision = match self.limiter.store.take(&key, quota, now).await {
+                Ok(decision) => decision,
+                Err(err) => {
+                    warn!("Rate limit of {} not checked: {}", key, err);
+                    continue;
+                }
+            };
+
+            if !decision.allowed {
+                self.give_back(taken).await;
+
+                return Some(decision);
+            }
+
+            if tightest.is_none_or(|current| decision.remaining < current.remaining) {
+                tightest = Some(decision);
+            }
+
+            taken.push((key, quota));
+        }
+
+        tightest
+    }
+
+    async fn give_back(&self, taken: Vec<(String, &Quota)>) {
+        for (key, quota) in taken {
+            if let Err(err) = self.limiter.store.give_back(&key, quota).await {
+                warn!("Rate limit of {} not given back: {}", key, err);
+            }
+        }
+    }
+
+    fn client<B>(&self, scope: RateLimitScope, request: &Request<B>) -> Option<String> {
+        match scope {
+            RateLimitScope::Ip => self.limiter.client_ip(request),
+            // Keys are hashed so they are not kept, nor logged, in the clear.
+            RateLimitScope::ApiKey => {
+                api_key(request.headers()).map(|key| sha256_hex(key.as_bytes()))
+            }
+        }
+    }
+}
+
+fn scope_name(scope: RateLimitScope) -> &'static str {
+    match scope {
+        RateLimitScope::Ip => "ip",
+        RateLimitScope::ApiKey => "key",
+    }
+}
+
+/// Middleware answering `429 Too Many Requests` once a client is out of
+/// tokens in any of the group's buckets. Every response of the group tells
+/// what is left of the tightest quota in the `RateLimit-*` headers.
+pub async fn enforce_rate_limit<B>(
+    State(group): State<RateLimitGroup>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let Some(decision) = group.check(&request).await else {
+        return next.run(request).await;
+    };
+
+    let mut response = if decision.allowed {
+        next.run(request).await
+    } else {
+        ApplicationError::TooManyRequests(decision.retry_after_seconds).into_response()
+    };
+
+    let headers = response.headers_mut();
+    headers.insert(
+        RATE_LIMIT_LIMIT.clone(),
+        HeaderValue::from(decision.quota.requests),
+    );
+    headers.insert(
+        RATE_LIMIT_REMAINING.clone(),
+        HeaderValue::from(decision.remaining),
+    );
+    headers.insert(
+        RATE_LIMIT_RESET.clone(),
+        HeaderValue::from(decision.reset_seconds),
+    );
+    headers.insert(
+        RATE_LIMIT_POLICY.clone(),
+        HeaderValue::from_str(&format!(
+            "{};w={}",
+            decision.quota.requests, decision.quota.period_seconds
+        ))
+        .expect("rate limit policy is a valid header value"),
+    );
+
+    response
+}
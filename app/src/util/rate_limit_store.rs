@@ -0,0 +1,345 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use axum::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use surrealdb::sql::Datetime;
+
+use crate::common::{DatabaseDriver, RepositoryError};
+
+type RepositoryResult<T> = Result<T, RepositoryError>;
+
+/// Buckets the in-memory store holds before dropping the full ones.
+static MAX_MEMORY_BUCKETS: usize = 10_000;
+
+/// Writes racing on the same bucket are retried this many times.
+static MAX_WRITE_ATTEMPTS: u32 = 3;
+
+/// How often the SurrealDB store deletes the buckets that are full again.
+static PRUNE_INTERVAL_SECONDS: i64 = 60;
+
+/// `requests` every `period_seconds`, refilled a token at a time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quota {
+    pub requests: u32,
+    pub period_seconds: u64,
+}
+
+impl Quota {
+    pub fn new(requests: u32, period_seconds: u64) -> Self {
+        Self {
+            requests: requests.max(1),
+            period_seconds: period_seconds.max(1),
+        }
+    }
+
+    fn capacity(&self) -> f64 {
+        f64::from(self.requests)
+    }
+
+    fn tokens_per_second(&self) -> f64 {
+        self.capacity() / self.period_seconds as f64
+    }
+
+    fn seconds_until(&self, tokens: f64, target: f64) -> u64 {
+        ((target - tokens).max(0.0) / self.tokens_per_second()).ceil() as u64
+    }
+}
+
+/// The tokens left in a bucket when it was last taken from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bucket {
+    pub tokens: f64,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Bucket {
+    fn full(quota: &Quota, now: DateTime<Utc>) -> Self {
+        Self {
+            tokens: quota.capacity(),
+            updated_at: now,
+        }
+    }
+
+    /// Refills the bucket up to `now` and takes a token from it if one is
+    /// left, returning the bucket to store and what was decided.
+    pub fn take(bucket: Option<Self>, quota: &Quota, now: DateTime<Utc>) -> (Self, Decision) {
+        let bucket = bucket.unwrap_or_else(|| Self::full(quota, now));
+        // Another instance's clock can be ahead, time never runs backwards.
+        let now = now.max(bucket.updated_at);
+        let elapsed = (now - bucket.updated_at).num_milliseconds() as f64 / 1000.0;
+        let tokens = (bucket.tokens + elapsed * quota.tokens_per_second()).min(quota.capacity());
+
+        let allowed = tokens >= 1.0;
+        let tokens = if allowed { tokens - 1.0 } else { tokens };
+
+        let decision = Decision {
+            allowed,
+            quota: *quota,
+            remaining: tokens.floor() as u32,
+            reset_seconds: quota.seconds_until(tokens, quota.capacity()),
+            retry_after_seconds: if allowed {
+                0
+            } else {
+                quota.seconds_until(tokens, 1.0).max(1)
+            },
+        };
+
+        (
+            Self {
+                tokens,
+                updated_at: now,
+            },
+            decision,
+        )
+    }
+
+    /// Puts back a token taken for a request that was refused after all.
+    pub fn give_back(self, quota: &Quota) -> Self {
+        Self {
+            tokens: (self.tokens + 1.0).min(quota.capacity()),
+            updated_at: self.updated_at,
+        }
+    }
+
+    /// When the bucket is full again, and as good as never taken from.
+    fn expires_at(&self, quota: &Quota) -> DateTime<Utc> {
+        self.updated_at
+            + Duration::seconds(quota.seconds_until(self.tokens, quota.capacity()) as i64)
+    }
+}
+
+/// Whether a request fits in its quota, and what is left of the quota.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Decision {
+    pub allowed: bool,
+    pub quota: Quota,
+    pub remaining: u32,
+    /// Seconds until the whole quota is available again
+    pub reset_seconds: u64,
+    /// Seconds until the next request is allowed, 0 when this one was
+    pub retry_after_seconds: u64,
+}
+
+/// Keeps one token bucket per key. Taking a token has to be atomic for the
+/// instances sharing the store.
+#[async_trait]
+pub trait RateLimitStore: Send + Sync + 'static {
+    async fn take(
+        &self,
+        key: &str,
+        quota: &Quota,
+        now: DateTime<Utc>,
+    ) -> RepositoryResult<Decision>;
+
+    /// Puts back a token [`Self::take`] took from the bucket of `key`.
+    async fn give_back(&self, key: &str, quota: &Quota) -> RepositoryResult<()>;
+}
+
+/// Buckets of this instance alone, each instance allows the full quota.
+#[derive(Clone, Default)]
+pub struct InMemoryRateLimitStore {
+    buckets: Arc<Mutex<HashMap<String, (Bucket, Quota)>>>,
+}
+
+impl InMemoryRateLimitStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for InMemoryRateLimitStore {
+    async fn take(
+        &self,
+        key: &str,
+        quota: &Quota,
+        now: DateTime<Utc>,
+    ) -> RepositoryResult<Decision> {
+        let mut buckets = self
+            .buckets
+            .lock()
+            .expect("rate limit buckets are not poisoned");
+
+        if buckets.len() >= MAX_MEMORY_BUCKETS {
+            buckets.retain(|_, (bucket, quota)| bucket.expires_at(quota) > now);
+        }
+
+        let (bucket, decision) =
+            Bucket::take(buckets.get(key).map(|(bucket, _)| *bucket), quota, now);
+        buckets.insert(key.to_owned(), (bucket, *quota));
+
+        Ok(decision)
+    }
+
+    async fn give_back(&self, key: &str, quota: &Quota) -> RepositoryResult<()> {
+        let mut buckets = self
+            .buckets
+            .lock()
+            .expect("rate limit buckets are not poisoned");
+
+        if let Some((bucket, _)) = buckets.get_mut(key) {
+            *bucket = bucket.give_back(quota);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+struct BucketModel {
+    tokens: f64,
+    updated_at: DateTime<Utc>,
+}
+
+/// Buckets in the `rate_limit` table, shared by the instances using the same
+/// database. A bucket is only written if nobody wrote it since it was read,
+/// racing requests read it again.
+#[derive(Clone)]
+pub struct SurrealRateLimitStore {
+    driver: DatabaseDriver,
+    pruned_at: Arc<Mutex<Option<DateTime<Utc>>>>,
+}
+
+impl SurrealRateLimitStore {
+    pub fn new(driver: DatabaseDriver) -> Self {
+        Self {
+            driver,
+            pruned_at: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    async fn read(&self, key: &str) -> RepositoryResult<Option<Bucket>> {
+        let mut response = self
+            .driver
+            .client
+            .query("SELECT tokens, updated_at FROM type::thing('rate_limit', $key)")
+            .bind(("key", key))
+            .await?;
+
+        let result: Vec<BucketModel> = response.take(0)?;
+
+        Ok(result.into_iter().next().map(|model| Bucket {
+            tokens: model.tokens,
+            updated_at: model.updated_at,
+        }))
+    }
+
+    /// Returns whether the bucket was written, it was not if another request
+    /// wrote it since `previous` was read.
+    async fn write(
+        &self,
+        key: &str,
+        previous: Option<Bucket>,
+        bucket: Bucket,
+        quota: &Quota,
+    ) -> RepositoryResult<bool> {
+        let query = match previous {
+            None => {
+                r#"
+                CREATE type::thing('rate_limit', $key)
+                CONTENT { tokens: $tokens, updated_at: $updated_at, expires_at: $expires_at }
+                "#
+            }
+            Some(_) => {
+                r#"
+                UPDATE type::thing('rate_limit', $key)
+                SET tokens = $tokens, updated_at = $updated_at, expires_at = $expires_at
+                WHERE updated_at = $previous_updated_at
+                "#
+            }
+        };
+
+        let mut response = self
+            .driver
+            .client
+            .query(query)
+            .bind(("key", key))
+            .bind(("tokens", bucket.tokens))
+            .bind(("updated_at", Datetime(bucket.updated_at)))
+            .bind(("expires_at", Datetime(bucket.expires_at(quota))))
+            .bind((
+                "previous_updated_at",
+                previous.map(|bucket| Datetime(bucket.updated_at)),
+            ))
+            .await?;
+
+        // Creating a bucket another request just created fails.
+        match response.take::<Vec<BucketModel>>(0) {
+            Ok(written) => Ok(!written.is_empty()),
+            Err(_) if previous.is_none() => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn prune(&self, now: DateTime<Utc>) -> RepositoryResult<()> {
+        {
+            let mut pruned_at = self.pruned_at.lock().expect("prune time is not poisoned");
+
+            if matches!(*pruned_at, Some(at) if now - at < Duration::seconds(PRUNE_INTERVAL_SECONDS))
+            {
+                return Ok(());
+            }
+
+            *pruned_at = Some(now);
+        }
+
+        self.driver
+            .client
+            .query("DELETE rate_limit WHERE expires_at < $now")
+            .bind(("now", Datetime(now)))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for SurrealRateLimitStore {
+    async fn take(
+        &self,
+        key: &str,
+        quota: &Quota,
+        now: DateTime<Utc>,
+    ) -> RepositoryResult<Decision> {
+        self.prune(now).await?;
+
+        for _ in 0..MAX_WRITE_ATTEMPTS {
+            let previous = self.read(key).await?;
+            let (bucket, decision) = Bucket::take(previous, quota, now);
+
+            if self.write(key, previous, bucket, quota).await? {
+                return Ok(decision);
+            }
+        }
+
+        Err(RepositoryError::Query(format!(
+            "rate limit bucket {} kept changing while being taken from",
+            key
+        )))
+    }
+
+    async fn give_back(&self, key: &str, quota: &Quota) -> RepositoryResult<()> {
+        for _ in 0..MAX_WRITE_ATTEMPTS {
+            let Some(previous) = self.read(key).await? else {
+                return Ok(());
+            };
+
+            if self
+                .write(key, Some(previous), previous.give_back(quota), quota)
+                .await?
+            {
+                return Ok(());
+            }
+        }
+
+        Err(RepositoryError::Query(format!(
+            "rate limit bucket {} kept changing while being given back to",
+            key
+        )))
+    }
+}
@@ -0,0 +1,303 @@
+use std::{collections::HashSet, fmt, sync::Arc};
+
+use chrono::{SecondsFormat, Utc};
+use serde_json::{Map, Value};
+use tracing::{
+    field::{Field, Visit},
+    span::Record,
+    Event, Subscriber,
+};
+use tracing_subscriber::{
+    field::{MakeVisitor, RecordFields, VisitFmt, VisitOutput},
+    fmt::{
+        format::{FormatEvent, FormatFields, Writer},
+        FmtContext, FormattedFields,
+    },
+    registry::LookupSpan,
+};
+
+/// Written in place of the value of a masked field.
+pub static REDACTED: &str = "[REDACTED]";
+
+/// The field of an event holding its message, never masked.
+static MESSAGE_FIELD: &str = "message";
+
+/// Names of the log fields whose values are masked. A name also covers the
+/// fields ending in it, `token` masks `api_token` and `webhook.token` too.
+#[derive(Clone, Debug, Default)]
+pub struct Redaction {
+    fields: Arc<HashSet<String>>,
+}
+
+impl Redaction {
+    pub fn new<I, F>(fields: I) -> Self
+    where
+        I: IntoIterator<Item = F>,
+        F: AsRef<str>,
+    {
+        let fields = fields
+            .into_iter()
+            .map(|field| field.as_ref().trim().to_lowercase())
+            .filter(|field| !field.is_empty())
+            .collect();
+
+        Self {
+            fields: Arc::new(fields),
+        }
+    }
+
+    pub fn masks(&self, field: &str) -> bool {
+        if field == MESSAGE_FIELD {
+            return false;
+        }
+
+        let field = field.to_lowercase();
+        let last_part = field.rsplit(['.', '_']).next().unwrap_or_default();
+
+        self.fields.contains(&field) || self.fields.contains(last_part)
+    }
+}
+
+/// Formats fields like `inner` does, with the values of masked fields
+/// replaced by [`REDACTED`].
+#[derive(Debug)]
+pub struct RedactedFields<M> {
+    inner: M,
+    redaction: Redaction,
+}
+
+impl<M> RedactedFields<M> {
+    pub fn new(inner: M, redaction: Redaction) -> Self {
+        Self { inner, redaction }
+    }
+}
+
+impl<'a, M: MakeVisitor<Writer<'a>>> MakeVisitor<Writer<'a>> for RedactedFields<M> {
+    type Visitor = RedactingVisitor<M::Visitor>;
+
+    fn make_visitor(&self, target: Writer<'a>) -> Self::Visitor {
+        RedactingVisitor {
+            inner: self.inner.make_visitor(target),
+            redaction: self.redaction.clone(),
+        }
+    }
+}
+
+pub struct RedactingVisitor<V> {
+    inner: V,
+    redaction: Redaction,
+}
+
+impl<V: Visit> Visit for RedactingVisitor<V> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        if self.redaction.masks(field.name()) {
+            self.inner.record_str(field, REDACTED);
+        } else {
+            self.inner.record_f64(field, value);
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        if self.redaction.masks(field.name()) {
+            self.inner.record_str(field, REDACTED);
+        } else {
+            self.inner.record_i64(field, value);
+        }
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if self.redaction.masks(field.name()) {
+            self.inner.record_str(field, REDACTED);
+        } else {
+            self.inner.record_u64(field, value);
+        }
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        if self.redaction.masks(field.name()) {
+            self.inner.record_str(field, REDACTED);
+        } else {
+            self.inner.record_bool(field, value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if self.redaction.masks(field.name()) {
+            self.inner.record_str(field, REDACTED);
+        } else {
+            self.inner.record_str(field, value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if self.redaction.masks(field.name()) {
+            self.inner.record_str(field, REDACTED);
+        } else {
+            self.inner.record_debug(field, value);
+        }
+    }
+}
+
+impl<V: VisitOutput<fmt::Result>> VisitOutput<fmt::Result> for RedactingVisitor<V> {
+    fn finish(self) -> fmt::Result {
+        self.inner.finish()
+    }
+}
+
+impl<V: VisitFmt> VisitFmt for RedactingVisitor<V> {
+    fn writer(&mut self) -> &mut dyn fmt::Write {
+        self.inner.writer()
+    }
+}
+
+/// Formats span fields as a JSON object for [`RedactedJson`], with the
+/// values of masked fields replaced by [`REDACTED`].
+#[derive(Debug, Default)]
+pub struct RedactedJsonFields {
+    redaction: Redaction,
+}
+
+impl RedactedJsonFields {
+    pub fn new(redaction: Redaction) -> Self {
+        Self { redaction }
+    }
+}
+
+impl<'a> FormatFields<'a> for RedactedJsonFields {
+    fn format_fields<R: RecordFields>(&self, mut writer: Writer<'a>, fields: R) -> fmt::Result {
+        let mut visitor = JsonVisitor::new(Map::new(), &self.redaction);
+        fields.record(&mut visitor);
+
+        write!(writer, "{}", Value::Object(visitor.fields))
+    }
+
+    fn add_fields(
+        &self,
+        current: &'a mut FormattedFields<Self>,
+        fields: &Record<'_>,
+    ) -> fmt::Result {
+        let recorded = serde_json::from_str(&current.fields).unwrap_or_default();
+        let mut visitor = JsonVisitor::new(recorded, &self.redaction);
+        fields.record(&mut visitor);
+
+        current.fields = Value::Object(visitor.fields).to_string();
+
+        Ok(())
+    }
+}
+
+/// Writes every event as one line of JSON with its fields at the top level
+/// and, under `span`, the name of the current span with its fields and those
+/// of every span it is in, such as the request's `request_id`. The values of
+/// masked fields are replaced by [`REDACTED`]. The span fields have to be
+/// formatted by [`RedactedJsonFields`].
+#[derive(Debug, Default)]
+pub struct RedactedJson {
+    redaction: Redaction,
+}
+
+impl RedactedJson {
+    pub fn new(redaction: Redaction) -> Self {
+        Self { redaction }
+    }
+}
+
+impl<S, N> FormatEvent<S, N> for RedactedJson
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let metadata = event.metadata();
+
+        let mut line = Map::new();
+        line.insert(
+            String::from("timestamp"),
+            Value::from(Utc::now().to_rfc3339_opts(SecondsFormat::Micros, true)),
+        );
+        line.insert(
+            String::from("level"),
+            Value::from(metadata.level().to_string()),
+        );
+
+        let mut visitor = JsonVisitor::new(line, &self.redaction);
+        event.record(&mut visitor);
+        let mut line = visitor.fields;
+
+        line.insert(String::from("target"), Value::from(metadata.target()));
+
+        if let Some(scope) = ctx.event_scope() {
+            let mut fields = Map::new();
+            let mut name = None;
+
+            // From the root, so a span's fields win over its parents' ones.
+            for span in scope.from_root() {
+                let span_fields: Map<String, Value> = span
+                    .extensions()
+                    .get::<FormattedFields<N>>()
+                    .and_then(|fields| serde_json::from_str(&fields.fields).ok())
+                    .unwrap_or_default();
+
+                fields.extend(span_fields);
+                name = Some(span.name());
+            }
+
+            fields.insert(String::from("name"), Value::from(name));
+            line.insert(String::from("span"), Value::Object(fields));
+        }
+
+        writeln!(writer, "{}", Value::Object(line))
+    }
+}
+
+struct JsonVisitor<'a> {
+    fields: Map<String, Value>,
+    redaction: &'a Redaction,
+}
+
+impl<'a> JsonVisitor<'a> {
+    fn new(fields: Map<String, Value>, redaction: &'a Redaction) -> Self {
+        Self { fields, redaction }
+    }
+
+    fn insert(&mut self, field: &Field, value: impl FnOnce() -> Value) {
+        let value = if self.redaction.masks(field.name()) {
+            Value::from(REDACTED)
+        } else {
+            value()
+        };
+
+        self.fields.insert(field.name().to_string(), value);
+    }
+}
+
+impl Visit for JsonVisitor<'_> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, || Value::from(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, || Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, || Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, || Value::from(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, || Value::from(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.insert(field, || Value::from(format!("{:?}", value)));
+    }
+}
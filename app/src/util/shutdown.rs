@@ -0,0 +1,76 @@
+use std::{
+    mem,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures::future::join_all;
+use tokio::{sync::watch, task::JoinHandle};
+use tracing::warn;
+
+/// Tells the server and the background tasks to stop, and waits for the
+/// tasks that were tracked. Clones share the same signal and tasks.
+#[derive(Clone)]
+pub struct Shutdown {
+    sender: Arc<watch::Sender<bool>>,
+    tasks: Arc<Mutex<Vec<JoinHandle<()>>>>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        let (sender, _) = watch::channel(false);
+
+        Self {
+            sender: Arc::new(sender),
+            tasks: Arc::new(Mutex::new(vec![])),
+        }
+    }
+
+    pub fn trigger(&self) {
+        self.sender.send_replace(true);
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        *self.sender.borrow()
+    }
+
+    /// Resolves once [`Self::trigger`] is called, right away if it was.
+    pub async fn triggered(&self) {
+        let mut receiver = self.sender.subscribe();
+
+        // Only fails when the sender is dropped, and `self` holds it.
+        let _ = receiver.wait_for(|triggered| *triggered).await;
+    }
+
+    /// Waited for by [`Self::wait_for_tasks`].
+    pub fn track(&self, task: JoinHandle<()>) {
+        self.tasks.lock().unwrap().push(task);
+    }
+
+    /// Waits up to `timeout` for the tracked tasks to finish, aborting the
+    /// ones still running after it.
+    pub async fn wait_for_tasks(&self, timeout: Duration) {
+        let tasks = mem::take(&mut *self.tasks.lock().unwrap());
+        let aborts: Vec<_> = tasks.iter().map(JoinHandle::abort_handle).collect();
+
+        if tokio::time::timeout(timeout, join_all(tasks))
+            .await
+            .is_err()
+        {
+            warn!(
+                "Background tasks still running after {}s, aborting them",
+                timeout.as_secs()
+            );
+
+            for abort in aborts {
+                abort.abort();
+            }
+        }
+    }
+}
@@ -1,33 +1,110 @@
+use axum::{extract::MatchedPath, http::Request};
+use futures::future::BoxFuture;
+use opentelemetry::{propagation::TextMapPropagator, trace::TracerProvider as _, KeyValue};
+use opentelemetry_http::HeaderExtractor;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{
+    export::trace::{ExportResult, SpanData, SpanExporter},
+    propagation::TraceContextPropagator,
+    runtime,
+    trace::{self as sdktrace, EvictedQueue, TracerProvider},
+    Resource,
+};
 use tower_http::{
     classify::{ServerErrorsAsFailures, SharedClassifier},
-    trace::{DefaultMakeSpan, DefaultOnRequest, DefaultOnResponse, TraceLayer},
+    trace::{DefaultOnRequest, DefaultOnResponse, MakeSpan, TraceLayer},
+};
+use tracing::{Level, Span, Subscriber};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::{
+    fmt::{self, format::DefaultFields, MakeWriter},
+    layer::SubscriberExt,
+    registry::LookupSpan,
+    util::SubscriberInitExt,
+    EnvFilter, Layer,
 };
-use tracing::Level;
 
 use crate::common::{Config, Environment};
 
+use super::{
+    RedactedFields, RedactedJson, RedactedJsonFields, Redaction, REDACTED, REQUEST_ID_HEADER,
+};
+
+/// Used when `RUST_LOG` is unset or cannot be parsed.
+static DEFAULT_LOG_FILTER: &str = "info";
+
 pub struct SystemTelemetry;
-pub type Telemetry = TraceLayer<SharedClassifier<ServerErrorsAsFailures>>;
+pub type Telemetry = TraceLayer<SharedClassifier<ServerErrorsAsFailures>, RequestSpan>;
 
 impl SystemTelemetry {
+    /// Logs to stdout, as JSON in production, filtered by `RUST_LOG`. When
+    /// `OTEL_EXPORTER_OTLP_ENDPOINT` is set spans are also exported there over
+    /// OTLP/HTTP.
     pub fn init(config: &Config) -> Telemetry {
-        tracing_subscriber::fmt()
-            .compact()
-            .with_target(false)
+        let tracer = config.otlp_endpoint.as_deref().map(|endpoint| {
+            let exporter = opentelemetry_otlp::new_exporter()
+                .http()
+                .with_endpoint(endpoint)
+                .build_span_exporter()
+                .expect("Unable to build the OTLP exporter");
+            let redaction = Redaction::new(&config.redacted_fields);
+            let provider = tracer_provider(config, RedactedSpans::new(exporter, redaction));
+            let tracer = provider.tracer(config.app.name.clone());
+
+            opentelemetry::global::set_tracer_provider(provider);
+
+            tracer
+        });
+
+        let filter = EnvFilter::try_from_default_env()
+            .unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_FILTER));
+
+        tracing_subscriber::registry()
+            .with(filter)
+            .with(Self::logs(config, std::io::stdout))
+            .with(tracer.map(|tracer| tracing_opentelemetry::layer().with_tracer(tracer)))
             .init();
 
+        Self::layer(config)
+    }
+
+    /// Log lines written to `writer`, as JSON in production and compact
+    /// otherwise, with the values of `LOG_REDACTED_FIELDS` masked either way.
+    pub fn logs<S, W>(config: &Config, writer: W) -> Box<dyn Layer<S> + Send + Sync>
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+        W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+    {
+        let redaction = Redaction::new(&config.redacted_fields);
+
         match config.env {
-            Environment::Production => TraceLayer::new_for_http().make_span_with(
-                DefaultMakeSpan::new()
-                    .level(Level::INFO)
-                    .include_headers(false),
-            ),
+            Environment::Production => fmt::layer()
+                .event_format(RedactedJson::new(redaction.clone()))
+                .fmt_fields(RedactedJsonFields::new(redaction))
+                .with_writer(writer)
+                .boxed(),
+            _ => fmt::layer()
+                .compact()
+                .with_target(false)
+                .fmt_fields(RedactedFields::new(DefaultFields::new(), redaction))
+                .with_writer(writer)
+                .boxed(),
+        }
+    }
+
+    /// Exports the spans still buffered, before the process exits.
+    pub async fn shutdown() {
+        // The provider blocks on its batch exporter while shutting down.
+        let _ = tokio::task::spawn_blocking(opentelemetry::global::shutdown_tracer_provider).await;
+    }
+
+    /// The request tracing layer alone, for when the subscriber is set up
+    /// elsewhere.
+    pub fn layer(config: &Config) -> Telemetry {
+        match config.env {
+            Environment::Production => TraceLayer::new_for_http().make_span_with(RequestSpan),
             _ => TraceLayer::new_for_http()
-                .make_span_with(
-                    DefaultMakeSpan::new()
-                        .level(Level::INFO)
-                        .include_headers(false),
-                )
+                .make_span_with(RequestSpan)
                 .on_response(
                     DefaultOnResponse::new()
                         .level(Level::INFO)
@@ -37,3 +114,97 @@ impl SystemTelemetry {
         }
     }
 }
+
+/// Opens the span of a request, tagged with its route and `x-request-id` so
+/// every log line of the request carries them. A W3C `traceparent` header makes it a
+/// child of the caller's span, so the trace carries on through this service.
+#[derive(Clone, Debug)]
+pub struct RequestSpan;
+
+impl<B> MakeSpan<B> for RequestSpan {
+    fn make_span(&mut self, request: &Request<B>) -> Span {
+        // The route rather than the URI, which can hold a calendar feed token
+        // in its path or search terms in its query.
+        let route = request
+            .extensions()
+            .get::<MatchedPath>()
+            .map_or_else(|| request.uri().path(), MatchedPath::as_str);
+
+        let span = tracing::info_span!(
+            "request",
+            method = %request.method(),
+            route = %route,
+            version = ?request.version(),
+            request_id = request
+                .headers()
+                .get(REQUEST_ID_HEADER)
+                .and_then(|id| id.to_str().ok())
+                .unwrap_or_default(),
+        );
+        let parent = TraceContextPropagator::new().extract(&HeaderExtractor(request.headers()));
+        span.set_parent(parent);
+
+        span
+    }
+}
+
+/// Exports spans through `inner` with the values of masked attributes, and of
+/// masked fields of the events logged in them, replaced by [`REDACTED`].
+#[derive(Debug)]
+pub struct RedactedSpans<E> {
+    inner: E,
+    redaction: Redaction,
+}
+
+impl<E: SpanExporter> RedactedSpans<E> {
+    pub fn new(inner: E, redaction: Redaction) -> Self {
+        Self { inner, redaction }
+    }
+
+    fn mask(&self, attributes: &mut [KeyValue]) {
+        for attribute in attributes {
+            if self.redaction.masks(attribute.key.as_str()) {
+                attribute.value = REDACTED.into();
+            }
+        }
+    }
+}
+
+impl<E: SpanExporter> SpanExporter for RedactedSpans<E> {
+    fn export(&mut self, mut batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+        for span in batch.iter_mut() {
+            self.mask(&mut span.attributes);
+
+            // The queue cannot be changed in place, nor tell how many it can
+            // hold, the events are all kept in a new one.
+            let mut events: Vec<_> = std::mem::replace(&mut span.events, EvictedQueue::new(0))
+                .into_iter()
+                .collect();
+            for event in events.iter_mut() {
+                self.mask(&mut event.attributes);
+            }
+            span.events = EvictedQueue::new(events.len() as u32);
+            span.events.append_vec(&mut events);
+        }
+
+        self.inner.export(batch)
+    }
+
+    fn shutdown(&mut self) {
+        self.inner.shutdown()
+    }
+
+    fn force_flush(&mut self) -> BoxFuture<'static, ExportResult> {
+        self.inner.force_flush()
+    }
+}
+
+fn tracer_provider<E: SpanExporter + 'static>(config: &Config, exporter: E) -> TracerProvider {
+    TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_config(sdktrace::config().with_resource(Resource::new([
+            KeyValue::new("service.name", config.app.name.clone()),
+            KeyValue::new("service.version", config.app.version.clone()),
+        ])))
+        .build()
+}
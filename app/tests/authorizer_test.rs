@@ -0,0 +1,59 @@
+use app::util::{require_api_key, ApiKey, API_KEY_HEADER};
+use axum::{
+    http::{header, StatusCode},
+    middleware, routing, Router,
+};
+use axum_test_helper::TestClient;
+
+fn app(api_key: ApiKey) -> TestClient {
+    TestClient::new(
+        Router::new()
+            .route("/", routing::post(|| async { "ok" }))
+            .route_layer(middleware::from_fn_with_state(api_key, require_api_key)),
+    )
+}
+
+#[tokio::test]
+async fn lets_requests_with_the_key_through() {
+    let app = app(ApiKey::new(Some("s3cr3t")));
+
+    let res = app.post("/").header(API_KEY_HEADER, "s3cr3t").send().await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = app
+        .post("/")
+        .header(header::AUTHORIZATION, "Bearer s3cr3t")
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn refuses_requests_without_the_key() {
+    let app = app(ApiKey::new(Some("s3cr3t")));
+
+    let res = app.post("/").send().await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = app.post("/").header(API_KEY_HEADER, "guess").send().await;
+    let status = res.status();
+    let problem: serde_json::Value = res.json().await;
+
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(problem["code"], "UNAUTHORIZED");
+}
+
+#[tokio::test]
+async fn refuses_everyone_while_no_key_is_configured() {
+    let app = app(ApiKey::new(None));
+
+    let res = app.post("/").header(API_KEY_HEADER, "").send().await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = app
+        .post("/")
+        .header(API_KEY_HEADER, "anything")
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,155 @@
+use app::{docs::v1::calendar::CalendarFeedResponse, util::API_KEY_HEADER};
+use axum::http::StatusCode;
+use axum_test_helper::TestClient;
+use serde_json::json;
+use tokio::sync::Mutex;
+use ulid::Ulid;
+
+use crate::fixtures::{
+    app::{get_app, Dependencies, ADMIN_API_KEY, DATETIME_STRING},
+    clock::MockClock,
+    id_generator::MockUlidGenerator,
+};
+
+mod fixtures;
+
+// The feed token is shared by the whole workspace, so tests that rotate it
+// must not interleave.
+static FEED_LOCK: Mutex<()> = Mutex::const_new(());
+
+#[tokio::test]
+async fn returns_not_found_for_unknown_token() {
+    let id = Ulid::new();
+    let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+    let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+    let dependencies = Dependencies::new(clock.clone(), id_generator);
+
+    let app = get_app(dependencies).await;
+
+    let res = app.get("/v1/calendar/not-a-token.ics").send().await;
+
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn serves_open_todos_as_calendar_entries() {
+    let _guard = FEED_LOCK.lock().await;
+    let id = Ulid::new();
+    let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+    let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+    let dependencies = Dependencies::new(clock.clone(), id_generator);
+
+    let app = get_app(dependencies).await;
+    create_one_todo(&app).await;
+
+    let feed = rotate_token(&app).await;
+
+    let res = app.get(&feed.url).send().await;
+
+    let response_status = res.status();
+    let content_type = res.headers()["content-type"].to_str().unwrap().to_string();
+    let response_body = res.text().await;
+
+    assert_eq!(response_status, StatusCode::OK);
+    assert!(content_type.starts_with("text/calendar"));
+    assert!(response_body.contains(&format!("UID:{}-due\r\n", id)));
+    assert!(response_body.contains("DTSTART:20231104T153234Z\r\n"));
+    assert!(response_body.contains(&format!("UID:{}\r\n", id)));
+    assert!(response_body.contains("DUE:20231104T153234Z\r\n"));
+}
+
+#[tokio::test]
+async fn returns_not_modified_for_matching_etag() {
+    let _guard = FEED_LOCK.lock().await;
+    let id = Ulid::new();
+    let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+    let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+    let dependencies = Dependencies::new(clock.clone(), id_generator);
+
+    let app = get_app(dependencies).await;
+
+    let feed = rotate_token(&app).await;
+
+    let res = app.get(&feed.url).send().await;
+    let etag = res.headers()["etag"].to_str().unwrap().to_string();
+
+    let res = app
+        .get(&feed.url)
+        .header("if-none-match", etag.clone())
+        .send()
+        .await;
+
+    assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(res.headers()["etag"].to_str().unwrap(), etag);
+}
+
+#[tokio::test]
+async fn rotation_revokes_the_previous_token() {
+    let _guard = FEED_LOCK.lock().await;
+    let id = Ulid::new();
+    let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+    let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+    let dependencies = Dependencies::new(clock.clone(), id_generator);
+
+    let app = get_app(dependencies).await;
+
+    let previous = rotate_token(&app).await;
+    let current = rotate_token(&app).await;
+
+    assert_ne!(previous.token, current.token);
+
+    let res = app.get(&previous.url).send().await;
+
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let res = app.get(&current.url).send().await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn rotating_the_token_takes_the_api_key() {
+    let id = Ulid::new();
+    let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+    let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+    let app = get_app(Dependencies::new(clock, id_generator)).await;
+
+    let res = app.post("/v1/calendar/token").send().await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = app
+        .post("/v1/calendar/token")
+        .header(API_KEY_HEADER, "not-the-key")
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
+async fn rotate_token(app: &TestClient) -> CalendarFeedResponse {
+    let res = app
+        .post("/v1/calendar/token")
+        .header(API_KEY_HEADER, ADMIN_API_KEY)
+        .send()
+        .await;
+
+    assert_eq!(res.status(), StatusCode::OK, "Unable to rotate feed token");
+
+    res.json().await
+}
+
+async fn create_one_todo(app: &TestClient) {
+    let payload = json!({
+      "description": "Dummy description",
+      "dueDate": DATETIME_STRING,
+      "subject": "Dummy subject"
+    });
+
+    let res = app.post("/v1/todos").json(&payload).send().await;
+
+    assert_eq!(res.status(), StatusCode::OK, "Unable to create Todo");
+}
@@ -0,0 +1,73 @@
+use std::time::{Duration, Instant};
+
+use app::{
+    common::{Config, DatabaseDriver, DatabaseError, DatabaseMonitor},
+    util::{CircuitBreaker, SystemClock, UlidGenerator},
+    AppBuilder,
+};
+use axum::http::StatusCode;
+use axum_test_helper::TestClient;
+use surrealdb::Surreal;
+
+/// A client that never connected, so every query fails right away.
+fn unreachable_driver() -> DatabaseDriver {
+    DatabaseDriver {
+        client: Surreal::init(),
+    }
+}
+
+#[tokio::test]
+async fn gives_up_connecting_after_the_configured_attempts() {
+    let mut config = Config::new();
+    config.db.url = String::from("127.0.0.1:1");
+    config.db.connect_attempts = 2;
+
+    let started = Instant::now();
+    let result = DatabaseDriver::init(&config).await;
+
+    assert!(matches!(result, Err(DatabaseError::Connection(_))));
+    assert!(started.elapsed() >= Duration::from_millis(500));
+}
+
+#[tokio::test]
+async fn opens_the_breaker_while_the_database_is_unreachable() {
+    let breaker = CircuitBreaker::new(2);
+    let monitor = DatabaseMonitor::new(unreachable_driver(), Config::new(), breaker.clone());
+
+    monitor.tick().await;
+    assert!(!breaker.is_open());
+
+    monitor.tick().await;
+    assert!(breaker.is_open());
+
+    assert!(breaker.record_success());
+    assert!(!breaker.is_open());
+}
+
+#[tokio::test]
+async fn fails_fast_while_the_breaker_is_open() {
+    let breaker = CircuitBreaker::new(1);
+    breaker.record_failure();
+
+    let app = AppBuilder::new()
+        .clock(SystemClock::new())
+        .config(Config::new())
+        .database_driver(unreachable_driver())
+        .id_generator(UlidGenerator::new())
+        .circuit_breaker(breaker)
+        .build()
+        .await
+        .unwrap();
+    let client = TestClient::new(app);
+
+    let res = client.get("/v1/todos").send().await;
+    let response_status = res.status();
+    let response_body: serde_json::Value = res.json().await;
+
+    assert_eq!(response_status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(response_body["code"], "SERVICE_UNAVAILABLE");
+
+    let res = client.get("/livez").send().await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+}
@@ -0,0 +1,126 @@
+use app::docs::v1::events::{EventKind, TodoEvent};
+use axum::http::StatusCode;
+use axum_test_helper::TestClient;
+use serde_json::json;
+use ulid::Ulid;
+
+use crate::fixtures::{
+    app::{get_app, Dependencies, DATETIME_STRING},
+    clock::MockClock,
+    events::{get_relay, RecordingSubscriber},
+    id_generator::MockUlidGenerator,
+};
+
+mod fixtures;
+
+#[tokio::test]
+async fn publishes_an_event_for_every_change() {
+    let id = Ulid::new();
+    let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+    let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+    let subscriber = RecordingSubscriber::new();
+
+    let dependencies = Dependencies::new(clock, id_generator).with_subscriber(subscriber.clone());
+    let app = get_app(dependencies).await;
+
+    create_todo(&app, "2023-11-05T18:00:00Z").await;
+
+    let res = app
+        .patch(&format!("/v1/todos/{}", id))
+        .json(&json!({ "subject": "Renamed", "isDone": true }))
+        .send()
+        .await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = app.delete(&format!("/v1/todos/{}", id)).send().await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let events = subscriber.events_for(&id);
+    let kinds: Vec<EventKind> = events.iter().map(|event| event.event.kind()).collect();
+
+    assert_eq!(
+        kinds,
+        vec![
+            EventKind::TodoCreated,
+            EventKind::TodoUpdated,
+            EventKind::TodoCompleted,
+            EventKind::TodoDeleted
+        ]
+    );
+    assert!(events[0].id.ends_with("_todo.created"));
+
+    let TodoEvent::TodoUpdated { changes, .. } = &events[1].event else {
+        panic!("expected an update, got {:?}", events[1].event);
+    };
+    let changes: Vec<_> = changes
+        .iter()
+        .map(|change| (change.field.as_str(), &change.from, &change.to))
+        .collect();
+
+    assert_eq!(
+        changes,
+        vec![
+            ("subject", &json!("Dummy subject"), &json!("Renamed")),
+            ("isDone", &json!(false), &json!(true)),
+        ]
+    );
+    assert_eq!(events[3].event.todo().subject, "Renamed");
+}
+
+// The relay publishes every change left unpublished in the database, so the
+// todo here happens in 2001, before any other test's.
+
+#[tokio::test]
+async fn republishes_a_change_until_every_subscriber_handled_it() {
+    let id = Ulid::new();
+    let clock = MockClock::with_frozen_time("2001-01-01T00:00:00Z".into());
+    let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+    let subscriber = RecordingSubscriber::new();
+
+    let dependencies = Dependencies::new(clock, id_generator).with_subscriber(subscriber.clone());
+    let app = get_app(dependencies).await;
+
+    subscriber.fail(true);
+    create_todo(&app, "2001-01-02T18:00:00Z").await;
+
+    assert_eq!(subscriber.events_for(&id).len(), 1);
+
+    subscriber.fail(false);
+    relay_tick_at("2001-01-01T00:00:30Z", &subscriber).await;
+
+    // Left to the request that made the change for a minute.
+    assert_eq!(subscriber.events_for(&id).len(), 1);
+
+    relay_tick_at("2001-01-01T00:02:00Z", &subscriber).await;
+    relay_tick_at("2001-01-01T00:03:00Z", &subscriber).await;
+
+    let events = subscriber.events_for(&id);
+
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[1].id, events[0].id);
+    assert_eq!(events[1].event.kind(), EventKind::TodoCreated);
+
+    app.delete(&format!("/v1/todos/{}", id)).send().await;
+}
+
+async fn create_todo(app: &TestClient, due_date: &str) {
+    let payload = json!({
+      "description": "Dummy description",
+      "dueDate": due_date,
+      "subject": "Dummy subject",
+    });
+
+    let res = app.post("/v1/todos").json(&payload).send().await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+async fn relay_tick_at(time: &str, subscriber: &RecordingSubscriber) {
+    get_relay(MockClock::with_frozen_time(time.into()), subscriber.clone())
+        .await
+        .tick()
+        .await
+        .unwrap();
+}
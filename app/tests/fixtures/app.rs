@@ -1,16 +1,25 @@
 use app::{
     common::{Config, DatabaseDriver},
-    util::{Clock, IdGenerator},
+    resource::v1::health::Readiness,
+    util::{Clock, IdGenerator, SystemTelemetry},
     AppBuilder,
 };
 use axum_test_helper::TestClient;
 use ulid::Ulid;
 
+use super::events::RecordingSubscriber;
+
 pub static DATETIME_STRING: &str = "2023-11-04T15:32:34.205052Z";
 
+/// The `ADMIN_API_KEY` of the test app.
+pub static ADMIN_API_KEY: &str = "test-admin-key";
+
 pub struct Dependencies<C: Clock + Clone, G: IdGenerator<Ulid> + Clone> {
     pub clock: C,
     id_generator: G,
+    subscriber: Option<RecordingSubscriber>,
+    telemetry: bool,
+    readiness: Option<Readiness>,
 }
 
 impl<C: Clock + Clone, G: IdGenerator<Ulid> + Clone> Dependencies<C, G> {
@@ -18,31 +27,78 @@ impl<C: Clock + Clone, G: IdGenerator<Ulid> + Clone> Dependencies<C, G> {
         Self {
             clock,
             id_generator,
+            subscriber: None,
+            telemetry: false,
+            readiness: None,
         }
     }
+
+    // Only the event tests register a subscriber.
+    #[allow(dead_code)]
+    pub fn with_subscriber(mut self, subscriber: RecordingSubscriber) -> Self {
+        self.subscriber = Some(subscriber);
+
+        self
+    }
+
+    // Only the tracing tests need request spans.
+    #[allow(dead_code)]
+    pub fn with_telemetry(mut self) -> Self {
+        self.telemetry = true;
+
+        self
+    }
+
+    // Only the health tests drain the app.
+    #[allow(dead_code)]
+    pub fn with_readiness(mut self, readiness: Readiness) -> Self {
+        self.readiness = Some(readiness);
+
+        self
+    }
 }
 
 pub async fn get_app<C, G>(
     Dependencies {
         clock,
         id_generator,
+        subscriber,
+        telemetry,
+        readiness,
     }: Dependencies<C, G>,
 ) -> TestClient
 where
     C: Clock + Clone,
     G: IdGenerator<Ulid> + Clone,
 {
-    let config = Config::new();
+    let mut config = Config::new();
+    config.admin_api_key = Some(String::from(ADMIN_API_KEY));
+    // The webhook receivers listen on loopback.
+    config.webhook_allow_private_urls = true;
 
     let database_driver = DatabaseDriver::init(&config)
         .await
         .unwrap_or_else(|_| panic!("Unable to init database driver"));
 
-    let app = AppBuilder::new()
+    let mut app = AppBuilder::new()
         .clock(clock)
-        .config(config)
+        .config(config.clone())
         .database_driver(database_driver)
-        .id_generator(id_generator)
+        .id_generator(id_generator);
+
+    if let Some(subscriber) = subscriber {
+        app = app.subscriber(subscriber);
+    }
+
+    if let Some(readiness) = readiness {
+        app = app.readiness(readiness);
+    }
+
+    if telemetry {
+        app = app.telemetry(SystemTelemetry::layer(&config));
+    }
+
+    let app = app
         .build()
         .await
         .unwrap_or_else(|_| panic!("Unable to build App"));
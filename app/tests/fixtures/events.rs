@@ -0,0 +1,83 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+
+use app::{
+    common::{Config, DatabaseDriver},
+    docs::v1::events::DomainEvent,
+    resource::v1::{
+        events::{
+            EventBus, EventPublisher, EventSubscriber, OutboxRelay, OutboxRepository,
+            SubscriberError,
+        },
+        webhooks::{WebhookRepository, WebhookSubscriber},
+    },
+    util::Clock,
+};
+use axum::async_trait;
+use ulid::Ulid;
+
+/// Keeps every event it is handed, and fails them while told to.
+#[derive(Clone, Default)]
+pub struct RecordingSubscriber {
+    events: Arc<Mutex<Vec<DomainEvent>>>,
+    failing: Arc<AtomicBool>,
+}
+
+impl RecordingSubscriber {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn fail(&self, failing: bool) {
+        self.failing.store(failing, Ordering::SeqCst);
+    }
+
+    /// Events about the todo, in the order they were handled.
+    pub fn events_for(&self, todo_id: &Ulid) -> Vec<DomainEvent> {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|event| event.event.todo().id == *todo_id)
+            .cloned()
+            .collect()
+    }
+}
+
+#[async_trait]
+impl EventSubscriber for RecordingSubscriber {
+    fn name(&self) -> &'static str {
+        "recording"
+    }
+
+    async fn handle(&self, event: &DomainEvent) -> Result<(), SubscriberError> {
+        self.events.lock().unwrap().push(event.clone());
+
+        if self.failing.load(Ordering::SeqCst) {
+            return Err(SubscriberError(String::from("told to fail")));
+        }
+
+        Ok(())
+    }
+}
+
+/// A relay publishing to the webhooks and the given subscriber, like the app.
+pub async fn get_relay<C: Clock>(clock: C, subscriber: RecordingSubscriber) -> OutboxRelay<C> {
+    let database_driver = DatabaseDriver::init(&Config::new())
+        .await
+        .unwrap_or_else(|_| panic!("Unable to init database driver"));
+
+    let mut bus = EventBus::new();
+    bus.subscribe(Arc::new(WebhookSubscriber::new(WebhookRepository::new(
+        database_driver.clone(),
+    ))));
+    bus.subscribe(Arc::new(subscriber));
+
+    OutboxRelay::new(
+        OutboxRepository::new(database_driver.clone()),
+        clock,
+        EventPublisher::new(OutboxRepository::new(database_driver), bus),
+    )
+}
@@ -1,3 +1,15 @@
 pub mod app;
 pub mod clock;
+// Only the event tests record what was published.
+#[allow(dead_code)]
+pub mod events;
 pub mod id_generator;
+// Only the reminder tests drive a scheduler.
+#[allow(dead_code)]
+pub mod notifier;
+// Only the tracing tests collect spans.
+#[allow(dead_code)]
+pub mod tracing;
+// Only the webhook tests run a receiver.
+#[allow(dead_code)]
+pub mod webhook;
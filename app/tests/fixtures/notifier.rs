@@ -0,0 +1,52 @@
+use std::sync::{Arc, Mutex};
+
+use app::{
+    common::{Config, DatabaseDriver},
+    docs::v1::reminders::Reminder,
+    resource::v1::reminders::{Notifier, NotifierError, ReminderRepository, ReminderScheduler},
+    util::Clock,
+};
+use axum::async_trait;
+use ulid::Ulid;
+
+/// Keeps every reminder it is handed, for tests to look at.
+#[derive(Clone, Default)]
+pub struct RecordingNotifier {
+    reminders: Arc<Mutex<Vec<Reminder>>>,
+}
+
+impl RecordingNotifier {
+    pub fn reminders_for(&self, todo_id: &Ulid) -> Vec<Reminder> {
+        self.reminders
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|reminder| &reminder.todo_id == todo_id)
+            .cloned()
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Notifier for RecordingNotifier {
+    async fn notify(&self, reminder: &Reminder) -> Result<(), NotifierError> {
+        self.reminders.lock().unwrap().push(reminder.clone());
+
+        Ok(())
+    }
+}
+
+pub async fn get_scheduler<C: Clock>(
+    clock: C,
+    notifier: RecordingNotifier,
+) -> ReminderScheduler<C> {
+    let database_driver = DatabaseDriver::init(&Config::new())
+        .await
+        .unwrap_or_else(|_| panic!("Unable to init database driver"));
+
+    ReminderScheduler::new(
+        ReminderRepository::new(database_driver),
+        clock,
+        Box::new(notifier),
+    )
+}
@@ -0,0 +1,70 @@
+use std::sync::{Arc, Mutex};
+
+use app::{
+    common::Config,
+    util::{RedactedSpans, Redaction},
+};
+use futures::future::{self, BoxFuture};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_sdk::{
+    export::trace::{ExportResult, SpanData, SpanExporter},
+    trace::TracerProvider,
+};
+use tracing::subscriber::DefaultGuard;
+use tracing_subscriber::layer::SubscriberExt;
+
+/// Stand-in for an OTLP collector, keeping every span it is sent.
+#[derive(Clone, Debug, Default)]
+struct SpanStore {
+    spans: Arc<Mutex<Vec<SpanData>>>,
+}
+
+impl SpanExporter for SpanStore {
+    fn export(&mut self, batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+        self.spans.lock().unwrap().extend(batch);
+
+        Box::pin(future::ready(Ok(())))
+    }
+}
+
+/// Exports the spans of the current thread to an in-process collector until
+/// dropped, masking the configured fields like the OTLP exporter. Tests run on a single thread, the app they serve included.
+pub struct Collector {
+    store: SpanStore,
+    provider: TracerProvider,
+    _guard: DefaultGuard,
+}
+
+impl Collector {
+    pub fn start() -> Self {
+        let store = SpanStore::default();
+        let redaction = Redaction::new(&Config::new().redacted_fields);
+        let provider = TracerProvider::builder()
+            .with_simple_exporter(RedactedSpans::new(store.clone(), redaction))
+            .build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+
+        Self {
+            store,
+            provider,
+            _guard: tracing::subscriber::set_default(subscriber),
+        }
+    }
+
+    /// Every finished span with the name.
+    pub fn spans_named(&self, name: &str) -> Vec<SpanData> {
+        for result in self.provider.force_flush() {
+            result.unwrap();
+        }
+
+        self.store
+            .spans
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|span| span.name == name)
+            .cloned()
+            .collect()
+    }
+}
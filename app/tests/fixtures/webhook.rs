@@ -0,0 +1,111 @@
+use std::{
+    net::TcpListener,
+    sync::{Arc, Mutex},
+};
+
+use app::{
+    common::{Config, DatabaseDriver},
+    docs::v1::webhooks::WebhookPayload,
+    resource::v1::webhooks::{WebhookDispatcher, WebhookRepository},
+    util::Clock,
+};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing, Router,
+};
+use ulid::Ulid;
+
+#[derive(Clone, Debug)]
+pub struct ReceivedRequest {
+    pub headers: HeaderMap,
+    pub body: String,
+}
+
+impl ReceivedRequest {
+    pub fn header(&self, name: &str) -> &str {
+        self.headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+    }
+
+    pub fn payload(&self) -> WebhookPayload {
+        serde_json::from_str(&self.body).unwrap()
+    }
+}
+
+#[derive(Clone)]
+struct ReceiverState {
+    requests: Arc<Mutex<Vec<ReceivedRequest>>>,
+    status: Arc<Mutex<StatusCode>>,
+}
+
+/// Stand-in for a webhook consumer, listening on a random local port and
+/// keeping every request it is sent.
+#[derive(Clone)]
+pub struct Receiver {
+    state: ReceiverState,
+    pub url: String,
+}
+
+impl Receiver {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+
+        let state = ReceiverState {
+            requests: Arc::default(),
+            status: Arc::new(Mutex::new(StatusCode::OK)),
+        };
+        let router = Router::new()
+            .route("/hook", routing::post(receive))
+            .with_state(state.clone());
+
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(router.into_make_service()),
+        );
+
+        Self { state, url }
+    }
+
+    pub fn answer_with(&self, status: StatusCode) {
+        *self.state.status.lock().unwrap() = status;
+    }
+
+    /// Requests about the todo, in the order they came in.
+    pub fn requests_for(&self, todo_id: &Ulid) -> Vec<ReceivedRequest> {
+        self.state
+            .requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|request| request.payload().todo.id == todo_id.to_string())
+            .cloned()
+            .collect()
+    }
+}
+
+async fn receive(
+    State(state): State<ReceiverState>,
+    headers: HeaderMap,
+    body: String,
+) -> StatusCode {
+    state
+        .requests
+        .lock()
+        .unwrap()
+        .push(ReceivedRequest { headers, body });
+
+    *state.status.lock().unwrap()
+}
+
+pub async fn get_dispatcher<C: Clock>(clock: C) -> WebhookDispatcher<C> {
+    let database_driver = DatabaseDriver::init(&Config::new())
+        .await
+        .unwrap_or_else(|_| panic!("Unable to init database driver"));
+
+    WebhookDispatcher::new(WebhookRepository::new(database_driver), clock).with_private_urls(true)
+}
@@ -1,4 +1,8 @@
-use app::docs::v1::health::HealthStatusResponse;
+use app::{
+    common::Config,
+    docs::v1::health::{HealthStatus, HealthStatusResponse, ProbeResponse},
+    resource::v1::health::Readiness,
+};
 use axum::http::StatusCode;
 use ulid::Ulid;
 
@@ -30,6 +34,7 @@ async fn returns_health_data() {
     let id = Ulid::new();
     let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
     let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+    let config = Config::new();
 
     let dependencies = Dependencies::new(clock.clone(), id_generator);
 
@@ -38,11 +43,72 @@ async fn returns_health_data() {
     let res = app.get("/v1/health").send().await;
     let body: HealthStatusResponse = res.json().await;
 
+    assert_eq!(body.status, HealthStatus::Ok);
+    assert_eq!(body.name, config.app.name);
+    assert_eq!(body.version, config.app.version);
+    assert!(!body.draining);
+    assert_eq!(body.database.status, HealthStatus::Ok);
+    assert!(body.database.latency_ms >= 0.0);
+    assert_eq!(body.migrations.status, HealthStatus::Ok);
+    assert_eq!(body.migrations.detail, None);
+}
+
+#[tokio::test]
+async fn probes_pass_when_dependencies_are_up() {
+    let id = Ulid::new();
+    let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+    let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+    let dependencies = Dependencies::new(clock.clone(), id_generator);
+
+    let app = get_app(dependencies).await;
+
+    let res = app.get("/livez").send().await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = app.get("/readyz").send().await;
+    let response_status = res.status();
+    let body: ProbeResponse = res.json().await;
+
+    assert_eq!(response_status, StatusCode::OK);
     assert_eq!(
         body,
-        HealthStatusResponse {
-            api: "OK".to_string(),
-            database: "OK".to_string()
+        ProbeResponse {
+            status: HealthStatus::Ok,
+            failing: vec![]
         }
     );
 }
+
+#[tokio::test]
+async fn is_not_ready_while_draining() {
+    let id = Ulid::new();
+    let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+    let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+    let readiness = Readiness::new();
+
+    let dependencies =
+        Dependencies::new(clock.clone(), id_generator).with_readiness(readiness.clone());
+
+    let app = get_app(dependencies).await;
+
+    readiness.drain();
+
+    let res = app.get("/readyz").send().await;
+    let response_status = res.status();
+    let body: ProbeResponse = res.json().await;
+
+    assert_eq!(response_status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body.status, HealthStatus::Nok);
+    assert_eq!(body.failing, vec![String::from("draining")]);
+
+    let res = app.get("/v1/health").send().await;
+    let response_status = res.status();
+    let body: HealthStatusResponse = res.json().await;
+
+    assert_eq!(response_status, StatusCode::SERVICE_UNAVAILABLE);
+    assert!(body.draining);
+
+    let res = app.get("/livez").send().await;
+    assert_eq!(res.status(), StatusCode::OK);
+}
@@ -0,0 +1,192 @@
+use std::time::Duration;
+
+use app::{
+    common::{ApplicationError, HttpConfig},
+    util::with_http_layers,
+};
+use axum::{
+    extract::rejection::JsonRejection,
+    http::{header, StatusCode},
+    routing, Json, Router,
+};
+use axum_test_helper::TestClient;
+use serde_json::Value;
+
+/// Every layer turned off.
+fn config() -> HttpConfig {
+    HttpConfig {
+        cors_enabled: false,
+        cors_allowed_origins: vec![],
+        cors_allowed_methods: vec![String::from("GET"), String::from("POST")],
+        cors_allow_credentials: false,
+        security_headers_enabled: false,
+        hsts_max_age_seconds: 0,
+        body_limit_bytes: 0,
+        request_timeout_seconds: 0,
+        compression_enabled: false,
+    }
+}
+
+fn app(config: &HttpConfig) -> TestClient {
+    let router = Router::new()
+        .route("/", routing::get(|| async { "x".repeat(4096) }))
+        .route(
+            "/echo",
+            routing::post(|body: Result<Json<Value>, JsonRejection>| async {
+                body.map(|Json(body)| body.to_string())
+                    .map_err(ApplicationError::from)
+            }),
+        )
+        .route(
+            "/slow",
+            routing::get(|| async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+
+                "done"
+            }),
+        );
+
+    TestClient::new(with_http_layers(router, config))
+}
+
+#[tokio::test]
+async fn sets_security_headers_when_enabled() {
+    let res = app(&config()).get("/").send().await;
+
+    assert!(res.headers().get(header::X_CONTENT_TYPE_OPTIONS).is_none());
+
+    let res = app(&HttpConfig {
+        security_headers_enabled: true,
+        hsts_max_age_seconds: 600,
+        ..config()
+    })
+    .get("/")
+    .send()
+    .await;
+
+    assert_eq!(
+        res.headers()[header::STRICT_TRANSPORT_SECURITY],
+        "max-age=600; includeSubDomains"
+    );
+    assert_eq!(res.headers()[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+    assert_eq!(res.headers()[header::X_FRAME_OPTIONS], "DENY");
+    assert_eq!(
+        res.headers()[header::CONTENT_SECURITY_POLICY],
+        "default-src 'none'; frame-ancestors 'none'"
+    );
+}
+
+#[tokio::test]
+async fn allows_the_configured_origins() {
+    let res = app(&config())
+        .get("/")
+        .header(header::ORIGIN, "https://todo.example.com")
+        .send()
+        .await;
+    assert!(res
+        .headers()
+        .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        .is_none());
+
+    let config = HttpConfig {
+        cors_enabled: true,
+        cors_allowed_origins: vec![String::from("https://todo.example.com")],
+        cors_allow_credentials: true,
+        ..config()
+    };
+
+    let res = app(&config)
+        .get("/")
+        .header(header::ORIGIN, "https://todo.example.com")
+        .send()
+        .await;
+    assert_eq!(
+        res.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+        "https://todo.example.com"
+    );
+    assert_eq!(
+        res.headers()[header::ACCESS_CONTROL_ALLOW_CREDENTIALS],
+        "true"
+    );
+    assert!(res.headers()[header::ACCESS_CONTROL_EXPOSE_HEADERS]
+        .to_str()
+        .unwrap()
+        .contains("x-request-id"));
+
+    let res = app(&config)
+        .get("/")
+        .header(header::ORIGIN, "https://evil.example.com")
+        .send()
+        .await;
+    assert!(res
+        .headers()
+        .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        .is_none());
+}
+
+#[tokio::test]
+async fn rejects_bodies_over_the_limit() {
+    let body = serde_json::json!({ "subject": "x".repeat(64) });
+
+    let res = app(&config()).post("/echo").json(&body).send().await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = app(&HttpConfig {
+        body_limit_bytes: 32,
+        ..config()
+    })
+    .post("/echo")
+    .json(&body)
+    .send()
+    .await;
+    let status = res.status();
+    let problem: Value = res.json().await;
+
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(problem["code"], "PAYLOAD_TOO_LARGE");
+}
+
+#[tokio::test]
+async fn times_out_slow_requests() {
+    let res = app(&HttpConfig {
+        request_timeout_seconds: 1,
+        ..config()
+    })
+    .get("/slow")
+    .send()
+    .await;
+    let status = res.status();
+    let problem: Value = res.json().await;
+
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(problem["code"], "SERVICE_UNAVAILABLE");
+}
+
+#[tokio::test]
+async fn compresses_responses_the_client_accepts() {
+    let res = app(&config())
+        .get("/")
+        .header(header::ACCEPT_ENCODING, "br")
+        .send()
+        .await;
+    assert!(res.headers().get(header::CONTENT_ENCODING).is_none());
+
+    let config = HttpConfig {
+        compression_enabled: true,
+        ..config()
+    };
+
+    let res = app(&config)
+        .get("/")
+        .header(header::ACCEPT_ENCODING, "br")
+        .send()
+        .await;
+    assert_eq!(res.headers()[header::CONTENT_ENCODING], "br");
+
+    let res = app(&config)
+        .get("/")
+        .header(header::ACCEPT_ENCODING, "gzip")
+        .send()
+        .await;
+    assert_eq!(res.headers()[header::CONTENT_ENCODING], "gzip");
+}
@@ -0,0 +1,48 @@
+use axum::http::StatusCode;
+use ulid::Ulid;
+
+use crate::fixtures::{
+    app::{get_app, Dependencies, DATETIME_STRING},
+    clock::MockClock,
+    id_generator::MockUlidGenerator,
+};
+
+mod fixtures;
+
+#[tokio::test]
+async fn counts_requests_by_route_and_times_queries() {
+    let id = Ulid::new();
+    let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+    let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+    let dependencies = Dependencies::new(clock, id_generator);
+    let app = get_app(dependencies).await;
+
+    for _ in 0..2 {
+        let res = app.get(&format!("/v1/todos/{}", Ulid::new())).send().await;
+
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    app.get("/v1/health").send().await;
+
+    let res = app.get("/metrics").send().await;
+    let response_status = res.status();
+    let content_type = res.headers()["content-type"].to_str().unwrap().to_owned();
+    let body = res.text().await;
+
+    assert_eq!(response_status, StatusCode::OK);
+    assert!(content_type.starts_with("text/plain; version=0.0.4"));
+    assert!(
+        body.contains(r#"http_requests_total{method="GET",path="/v1/todos/:id",status="404"} 2"#)
+    );
+    assert!(body
+        .contains(r#"http_request_duration_seconds_count{method="GET",path="/v1/todos/:id"} 2"#));
+    assert!(body.contains(r#"http_requests_in_flight{method="GET",path="/v1/todos/:id"} 0"#));
+    assert!(body.contains(
+        r#"db_query_duration_seconds_count{operation="get_todo_by_id",repository="todo"} 2"#
+    ));
+    assert!(body
+        .contains(r#"db_query_duration_seconds_count{operation="check",repository="health"} 1"#));
+    assert!(!body.contains(&id.to_string()));
+}
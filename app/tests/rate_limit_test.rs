@@ -0,0 +1,197 @@
+use app::{
+    common::{RateLimitConfig, RateLimitRules},
+    util::{
+        enforce_rate_limit, Decision, InMemoryRateLimitStore, Quota, RateLimitStore, RateLimiter,
+        SystemClock, API_KEY_HEADER,
+    },
+};
+use axum::{
+    http::{header, StatusCode},
+    middleware, routing, Router,
+};
+use axum_test_helper::TestClient;
+use chrono::{DateTime, Duration, Utc};
+
+async fn take(store: &InMemoryRateLimitStore, key: &str, at: DateTime<Utc>) -> Decision {
+    let Ok(decision) = store.take(key, &Quota::new(2, 10), at).await else {
+        panic!("the in-memory store does not fail");
+    };
+
+    decision
+}
+
+fn config(rules: &str, trusted_hops: usize) -> RateLimitConfig {
+    RateLimitConfig {
+        enabled: true,
+        backend: Default::default(),
+        client_ip_header: Some(String::from("x-forwarded-for")),
+        trusted_hops,
+        rules: rules.parse::<RateLimitRules>().unwrap().0,
+    }
+}
+
+fn limited_app(group: &str, rules: &str) -> TestClient {
+    behind_proxies(group, rules, 0)
+}
+
+fn behind_proxies(group: &str, rules: &str, trusted_hops: usize) -> TestClient {
+    let limiter = RateLimiter::new(
+        InMemoryRateLimitStore::new(),
+        SystemClock,
+        &config(rules, trusted_hops),
+    );
+
+    TestClient::new(
+        Router::new()
+            .route("/", routing::get(|| async { "ok" }))
+            .route_layer(middleware::from_fn_with_state(
+                limiter.group(group),
+                enforce_rate_limit,
+            )),
+    )
+}
+
+#[tokio::test]
+async fn refills_buckets_over_time() {
+    let store = InMemoryRateLimitStore::new();
+    let now: DateTime<Utc> = "2023-10-29T10:00:00Z".parse().unwrap();
+
+    assert!(take(&store, "a", now).await.allowed);
+    assert!(take(&store, "a", now).await.allowed);
+
+    let refused = take(&store, "a", now).await;
+    assert!(!refused.allowed);
+    assert_eq!(refused.remaining, 0);
+    assert_eq!(refused.retry_after_seconds, 5);
+    assert_eq!(refused.reset_seconds, 10);
+
+    let later = take(&store, "a", now + Duration::seconds(5)).await;
+    assert!(later.allowed);
+    assert_eq!(later.remaining, 0);
+
+    assert!(take(&store, "b", now).await.allowed);
+}
+
+#[tokio::test]
+async fn throttles_with_a_problem_and_rate_limit_headers() {
+    let app = limited_app("todos", "default.ip=2/60");
+
+    for remaining in ["1", "0"] {
+        let res = app
+            .get("/")
+            .header(
+                "x-forwarded-for",
+                format!("10.0.0.{}, 203.0.113.7", remaining),
+            )
+            .send()
+            .await;
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()["ratelimit-limit"], "2");
+        assert_eq!(res.headers()["ratelimit-remaining"], remaining);
+        assert_eq!(res.headers()["ratelimit-policy"], "2;w=60");
+    }
+
+    let res = app
+        .get("/")
+        .header("x-forwarded-for", "203.0.113.7")
+        .send()
+        .await;
+    let status = res.status();
+    let retry_after = res.headers()[header::RETRY_AFTER]
+        .to_str()
+        .unwrap()
+        .to_owned();
+    let reset = res.headers()["ratelimit-reset"]
+        .to_str()
+        .unwrap()
+        .to_owned();
+    let body: serde_json::Value = res.json().await;
+
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert!((1..=30).contains(&retry_after.parse::<u64>().unwrap()));
+    assert!((31..=60).contains(&reset.parse::<u64>().unwrap()));
+    assert_eq!(body["code"], "TOO_MANY_REQUESTS");
+
+    let res = app
+        .get("/")
+        .header("x-forwarded-for", "198.51.100.1")
+        .send()
+        .await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn limits_api_keys_with_the_rules_of_their_group() {
+    let app = limited_app("webhooks", "default.key=5/60,webhooks.key=1/60");
+
+    let res = app.get("/").header(API_KEY_HEADER, "key-1").send().await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()["ratelimit-limit"], "1");
+
+    let res = app
+        .get("/")
+        .header(header::AUTHORIZATION, "Bearer key-1")
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    let res = app.get("/").header(API_KEY_HEADER, "key-2").send().await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    // Neither an address nor a key, nothing to count the request against.
+    let res = app.get("/").send().await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(res.headers().get("ratelimit-limit").is_none());
+}
+
+#[tokio::test]
+async fn refused_requests_do_not_count_against_other_quotas() {
+    let app = limited_app("todos", "default.ip=3/60,default.key=1/60");
+
+    let res = app
+        .get("/")
+        .header("x-forwarded-for", "203.0.113.7")
+        .header(API_KEY_HEADER, "key-1")
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    for _ in 0..5 {
+        let res = app
+            .get("/")
+            .header("x-forwarded-for", "203.0.113.7")
+            .header(API_KEY_HEADER, "key-1")
+            .send()
+            .await;
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    let res = app
+        .get("/")
+        .header("x-forwarded-for", "203.0.113.7")
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()["ratelimit-remaining"], "1");
+}
+
+#[tokio::test]
+async fn limits_the_address_the_trusted_proxies_saw() {
+    let app = behind_proxies("todos", "default.ip=1/60", 1);
+
+    let res = app
+        .get("/")
+        .header("x-forwarded-for", "192.0.2.1, 203.0.113.7, 10.0.0.1")
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = app
+        .get("/")
+        .header("x-forwarded-for", "192.0.2.2, 203.0.113.7, 10.0.0.2")
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+}
@@ -0,0 +1,185 @@
+use std::{
+    io,
+    sync::{Arc, Mutex},
+};
+
+use app::{
+    common::{ApplicationError, Config, Environment},
+    docs::v1::reminders::Reminder,
+    resource::v1::reminders::{LoggingNotifier, Notifier},
+    util::SystemTelemetry,
+};
+use axum::{http::StatusCode, routing, Router};
+use axum_test_helper::TestClient;
+use tracing::{info, info_span, instrument, subscriber::DefaultGuard, Instrument};
+use tracing_subscriber::layer::SubscriberExt;
+
+#[derive(Clone, Default)]
+struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl io::Write for Buffer {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(bytes);
+
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Keeps the log lines of the current thread, formatted as the app would
+/// for `config`, until dropped.
+struct CapturedLogs {
+    buffer: Buffer,
+    _guard: DefaultGuard,
+}
+
+impl CapturedLogs {
+    fn start(config: &Config) -> Self {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::registry()
+            .with(SystemTelemetry::logs(config, move || writer.clone()));
+
+        Self {
+            buffer,
+            _guard: tracing::subscriber::set_default(subscriber),
+        }
+    }
+
+    fn text(&self) -> String {
+        String::from_utf8(self.buffer.0.lock().unwrap().clone()).unwrap()
+    }
+
+    fn lines(&self) -> Vec<String> {
+        self.text().lines().map(str::to_owned).collect()
+    }
+}
+
+#[tokio::test]
+async fn masks_configured_fields_in_json_logs() {
+    let mut config = Config::new();
+    config.env = Environment::Production;
+    config.redacted_fields = vec![String::from("description"), String::from("token")];
+    let logs = CapturedLogs::start(&config);
+
+    info_span!(
+        "todo",
+        todo.description = "Call the bank about 4242",
+        todo.count = 2
+    )
+    .in_scope(|| info!(api_token = "s3cr3t", attempts = 3, "Imported todos"));
+
+    let lines = logs.lines();
+    let line: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+
+    assert_eq!(lines.len(), 1);
+    assert_eq!(line["level"], "INFO");
+    assert_eq!(line["message"], "Imported todos");
+    assert_eq!(line["api_token"], "[REDACTED]");
+    assert_eq!(line["attempts"], 3);
+    assert_eq!(line["span"]["name"], "todo");
+    assert_eq!(line["span"]["todo.description"], "[REDACTED]");
+    assert_eq!(line["span"]["todo.count"], 2);
+    assert!(!logs.text().contains("4242"));
+    assert!(!logs.text().contains("s3cr3t"));
+}
+
+#[instrument(name = "todo_service.get_todos", skip_all, fields(todo.count = 2))]
+async fn get_todos() {
+    info!("Listing todos");
+}
+
+#[tokio::test]
+async fn carries_the_fields_of_enclosing_spans_in_json_logs() {
+    let mut config = Config::new();
+    config.env = Environment::Production;
+    let logs = CapturedLogs::start(&config);
+
+    get_todos()
+        .instrument(info_span!(
+            "request",
+            request_id = "7d3c4a1e",
+            todo.count = 1
+        ))
+        .await;
+
+    let lines = logs.lines();
+    let line: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+
+    assert_eq!(line["message"], "Listing todos");
+    assert_eq!(line["span"]["name"], "todo_service.get_todos");
+    assert_eq!(line["span"]["request_id"], "7d3c4a1e");
+    assert_eq!(line["span"]["todo.count"], 2);
+}
+
+#[tokio::test]
+async fn masks_configured_fields_in_compact_logs() {
+    let mut config = Config::new();
+    config.env = Environment::Development;
+    config.redacted_fields = vec![String::from("email")];
+    let logs = CapturedLogs::start(&config);
+
+    info!(user.email = "jane@example.com", attempts = 3, "Signed in");
+
+    let text = logs.text();
+
+    assert!(text.contains("Signed in"));
+    assert!(text.contains("\"[REDACTED]\""));
+    assert!(!text.contains("jane@example.com"));
+}
+
+#[tokio::test]
+async fn masks_values_logged_by_the_app() {
+    let mut config = Config::new();
+    config.env = Environment::Production;
+    config.redacted_fields = vec![String::from("subject")];
+    let logs = CapturedLogs::start(&config);
+    let due_date = "2023-11-04T18:00:00Z".parse().unwrap();
+    let reminder = Reminder {
+        id: String::from("x8b3kq0m2v9d7c1f4n6p"),
+        todo_id: "01HDS25AGAJ88WNXE5KZ3CN8KG".parse().unwrap(),
+        subject: String::from("Call the bank about 4242"),
+        due_date,
+        remind_at: due_date,
+    };
+
+    LoggingNotifier.notify(&reminder).await.unwrap();
+
+    let line: serde_json::Value = serde_json::from_str(&logs.lines()[0]).unwrap();
+
+    assert_eq!(line["message"], "Reminder is due");
+    assert_eq!(line["subject"], "[REDACTED]");
+    assert_eq!(line["reminder_id"], "x8b3kq0m2v9d7c1f4n6p");
+    assert!(!logs.text().contains("4242"));
+}
+
+#[tokio::test]
+async fn server_errors_answer_only_an_error_id() {
+    let config = Config::new();
+    let logs = CapturedLogs::start(&config);
+    let router = Router::new().route(
+        "/fail",
+        routing::get(|| async {
+            Err::<(), _>(ApplicationError::ServerError(vec![String::from(
+                "There was a problem with the database: SELECT * FROM todo",
+            )]))
+        }),
+    );
+    let client = TestClient::new(router);
+
+    let res = client.get("/fail").send().await;
+    let response_status = res.status();
+    let response_body: serde_json::Value = res.json().await;
+    let error_id = response_body["errorId"].as_str().unwrap().to_owned();
+
+    assert_eq!(response_status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(response_body["code"], "SERVER_ERROR");
+    assert!(!response_body.to_string().contains("SELECT"));
+    assert!(logs
+        .lines()
+        .iter()
+        .any(|line| line.contains(&error_id) && line.contains("SELECT * FROM todo")));
+}
@@ -0,0 +1,179 @@
+use app::docs::v1::todos::TodoResponse;
+use axum::http::StatusCode;
+use axum_test_helper::TestClient;
+use serde_json::json;
+use ulid::Ulid;
+
+use crate::fixtures::{
+    app::{get_app, Dependencies, DATETIME_STRING},
+    clock::MockClock,
+    id_generator::MockUlidGenerator,
+    notifier::{get_scheduler, RecordingNotifier},
+};
+
+mod fixtures;
+
+// A scheduler claims every due reminder in the database, so the test that
+// expects a delivery uses the latest dates and no other test can take its
+// reminder first.
+
+#[tokio::test]
+async fn stores_reminder_offsets_on_the_todo() {
+    let id = Ulid::new();
+    let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+    let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+    let dependencies = Dependencies::new(clock.clone(), id_generator);
+    let app = get_app(dependencies).await;
+
+    let res = create_todo_due(
+        &app,
+        &id,
+        "2030-01-01T18:00:00Z",
+        json!(["1d", "90m", "60m"]),
+    )
+    .await;
+    let response_status = res.status();
+    let response_body: TodoResponse = res.json().await;
+
+    assert_eq!(response_status, StatusCode::OK);
+    assert_eq!(response_body.reminders, vec!["1h", "90m", "1d"]);
+}
+
+#[tokio::test]
+async fn rejects_unknown_reminder_offsets() {
+    let id = Ulid::new();
+    let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+    let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+    let dependencies = Dependencies::new(clock.clone(), id_generator);
+    let app = get_app(dependencies).await;
+
+    let res = create_todo_due(&app, &id, "2030-01-01T18:00:00Z", json!(["soon"])).await;
+
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn delivers_a_due_reminder_exactly_once() {
+    let id = Ulid::new();
+    let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+    let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+    let dependencies = Dependencies::new(clock.clone(), id_generator);
+    let app = get_app(dependencies).await;
+
+    let res = create_todo_due(&app, &id, "2031-06-01T18:00:00Z", json!(["1h"])).await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let notifier = RecordingNotifier::default();
+
+    let early = MockClock::with_frozen_time("2031-06-01T16:59:00Z".into());
+    get_scheduler(early, notifier.clone())
+        .await
+        .tick()
+        .await
+        .unwrap();
+
+    assert!(notifier.reminders_for(&id).is_empty());
+
+    let due = MockClock::with_frozen_time("2031-06-01T17:00:30Z".into());
+    let scheduler = get_scheduler(due.clone(), notifier.clone()).await;
+    scheduler.tick().await.unwrap();
+    scheduler.tick().await.unwrap();
+
+    let reminders = notifier.reminders_for(&id);
+
+    assert_eq!(reminders.len(), 1);
+    assert_eq!(reminders[0].subject, format!("Call {}", id));
+    assert_eq!(
+        reminders[0].remind_at.to_rfc3339(),
+        "2031-06-01T17:00:00+00:00"
+    );
+
+    // A restarted scheduler must not send it again.
+    get_scheduler(due, notifier.clone())
+        .await
+        .tick()
+        .await
+        .unwrap();
+
+    assert_eq!(notifier.reminders_for(&id).len(), 1);
+}
+
+#[tokio::test]
+async fn skips_reminders_of_completed_todos() {
+    let id = Ulid::new();
+    let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+    let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+    let dependencies = Dependencies::new(clock.clone(), id_generator);
+    let app = get_app(dependencies).await;
+
+    create_todo_due(&app, &id, "2030-03-01T18:00:00Z", json!(["1h"])).await;
+
+    let res = app
+        .patch(&format!("/v1/todos/{}", id))
+        .json(&json!({ "isDone": true }))
+        .send()
+        .await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let notifier = RecordingNotifier::default();
+    let due = MockClock::with_frozen_time("2030-03-01T17:30:00Z".into());
+    get_scheduler(due, notifier.clone())
+        .await
+        .tick()
+        .await
+        .unwrap();
+
+    assert!(notifier.reminders_for(&id).is_empty());
+}
+
+#[tokio::test]
+async fn reschedules_reminders_when_the_due_date_moves() {
+    let id = Ulid::new();
+    let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+    let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+    let dependencies = Dependencies::new(clock.clone(), id_generator);
+    let app = get_app(dependencies).await;
+
+    create_todo_due(&app, &id, "2030-04-01T18:00:00Z", json!(["1h"])).await;
+
+    let res = app
+        .patch(&format!("/v1/todos/{}", id))
+        .json(&json!({ "dueDate": "2030-04-08T18:00:00Z" }))
+        .send()
+        .await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let notifier = RecordingNotifier::default();
+    let old_reminder_time = MockClock::with_frozen_time("2030-04-01T17:30:00Z".into());
+    get_scheduler(old_reminder_time, notifier.clone())
+        .await
+        .tick()
+        .await
+        .unwrap();
+
+    assert!(notifier.reminders_for(&id).is_empty());
+}
+
+async fn create_todo_due(
+    app: &TestClient,
+    id: &Ulid,
+    due_date: &str,
+    reminders: serde_json::Value,
+) -> axum_test_helper::TestResponse {
+    let payload = json!({
+      "description": "Dummy description",
+      "dueDate": due_date,
+      "subject": format!("Call {}", id),
+      "reminders": reminders
+    });
+
+    app.post("/v1/todos").json(&payload).send().await
+}
@@ -0,0 +1,90 @@
+use app::util::REQUEST_ID_HEADER;
+use axum::http::StatusCode;
+use ulid::Ulid;
+
+use crate::fixtures::{
+    app::{get_app, Dependencies, DATETIME_STRING},
+    clock::MockClock,
+    id_generator::MockUlidGenerator,
+};
+
+mod fixtures;
+
+#[tokio::test]
+async fn tags_problems_with_the_request_id() {
+    let id = Ulid::new();
+    let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+    let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+    let dependencies = Dependencies::new(clock, id_generator);
+    let app = get_app(dependencies).await;
+
+    let res = app
+        .get(&format!("/v1/todos/{}", Ulid::new()))
+        .header(REQUEST_ID_HEADER, "checkout-42")
+        .send()
+        .await;
+    let response_status = res.status();
+    let request_id = res.headers()[REQUEST_ID_HEADER]
+        .to_str()
+        .unwrap()
+        .to_owned();
+    let response_body: serde_json::Value = res.json().await;
+
+    assert_eq!(response_status, StatusCode::NOT_FOUND);
+    assert_eq!(request_id, "checkout-42");
+    assert_eq!(response_body["code"], "RESOURCE_NOT_FOUND");
+    assert_eq!(response_body["requestId"], "checkout-42");
+}
+
+#[tokio::test]
+async fn makes_up_a_request_id_when_missing_or_unusable() {
+    let id = Ulid::new();
+    let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+    let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+    let dependencies = Dependencies::new(clock, id_generator);
+    let app = get_app(dependencies).await;
+
+    let res = app.get("/v1/health").send().await;
+    let request_id = res.headers()[REQUEST_ID_HEADER]
+        .to_str()
+        .unwrap()
+        .to_owned();
+
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(Ulid::from_string(&request_id).is_ok());
+
+    let res = app
+        .get("/v1/todos/not-a-ulid")
+        .header(REQUEST_ID_HEADER, "x".repeat(129))
+        .send()
+        .await;
+    let request_id = res.headers()[REQUEST_ID_HEADER]
+        .to_str()
+        .unwrap()
+        .to_owned();
+    let response_body: serde_json::Value = res.json().await;
+
+    assert!(Ulid::from_string(&request_id).is_ok());
+    assert_eq!(response_body["requestId"], request_id);
+}
+
+#[tokio::test]
+async fn tags_the_redirect_of_unknown_paths() {
+    let id = Ulid::new();
+    let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+    let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+    let dependencies = Dependencies::new(clock, id_generator);
+    let app = get_app(dependencies).await;
+
+    let res = app
+        .get("/nowhere")
+        .header(REQUEST_ID_HEADER, "checkout-42")
+        .send()
+        .await;
+
+    assert_eq!(res.status(), StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(res.headers()[REQUEST_ID_HEADER], "checkout-42");
+}
@@ -0,0 +1,90 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use app::{
+    common::DatabaseDriver,
+    resource::v1::webhooks::{WebhookDispatcher, WebhookRepository},
+    util::{Shutdown, SystemClock},
+};
+use surrealdb::Surreal;
+
+/// Sets the flag when the task holding it is dropped, finished or aborted.
+struct DropFlag(Arc<AtomicBool>);
+
+impl Drop for DropFlag {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+#[tokio::test]
+async fn stops_background_tasks_between_ticks() {
+    let shutdown = Shutdown::new();
+    // Never connected, so every tick fails fast and the task keeps going.
+    let database_driver = DatabaseDriver {
+        client: Surreal::init(),
+    };
+
+    shutdown.track(
+        WebhookDispatcher::new(WebhookRepository::new(database_driver), SystemClock::new())
+            .with_interval(Duration::from_millis(10))
+            .with_shutdown(shutdown.clone())
+            .spawn(),
+    );
+    tokio::time::sleep(Duration::from_millis(30)).await;
+
+    shutdown.trigger();
+    let waited = tokio::time::timeout(
+        Duration::from_secs(1),
+        shutdown.wait_for_tasks(Duration::from_secs(10)),
+    )
+    .await;
+
+    assert!(shutdown.is_triggered());
+    assert!(waited.is_ok());
+}
+
+#[tokio::test]
+async fn aborts_tasks_still_running_after_the_timeout() {
+    let shutdown = Shutdown::new();
+    let dropped = Arc::new(AtomicBool::new(false));
+    let flag = DropFlag(dropped.clone());
+
+    shutdown.track(tokio::spawn(async move {
+        let _flag = flag;
+
+        std::future::pending::<()>().await
+    }));
+
+    shutdown.trigger();
+    shutdown.wait_for_tasks(Duration::from_millis(20)).await;
+    tokio::task::yield_now().await;
+
+    assert!(dropped.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn triggered_resolves_for_every_clone() {
+    let shutdown = Shutdown::new();
+    let waiting = tokio::spawn({
+        let shutdown = shutdown.clone();
+
+        async move { shutdown.triggered().await }
+    });
+
+    shutdown.trigger();
+
+    assert!(tokio::time::timeout(Duration::from_secs(1), waiting)
+        .await
+        .is_ok());
+    assert!(
+        tokio::time::timeout(Duration::from_secs(1), shutdown.triggered())
+            .await
+            .is_ok()
+    );
+}
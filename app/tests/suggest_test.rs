@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use app::{
+    common::RepositoryError,
+    docs::v1::{
+        events::OutboxModel,
+        todos::{
+            Pagination, Todo, TodoFilter, TodoModel, TodoModelUpdate, TodoSearchModel,
+            TodoStatusCountModel, TodoSuggestionModel,
+        },
+    },
+    resource::v1::todos::{TodoRepository, TodoService, SUGGEST_LATENCY_BUDGET},
+    util::{SystemClock, UlidGenerator},
+};
+use axum::async_trait;
+use chrono::{DateTime, Utc};
+use surrealdb::sql::Thing;
+use ulid::Ulid;
+
+type RepositoryResult<T> = Result<T, RepositoryError>;
+
+static SUGGESTED_ID: &str = "01HGW2N7EHJVJYJ2TJEJDS4QYA";
+
+/// Takes `delay` to suggest a single todo, and is never asked for anything
+/// else.
+struct SlowTodoRepository {
+    delay: Duration,
+}
+
+#[async_trait]
+impl TodoRepository for SlowTodoRepository {
+    async fn create_todo(&self, _: Todo) -> RepositoryResult<OutboxModel> {
+        unreachable!("not called by suggest")
+    }
+
+    async fn create_todos(&self, _: Vec<Todo>) -> RepositoryResult<Vec<OutboxModel>> {
+        unreachable!("not called by suggest")
+    }
+
+    async fn get_todos(
+        &self,
+        _: &TodoFilter,
+        _: Option<Pagination>,
+    ) -> RepositoryResult<Vec<TodoModel>> {
+        unreachable!("not called by suggest")
+    }
+
+    async fn get_todo_by_id(&self, _: &Ulid) -> RepositoryResult<TodoModel> {
+        unreachable!("not called by suggest")
+    }
+
+    async fn update_todo(
+        &self,
+        _: &Ulid,
+        _: TodoModelUpdate,
+        _: DateTime<Utc>,
+    ) -> RepositoryResult<OutboxModel> {
+        unreachable!("not called by suggest")
+    }
+
+    async fn delete_todo(&self, _: &Ulid, _: DateTime<Utc>) -> RepositoryResult<OutboxModel> {
+        unreachable!("not called by suggest")
+    }
+
+    async fn search_todo(
+        &self,
+        _: &str,
+        _: &TodoFilter,
+        _: Pagination,
+    ) -> RepositoryResult<Vec<TodoSearchModel>> {
+        unreachable!("not called by suggest")
+    }
+
+    async fn count_search_by_status(
+        &self,
+        _: &str,
+        _: &TodoFilter,
+    ) -> RepositoryResult<Vec<TodoStatusCountModel>> {
+        unreachable!("not called by suggest")
+    }
+
+    async fn suggest_todos(&self, _: &str, _: u32) -> RepositoryResult<Vec<TodoSuggestionModel>> {
+        tokio::time::sleep(self.delay).await;
+
+        Ok(vec![TodoSuggestionModel {
+            id: Thing::from(("todo", SUGGESTED_ID)),
+            subject: String::from("Buy groceries"),
+            score: 1.0,
+        }])
+    }
+}
+
+fn service(delay: Duration) -> TodoService<SlowTodoRepository, SystemClock, UlidGenerator> {
+    TodoService::new(
+        SlowTodoRepository { delay },
+        SystemClock::new(),
+        UlidGenerator::new(),
+        chrono_tz::UTC,
+    )
+}
+
+#[tokio::test(start_paused = true)]
+async fn answers_within_the_latency_budget() {
+    let service = service(SUGGEST_LATENCY_BUDGET - Duration::from_millis(1));
+
+    let suggestions = service.suggest_todos("groc", 8).await.unwrap();
+
+    assert_eq!(suggestions.len(), 1);
+    assert_eq!(suggestions[0].id.to_string(), SUGGESTED_ID);
+    assert_eq!(suggestions[0].subject, "Buy groceries");
+}
+
+#[tokio::test(start_paused = true)]
+async fn gives_up_past_the_latency_budget() {
+    let service = service(SUGGEST_LATENCY_BUDGET + Duration::from_millis(1));
+
+    let suggestions = service.suggest_todos("groc", 8).await.unwrap();
+
+    assert!(suggestions.is_empty());
+}
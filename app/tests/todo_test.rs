@@ -1,6 +1,10 @@
-use app::{docs::v1::todos::TodoResponse, util::Clock};
+use app::{
+    docs::v1::todos::{SearchTodosResponse, TodoResponse, TodoSuggestionResponse},
+    util::Clock,
+};
 use axum::http::StatusCode;
 use axum_test_helper::TestClient;
+use chrono::{DateTime, Utc};
 use serde_json::json;
 use ulid::Ulid;
 
@@ -12,6 +16,19 @@ use crate::fixtures::{
 
 mod fixtures;
 
+/// A letters-only word derived from the id, so that a search for it only
+/// matches the Todo created by the test.
+fn unique_word(id: &Ulid) -> String {
+    id.to_string()
+        .to_lowercase()
+        .chars()
+        .map(|c| match c.to_digit(10) {
+            Some(digit) => (b'a' + digit as u8) as char,
+            None => c,
+        })
+        .collect()
+}
+
 mod create_todo {
     use super::*;
 
@@ -67,6 +84,184 @@ mod create_todo {
         assert_eq!(response_body.due_date, clock.now());
         assert_eq!(response_body.created_at, clock.now());
     }
+
+    #[tokio::test]
+    async fn resolves_natural_due_dates_against_the_clock() {
+        let id = Ulid::new();
+        let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+        let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+        let dependencies = Dependencies::new(clock.clone(), id_generator);
+        let app = get_app(dependencies).await;
+
+        let payload = json!({
+          "description": "Buy groceries from the supermarket for the weekend.",
+          "dueDate": "tomorrow 5pm",
+          "subject": "Buy groceries"
+        });
+
+        let res = app.post("/v1/todos").json(&payload).send().await;
+        let response_status = res.status();
+        let response_body: TodoResponse = res.json().await;
+
+        assert_eq!(response_status, StatusCode::OK);
+        assert_eq!(
+            response_body.due_date,
+            "2023-11-05T17:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn returns_problem_for_ambiguous_due_date() {
+        let id = Ulid::new();
+        let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+        let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+        let dependencies = Dependencies::new(clock.clone(), id_generator);
+        let app = get_app(dependencies).await;
+
+        let payload = json!({
+          "description": "Buy groceries from the supermarket for the weekend.",
+          "dueDate": "saturday at 5",
+          "subject": "Buy groceries"
+        });
+
+        let res = app.post("/v1/todos").json(&payload).send().await;
+        let response_status = res.status();
+        let response_body: serde_json::Value = res.json().await;
+
+        assert_eq!(response_status, StatusCode::BAD_REQUEST);
+        assert_eq!(response_body["code"], "VALIDATION_ERROR");
+        assert!(response_body["issues"][0]
+            .as_str()
+            .unwrap()
+            .starts_with("dueDate: `saturday at 5`"));
+    }
+
+    #[tokio::test]
+    async fn reads_wall_clock_times_in_the_todo_timezone_across_dst() {
+        let id = Ulid::new();
+        let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+        let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+        let dependencies = Dependencies::new(clock.clone(), id_generator);
+        let app = get_app(dependencies).await;
+
+        // New York leaves daylight saving time overnight.
+        let payload = json!({
+          "description": "Dummy description",
+          "dueDate": "tomorrow 9am",
+          "subject": "Dummy subject",
+          "timezone": "America/New_York"
+        });
+
+        let res = app.post("/v1/todos").json(&payload).send().await;
+        let response_status = res.status();
+        let response_body: TodoResponse = res.json().await;
+
+        assert_eq!(response_status, StatusCode::OK);
+        assert_eq!(
+            response_body.due_date.to_rfc3339(),
+            "2023-11-05T09:00:00-05:00"
+        );
+        assert_eq!(response_body.timezone.as_deref(), Some("America/New_York"));
+    }
+
+    #[tokio::test]
+    async fn keeps_all_day_todos_on_their_local_day() {
+        let id = Ulid::new();
+        let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+        let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+        let dependencies = Dependencies::new(clock.clone(), id_generator);
+        let app = get_app(dependencies).await;
+
+        let payload = json!({
+          "description": "Dummy description",
+          "dueDate": "2023-11-05",
+          "subject": "Dummy subject",
+          "timezone": "America/New_York",
+          "allDay": true
+        });
+
+        let res = app
+            .post("/v1/todos")
+            .header("time-zone", "Asia/Tokyo")
+            .json(&payload)
+            .send()
+            .await;
+        let response_status = res.status();
+        let response_body: TodoResponse = res.json().await;
+
+        assert_eq!(response_status, StatusCode::OK);
+        assert!(response_body.all_day);
+        assert_eq!(
+            response_body.due_date.to_rfc3339(),
+            "2023-11-05T23:59:59-05:00"
+        );
+    }
+
+    #[tokio::test]
+    async fn renders_due_date_in_the_requested_timezone() {
+        let id = Ulid::new();
+        let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+        let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+        let dependencies = Dependencies::new(clock.clone(), id_generator);
+        let app = get_app(dependencies).await;
+
+        let payload = json!({
+          "description": "Dummy description",
+          "dueDate": DATETIME_STRING,
+          "subject": "Dummy subject"
+        });
+
+        let res = app
+            .post("/v1/todos")
+            .header("time-zone", "Europe/Berlin")
+            .json(&payload)
+            .send()
+            .await;
+        let response_status = res.status();
+        let response_body: TodoResponse = res.json().await;
+
+        assert_eq!(response_status, StatusCode::OK);
+        assert_eq!(response_body.due_date, clock.now());
+        assert_eq!(
+            response_body.due_date.to_rfc3339(),
+            "2023-11-04T16:32:34.205052+01:00"
+        );
+        assert_eq!(response_body.timezone, None);
+    }
+
+    #[tokio::test]
+    async fn returns_problem_for_unknown_timezone() {
+        let id = Ulid::new();
+        let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+        let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+        let dependencies = Dependencies::new(clock.clone(), id_generator);
+        let app = get_app(dependencies).await;
+
+        let payload = json!({
+          "description": "Dummy description",
+          "dueDate": DATETIME_STRING,
+          "subject": "Dummy subject",
+          "timezone": "Mars/Olympus_Mons"
+        });
+
+        let res = app.post("/v1/todos").json(&payload).send().await;
+
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let res = app
+            .get("/v1/todos")
+            .header("time-zone", "Mars/Olympus_Mons")
+            .send()
+            .await;
+
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
 }
 
 mod get_todo_by_id {
@@ -149,6 +344,143 @@ mod get_todos {
         assert_eq!(response_status, StatusCode::OK);
         assert!(!response_body.is_empty());
     }
+
+    #[tokio::test]
+    async fn filters_todos_by_done_state() {
+        let id = Ulid::new().to_string();
+        let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+        let id_generator = MockUlidGenerator::with_fixed_value(&id);
+
+        let dependencies = Dependencies::new(clock.clone(), id_generator);
+        let app = get_app(dependencies).await;
+
+        create_one_todo(&app).await;
+
+        let res = app.get("/v1/todos?isDone=true&perPage=100").send().await;
+        let response_status = res.status();
+        let response_body: Vec<TodoResponse> = res.json().await;
+
+        assert_eq!(response_status, StatusCode::OK);
+        assert!(response_body.iter().all(|todo| todo.is_done));
+        assert!(response_body.iter().all(|todo| todo.id != id));
+    }
+
+    #[tokio::test]
+    async fn returns_bad_request_for_bad_pagination() {
+        let id = Ulid::new().to_string();
+        let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+        let id_generator = MockUlidGenerator::with_fixed_value(&id);
+
+        let dependencies = Dependencies::new(clock.clone(), id_generator);
+        let app = get_app(dependencies).await;
+
+        let res = app.get("/v1/todos?perPage=0").send().await;
+
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let res = app.get("/v1/todos?page=4294967295&perPage=2").send().await;
+
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn filters_todos_by_query() {
+        let id = Ulid::new();
+        let word = unique_word(&id);
+        let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+        let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+        let dependencies = Dependencies::new(clock.clone(), id_generator);
+        let app = get_app(dependencies).await;
+
+        let payload = json!({
+          "description": "Dummy description",
+          "dueDate": DATETIME_STRING,
+          "subject": format!("Plan {}", word)
+        });
+        app.post("/v1/todos").json(&payload).send().await;
+
+        let res = app
+            .get(&format!(
+                "/v1/todos?query=is:open%20due:%3C%3D2023-11-04%20%22plan%20{}%22",
+                word
+            ))
+            .send()
+            .await;
+        let response_status = res.status();
+        let response_body: Vec<TodoResponse> = res.json().await;
+
+        assert_eq!(response_status, StatusCode::OK);
+        assert_eq!(response_body.len(), 1);
+        assert_eq!(response_body[0].id, id.to_string());
+
+        let res = app
+            .get(&format!("/v1/todos?query=is:done%20{}", word))
+            .send()
+            .await;
+        let response_body: Vec<TodoResponse> = res.json().await;
+
+        assert!(response_body.is_empty());
+    }
+
+    #[tokio::test]
+    async fn returns_problem_pointing_at_bad_query_token() {
+        let id = Ulid::new().to_string();
+        let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+        let id_generator = MockUlidGenerator::with_fixed_value(&id);
+
+        let dependencies = Dependencies::new(clock.clone(), id_generator);
+        let app = get_app(dependencies).await;
+
+        let res = app
+            .get("/v1/todos?query=is:open%20due:%3Csoon")
+            .send()
+            .await;
+        let response_status = res.status();
+        let response_body: serde_json::Value = res.json().await;
+
+        assert_eq!(response_status, StatusCode::BAD_REQUEST);
+        assert_eq!(response_body["code"], "VALIDATION_ERROR");
+        assert!(response_body["issues"][0]
+            .as_str()
+            .unwrap()
+            .contains("at column 14 near `soon`"));
+    }
+
+    #[tokio::test]
+    async fn reads_today_in_the_requested_timezone() {
+        let id = Ulid::new();
+        let word = unique_word(&id);
+        let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+        let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+        let dependencies = Dependencies::new(clock.clone(), id_generator);
+        let app = get_app(dependencies).await;
+
+        let payload = json!({
+          "description": "Dummy description",
+          "dueDate": "2023-11-04T12:00:00Z",
+          "subject": format!("Plan {}", word)
+        });
+        app.post("/v1/todos").json(&payload).send().await;
+
+        let url = format!("/v1/todos?query=due:today%20{}", word);
+
+        let res = app.get(&url).send().await;
+        let response_body: Vec<TodoResponse> = res.json().await;
+
+        assert_eq!(response_body.len(), 1);
+
+        // It is already the 5th in Auckland, where the todo was due yesterday.
+        let res = app
+            .get(&url)
+            .header("time-zone", "Pacific/Auckland")
+            .send()
+            .await;
+        let response_body: Vec<TodoResponse> = res.json().await;
+
+        assert!(response_body.is_empty());
+    }
 }
 
 mod update_todo_by_id {
@@ -241,13 +573,9 @@ mod update_todo_by_id {
         assert_eq!(response_body.due_date, clock.now());
         assert_eq!(response_body.created_at, clock.now());
     }
-}
-
-mod delete_todo_by_id {
-    use super::*;
 
     #[tokio::test]
-    async fn returns_not_found_when_not_found_by_id() {
+    async fn accepts_natural_due_dates() {
         let id = Ulid::new();
         let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
         let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
@@ -258,15 +586,23 @@ mod delete_todo_by_id {
         create_one_todo(&app).await;
 
         let res = app
-            .delete(&format!("/v1/todos/{}", Ulid::new()))
+            .patch(&format!("/v1/todos/{}", id))
+            .json(&json!({ "dueDate": "next friday 9am" }))
             .send()
             .await;
 
-        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        let response_status = res.status();
+        let response_body: TodoResponse = res.json().await;
+
+        assert_eq!(response_status, StatusCode::OK);
+        assert_eq!(
+            response_body.due_date,
+            "2023-11-10T09:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
     }
 
     #[tokio::test]
-    async fn successfully_deletes_todo() {
+    async fn keeps_wall_clock_time_when_moving_timezone() {
         let id = Ulid::new();
         let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
         let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
@@ -274,10 +610,18 @@ mod delete_todo_by_id {
         let dependencies = Dependencies::new(clock.clone(), id_generator);
 
         let app = get_app(dependencies).await;
-        create_one_todo(&app).await;
+
+        let payload = json!({
+          "description": "Dummy description",
+          "dueDate": "2023-11-06T09:00:00-05:00",
+          "subject": "Dummy subject",
+          "timezone": "America/New_York"
+        });
+        app.post("/v1/todos").json(&payload).send().await;
 
         let res = app
-            .delete(&format!("/v1/todos/{}", id.clone()))
+            .patch(&format!("/v1/todos/{}", id))
+            .json(&json!({ "timezone": "Europe/Berlin" }))
             .send()
             .await;
 
@@ -285,23 +629,70 @@ mod delete_todo_by_id {
         let response_body: TodoResponse = res.json().await;
 
         assert_eq!(response_status, StatusCode::OK);
-
-        assert_eq!(response_body.id, id.to_string());
-        assert_eq!(response_body.subject, "Dummy subject".to_string());
-        assert_eq!(response_body.description, "Dummy description".to_string());
-        assert!(!response_body.is_done);
-        assert_eq!(response_body.due_date, clock.now());
-        assert_eq!(response_body.created_at, clock.now());
+        assert_eq!(
+            response_body.due_date.to_rfc3339(),
+            "2023-11-06T09:00:00+01:00"
+        );
     }
 }
 
-mod search_todos {
-    use std::vec;
-
+mod delete_todo_by_id {
     use super::*;
 
     #[tokio::test]
-    async fn returns_empty_array_if_none_found() {
+    async fn returns_not_found_when_not_found_by_id() {
+        let id = Ulid::new();
+        let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+        let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+        let dependencies = Dependencies::new(clock.clone(), id_generator);
+
+        let app = get_app(dependencies).await;
+        create_one_todo(&app).await;
+
+        let res = app
+            .delete(&format!("/v1/todos/{}", Ulid::new()))
+            .send()
+            .await;
+
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn successfully_deletes_todo() {
+        let id = Ulid::new();
+        let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+        let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+        let dependencies = Dependencies::new(clock.clone(), id_generator);
+
+        let app = get_app(dependencies).await;
+        create_one_todo(&app).await;
+
+        let res = app
+            .delete(&format!("/v1/todos/{}", id.clone()))
+            .send()
+            .await;
+
+        let response_status = res.status();
+        let response_body: TodoResponse = res.json().await;
+
+        assert_eq!(response_status, StatusCode::OK);
+
+        assert_eq!(response_body.id, id.to_string());
+        assert_eq!(response_body.subject, "Dummy subject".to_string());
+        assert_eq!(response_body.description, "Dummy description".to_string());
+        assert!(!response_body.is_done);
+        assert_eq!(response_body.due_date, clock.now());
+        assert_eq!(response_body.created_at, clock.now());
+    }
+}
+
+mod search_todos {
+    use super::*;
+
+    #[tokio::test]
+    async fn returns_empty_array_if_none_found() {
         let id = Ulid::new();
         let search_term = "qwerty";
         let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
@@ -317,13 +708,12 @@ mod search_todos {
             .send()
             .await;
 
-        let expected: Vec<TodoResponse> = vec![];
-
         let response_status = res.status();
-        let response_body: Vec<TodoResponse> = res.json().await;
+        let response_body: SearchTodosResponse = res.json().await;
 
         assert_eq!(response_status, StatusCode::OK);
-        assert_eq!(response_body, expected);
+        assert!(response_body.results.is_empty());
+        assert_eq!(response_body.total, 0);
     }
 
     #[tokio::test]
@@ -344,10 +734,498 @@ mod search_todos {
             .await;
 
         let response_status = res.status();
-        let response_body: Vec<TodoResponse> = res.json().await;
+        let response_body: SearchTodosResponse = res.json().await;
 
         assert_eq!(response_status, StatusCode::OK);
-        assert!(!response_body.is_empty());
+        assert!(!response_body.results.is_empty());
+        assert!(response_body.total >= response_body.results.len() as u64);
+        assert!(response_body
+            .results
+            .windows(2)
+            .all(|pair| pair[0].score >= pair[1].score));
+    }
+
+    #[tokio::test]
+    async fn returns_score_and_highlights() {
+        let id = Ulid::new();
+        let search_term = unique_word(&id);
+        let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+        let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+        let dependencies = Dependencies::new(clock.clone(), id_generator);
+
+        let app = get_app(dependencies).await;
+        create_todo_about(&app, &search_term).await;
+
+        let res = app
+            .get(&format!("/v1/todos/search?q={}", search_term))
+            .send()
+            .await;
+
+        let response_status = res.status();
+        let response_body: SearchTodosResponse = res.json().await;
+        let result = &response_body.results[0];
+
+        assert_eq!(response_status, StatusCode::OK);
+        assert_eq!(response_body.total, 1);
+        assert_eq!(result.todo.id, id.to_string());
+        assert!(result.score > 0.0);
+        assert_eq!(
+            result.highlights.subject,
+            Some(format!("Call <mark>{}</mark>", search_term))
+        );
+        assert_eq!(
+            result.highlights.description,
+            Some(format!("Ask <mark>{}</mark> about it", search_term))
+        );
+    }
+
+    #[tokio::test]
+    async fn paginates_filters_and_counts_matches() {
+        let id = Ulid::new();
+        let search_term = unique_word(&id);
+        let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+        let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+        let dependencies = Dependencies::new(clock.clone(), id_generator);
+
+        let app = get_app(dependencies).await;
+        create_todo_about(&app, &search_term).await;
+
+        let res = app
+            .get(&format!("/v1/todos/search?q={}&isDone=true", search_term))
+            .send()
+            .await;
+        let response_body: SearchTodosResponse = res.json().await;
+
+        assert!(response_body.results.is_empty());
+        assert_eq!(response_body.total, 0);
+        assert_eq!(response_body.facets.status.open, 1);
+        assert_eq!(response_body.facets.status.done, 0);
+
+        let res = app
+            .get(&format!(
+                "/v1/todos/search?q={}&page=2&perPage=1",
+                search_term
+            ))
+            .send()
+            .await;
+        let response_body: SearchTodosResponse = res.json().await;
+
+        assert!(response_body.results.is_empty());
+        assert_eq!(response_body.total, 1);
+        assert_eq!(response_body.page, 2);
+        assert_eq!(response_body.per_page, 1);
+    }
+
+    #[tokio::test]
+    async fn returns_bad_request_for_bad_pagination() {
+        let id = Ulid::new();
+        let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+        let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+        let dependencies = Dependencies::new(clock.clone(), id_generator);
+
+        let app = get_app(dependencies).await;
+
+        let res = app
+            .get("/v1/todos/search?q=Dummy&perPage=1000")
+            .send()
+            .await;
+
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let res = app
+            .get("/v1/todos/search?q=Dummy&page=4294967295&perPage=100")
+            .send()
+            .await;
+
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    async fn create_todo_about(app: &TestClient, word: &str) {
+        let payload = json!({
+          "description": format!("Ask {} about it", word),
+          "dueDate": DATETIME_STRING,
+          "subject": format!("Call {}", word)
+        });
+
+        let res = app.post("/v1/todos").json(&payload).send().await;
+
+        assert_eq!(res.status(), StatusCode::OK, "Unable to create Todo");
+    }
+}
+
+mod suggest_todos {
+    use super::*;
+
+    #[tokio::test]
+    async fn suggests_subjects_starting_with_the_prefix() {
+        let id = Ulid::new();
+        let word = random_first_word(&id);
+        let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+        let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+        let dependencies = Dependencies::new(clock.clone(), id_generator);
+
+        let app = get_app(dependencies).await;
+        create_todo_with_subject(&app, &format!("Call {}", word)).await;
+
+        let res = app
+            .get(&format!(
+                "/v1/todos/suggest?prefix={}",
+                word[..6].to_uppercase()
+            ))
+            .send()
+            .await;
+
+        let response_status = res.status();
+        let response_body: Vec<TodoSuggestionResponse> = res.json().await;
+
+        assert_eq!(response_status, StatusCode::OK);
+        assert_eq!(
+            response_body,
+            vec![TodoSuggestionResponse {
+                id: id.to_string(),
+                subject: format!("Call {}", word),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn matches_every_word_as_a_prefix() {
+        let id = Ulid::new();
+        let word = random_first_word(&id);
+        let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+        let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+        let dependencies = Dependencies::new(clock.clone(), id_generator);
+
+        let app = get_app(dependencies).await;
+        create_todo_with_subject(&app, &format!("Call {}", word)).await;
+
+        let res = app
+            .get(&format!("/v1/todos/suggest?prefix=ca%20{}", &word[..6]))
+            .send()
+            .await;
+        let response_body: Vec<TodoSuggestionResponse> = res.json().await;
+
+        assert_eq!(response_body.len(), 1);
+
+        let res = app
+            .get(&format!("/v1/todos/suggest?prefix=bu%20{}", &word[..6]))
+            .send()
+            .await;
+        let response_body: Vec<TodoSuggestionResponse> = res.json().await;
+
+        assert!(response_body.is_empty());
+    }
+
+    #[tokio::test]
+    async fn returns_at_most_limit_suggestions() {
+        let id = Ulid::new();
+        let word = random_first_word(&id);
+        let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+        let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+        let dependencies = Dependencies::new(clock.clone(), id_generator);
+
+        let app = get_app(dependencies).await;
+        create_todo_with_subject(&app, &format!("Call {}", word)).await;
+
+        // The mocked generator hands out a single id, so every other Todo
+        // goes through an app of its own.
+        for subject in ["Email", "Visit"] {
+            let other_id = MockUlidGenerator::with_fixed_value(&Ulid::new().to_string());
+            let other_app = get_app(Dependencies::new(clock.clone(), other_id)).await;
+
+            create_todo_with_subject(&other_app, &format!("{} {}", subject, word)).await;
+        }
+
+        let res = app
+            .get(&format!("/v1/todos/suggest?prefix={}&limit=2", &word[..6]))
+            .send()
+            .await;
+
+        let response_status = res.status();
+        let response_body: Vec<TodoSuggestionResponse> = res.json().await;
+
+        assert_eq!(response_status, StatusCode::OK);
+        assert_eq!(response_body.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn fails_for_empty_prefix() {
+        let id = Ulid::new();
+        let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+        let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+        let dependencies = Dependencies::new(clock.clone(), id_generator);
+
+        let app = get_app(dependencies).await;
+
+        let res = app.get("/v1/todos/suggest?prefix=").send().await;
+
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    /// Prefixes only match the start of a word, so the random part of the id
+    /// goes first to keep tests created in the same millisecond apart.
+    fn random_first_word(id: &Ulid) -> String {
+        unique_word(id).chars().rev().collect()
+    }
+
+    async fn create_todo_with_subject(app: &TestClient, subject: &str) {
+        let payload = json!({
+          "description": "Dummy description",
+          "dueDate": DATETIME_STRING,
+          "subject": subject
+        });
+
+        let res = app.post("/v1/todos").json(&payload).send().await;
+
+        assert_eq!(res.status(), StatusCode::OK, "Unable to create Todo");
+    }
+}
+
+mod export_todos {
+    use super::*;
+
+    #[tokio::test]
+    async fn returns_bad_request_for_unknown_format() {
+        let id = Ulid::new();
+        let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+        let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+        let dependencies = Dependencies::new(clock.clone(), id_generator);
+
+        let app = get_app(dependencies).await;
+
+        let res = app.get("/v1/todos/export?format=xlsx").send().await;
+
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn exports_csv() {
+        let id = Ulid::new();
+        let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+        let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+        let dependencies = Dependencies::new(clock.clone(), id_generator);
+
+        let app = get_app(dependencies).await;
+        create_one_todo(&app).await;
+
+        let res = app.get("/v1/todos/export?format=csv").send().await;
+
+        let response_status = res.status();
+        let content_type = res.headers()["content-type"].to_str().unwrap().to_string();
+        let response_body = res.text().await;
+        let mut lines = response_body.lines();
+
+        assert_eq!(response_status, StatusCode::OK);
+        assert!(content_type.starts_with("text/csv"));
+        assert_eq!(
+            lines.next(),
+            Some("id,subject,description,isDone,dueDate,createdAt,updatedAt")
+        );
+        assert!(lines.any(|line| line.starts_with(&format!("{},Dummy subject", id))));
+    }
+
+    #[tokio::test]
+    async fn exports_ndjson() {
+        let id = Ulid::new();
+        let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+        let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+        let dependencies = Dependencies::new(clock.clone(), id_generator);
+
+        let app = get_app(dependencies).await;
+        create_one_todo(&app).await;
+
+        let res = app
+            .get("/v1/todos/export?format=ndjson&isDone=false")
+            .send()
+            .await;
+
+        let response_status = res.status();
+        let response_body = res.text().await;
+        let todos: Vec<TodoResponse> = response_body
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(response_status, StatusCode::OK);
+        assert!(todos.iter().all(|todo| !todo.is_done));
+        assert!(todos.iter().any(|todo| todo.id == id.to_string()));
+    }
+
+    #[tokio::test]
+    async fn exports_icalendar() {
+        let id = Ulid::new();
+        let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+        let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+        let dependencies = Dependencies::new(clock.clone(), id_generator);
+
+        let app = get_app(dependencies).await;
+        create_one_todo(&app).await;
+
+        let res = app.get("/v1/todos/export?format=ics").send().await;
+
+        let response_status = res.status();
+        let response_body = res.text().await;
+
+        assert_eq!(response_status, StatusCode::OK);
+        assert!(response_body.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(response_body.ends_with("END:VCALENDAR\r\n"));
+        assert!(response_body.contains(&format!("UID:{}\r\n", id)));
+        assert!(response_body.contains("SUMMARY:Dummy subject\r\n"));
+        assert!(response_body.contains("DUE:20231104T153234Z\r\n"));
+        assert!(response_body.contains("STATUS:NEEDS-ACTION\r\n"));
+    }
+}
+
+mod import_todos {
+    use serde_json::Value;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn returns_bad_request_for_unknown_format() {
+        let id = Ulid::new();
+        let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+        let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+        let dependencies = Dependencies::new(clock.clone(), id_generator);
+
+        let app = get_app(dependencies).await;
+
+        let res = app
+            .post("/v1/todos/import")
+            .header("content-type", "application/pdf")
+            .body("%PDF-1.7")
+            .send()
+            .await;
+
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn reports_invalid_rows_on_dry_run() {
+        let id = Ulid::new();
+        let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+        let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+        let dependencies = Dependencies::new(clock.clone(), id_generator);
+
+        let app = get_app(dependencies).await;
+
+        let csv = format!(
+            "subject,description,dueDate\n\
+             Buy groceries,From the supermarket,{DATETIME_STRING}\n\
+             ,Missing subject,{DATETIME_STRING}\n\
+             Bad date,Has a bad date,yesterday\n"
+        );
+
+        let res = app
+            .post("/v1/todos/import?dryRun=true")
+            .header("content-type", "text/csv")
+            .body(csv)
+            .send()
+            .await;
+
+        let response_status = res.status();
+        let response_body: Value = res.json().await;
+
+        assert_eq!(response_status, StatusCode::OK);
+        assert_eq!(response_body["dryRun"], true);
+        assert_eq!(response_body["total"], 3);
+        assert_eq!(response_body["imported"], 1);
+        assert_eq!(response_body["failed"], 2);
+        assert_eq!(response_body["errors"][0]["row"], 2);
+        assert_eq!(response_body["errors"][0]["code"], "VALIDATION_ERROR");
+        assert_eq!(response_body["errors"][1]["row"], 3);
+
+        let res = app.get(&format!("/v1/todos/{}", id)).send().await;
+
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn imports_json_rows() {
+        let id = Ulid::new();
+        let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+        let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+        let dependencies = Dependencies::new(clock.clone(), id_generator);
+
+        let app = get_app(dependencies).await;
+
+        let payload = json!([{
+            "subject": "Imported subject",
+            "description": "Imported description",
+            "dueDate": DATETIME_STRING,
+            "isDone": true
+        }]);
+
+        let res = app.post("/v1/todos/import").json(&payload).send().await;
+        let response_status = res.status();
+        let response_body: Value = res.json().await;
+
+        assert_eq!(response_status, StatusCode::OK);
+        assert_eq!(response_body["imported"], 1);
+        assert_eq!(response_body["failed"], 0);
+
+        let res = app.get(&format!("/v1/todos/{}", id)).send().await;
+        let response_body: TodoResponse = res.json().await;
+
+        assert_eq!(response_body.subject, "Imported subject");
+        assert!(response_body.is_done);
+        assert_eq!(response_body.due_date, clock.now());
+    }
+
+    #[tokio::test]
+    async fn reads_vtodo_components_from_icalendar() {
+        let id = Ulid::new();
+        let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+        let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+        let dependencies = Dependencies::new(clock.clone(), id_generator);
+
+        let app = get_app(dependencies).await;
+
+        let calendar = "BEGIN:VCALENDAR\r\n\
+            VERSION:2.0\r\n\
+            BEGIN:VTODO\r\n\
+            UID:1\r\n\
+            SUMMARY:Call the dentist\r\n\
+            DESCRIPTION:Book a check-up\\, ask about \r\n the bill\r\n\
+            DUE;VALUE=DATE:20231110\r\n\
+            STATUS:COMPLETED\r\n\
+            END:VTODO\r\n\
+            BEGIN:VTODO\r\n\
+            UID:2\r\n\
+            SUMMARY:No due date\r\n\
+            DESCRIPTION:Never mind\r\n\
+            END:VTODO\r\n\
+            END:VCALENDAR\r\n";
+
+        let res = app
+            .post("/v1/todos/import?dryRun=true")
+            .header("content-type", "text/calendar")
+            .body(calendar)
+            .send()
+            .await;
+
+        let response_status = res.status();
+        let response_body: Value = res.json().await;
+
+        assert_eq!(response_status, StatusCode::OK);
+        assert_eq!(response_body["total"], 2);
+        assert_eq!(response_body["imported"], 1);
+        assert_eq!(response_body["errors"][0]["row"], 2);
     }
 }
 
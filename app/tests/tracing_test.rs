@@ -0,0 +1,116 @@
+use app::util::REQUEST_ID_HEADER;
+use axum::http::StatusCode;
+use opentelemetry::{trace::SpanId, Key};
+use opentelemetry_sdk::export::trace::SpanData;
+use serde_json::json;
+use tracing::{info, info_span};
+use ulid::Ulid;
+
+use crate::fixtures::{
+    app::{get_app, Dependencies, DATETIME_STRING},
+    clock::MockClock,
+    id_generator::MockUlidGenerator,
+    tracing::Collector,
+};
+
+mod fixtures;
+
+static TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+static CALLER_SPAN_ID: &str = "00f067aa0ba902b7";
+
+#[tokio::test]
+async fn continues_the_callers_trace_down_to_the_repository() {
+    let collector = Collector::start();
+    let id = Ulid::new();
+    let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+    let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+    let dependencies = Dependencies::new(clock, id_generator).with_telemetry();
+    let app = get_app(dependencies).await;
+
+    let res = app
+        .post("/v1/todos")
+        .header(
+            "traceparent",
+            format!("00-{}-{}-01", TRACE_ID, CALLER_SPAN_ID),
+        )
+        .header(REQUEST_ID_HEADER, "checkout-42")
+        .json(&json!({
+          "description": "Dummy description",
+          "dueDate": "2023-11-05T18:00:00Z",
+          "subject": "Dummy subject",
+        }))
+        .send()
+        .await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let request = &collector.spans_named("request")[0];
+    let service = &collector.spans_named("todo_service.create_todo")[0];
+    let repository = &collector.spans_named("todo_repository.create_todo")[0];
+
+    assert_eq!(request.span_context.trace_id().to_string(), TRACE_ID);
+    assert_eq!(request.parent_span_id.to_string(), CALLER_SPAN_ID);
+    assert_eq!(
+        attribute(request, "request_id"),
+        Some(String::from("checkout-42"))
+    );
+    assert_eq!(attribute(request, "route"), Some(String::from("/v1/todos")));
+    assert_eq!(service.span_context.trace_id().to_string(), TRACE_ID);
+    assert_eq!(service.parent_span_id, request.span_context.span_id());
+    assert_eq!(repository.parent_span_id, service.span_context.span_id());
+    assert_eq!(attribute(service, "todo.id"), Some(id.to_string()));
+    assert_eq!(attribute(repository, "todo.id"), Some(id.to_string()));
+
+    let res = app.delete(&format!("/v1/todos/{}", id)).send().await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+
+    // Without a traceparent the request starts a trace of its own.
+    let request = &collector.spans_named("request")[1];
+    let service = &collector.spans_named("todo_service.delete_todo")[0];
+
+    assert_eq!(
+        attribute(request, "route"),
+        Some(String::from("/v1/todos/:id"))
+    );
+
+    assert_ne!(service.span_context.trace_id().to_string(), TRACE_ID);
+    assert_ne!(service.parent_span_id, SpanId::INVALID);
+    assert_eq!(attribute(service, "todo.id"), Some(id.to_string()));
+}
+
+#[tokio::test]
+async fn masks_redacted_fields_in_exported_spans() {
+    let collector = Collector::start();
+
+    info_span!("import", todo.description = "Call the bank about 4242")
+        .in_scope(|| info!(api_token = "s3cr3t", attempts = 3, "Imported todos"));
+
+    let span = &collector.spans_named("import")[0];
+    let event = span.events.iter().next().unwrap();
+    let event_attribute = |key: &'static str| {
+        event
+            .attributes
+            .iter()
+            .find(|attribute| attribute.key == Key::from_static_str(key))
+            .map(|attribute| attribute.value.to_string())
+    };
+
+    assert_eq!(
+        attribute(span, "todo.description"),
+        Some(String::from("[REDACTED]"))
+    );
+    assert_eq!(
+        event_attribute("api_token"),
+        Some(String::from("[REDACTED]"))
+    );
+    assert_eq!(event_attribute("attempts"), Some(String::from("3")));
+}
+
+fn attribute(span: &SpanData, key: &'static str) -> Option<String> {
+    span.attributes
+        .iter()
+        .find(|attribute| attribute.key == Key::from_static_str(key))
+        .map(|attribute| attribute.value.to_string())
+}
@@ -0,0 +1,156 @@
+use app::docs::v1::{todos::TodoResponse, views::ViewResponse};
+use axum::http::StatusCode;
+use axum_test_helper::TestClient;
+use serde_json::json;
+use ulid::Ulid;
+
+use crate::fixtures::{
+    app::{get_app, Dependencies, DATETIME_STRING},
+    clock::MockClock,
+    id_generator::MockUlidGenerator,
+};
+
+mod fixtures;
+
+#[tokio::test]
+async fn lists_built_in_views_first() {
+    let id = Ulid::new();
+    let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+    let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+    let dependencies = Dependencies::new(clock.clone(), id_generator);
+
+    let app = get_app(dependencies).await;
+
+    let res = app.get("/v1/views").send().await;
+
+    let response_status = res.status();
+    let response_body: Vec<ViewResponse> = res.json().await;
+    let ids: Vec<&str> = response_body.iter().map(|v| v.id.as_str()).collect();
+
+    assert_eq!(response_status, StatusCode::OK);
+    assert_eq!(ids[..4], ["today", "overdue", "upcoming", "completed"]);
+    assert!(response_body[..4].iter().all(|v| v.is_virtual));
+}
+
+#[tokio::test]
+async fn computes_built_in_views_against_the_clock() {
+    let id = Ulid::new();
+    let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+    let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+    let dependencies = Dependencies::new(clock.clone(), id_generator);
+
+    let app = get_app(dependencies).await;
+    create_todo_due(&app, &id, "2023-11-01T09:00:00Z").await;
+
+    let overdue = view_todo_ids(&app, "overdue").await;
+    let today = view_todo_ids(&app, "today").await;
+    let upcoming = view_todo_ids(&app, "upcoming").await;
+
+    assert!(overdue.contains(&id.to_string()));
+    assert!(!today.contains(&id.to_string()));
+    assert!(!upcoming.contains(&id.to_string()));
+}
+
+#[tokio::test]
+async fn saves_updates_and_deletes_a_view() {
+    let id = Ulid::new();
+    let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+    let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+    let dependencies = Dependencies::new(clock.clone(), id_generator);
+
+    let app = get_app(dependencies).await;
+    create_todo_due(&app, &id, DATETIME_STRING).await;
+
+    let payload = json!({
+      "name": "Reviews",
+      "query": format!("is:open \"review {}\"", id.to_string().to_lowercase())
+    });
+
+    let res = app.post("/v1/views").json(&payload).send().await;
+    let response_status = res.status();
+    let view: ViewResponse = res.json().await;
+
+    assert_eq!(response_status, StatusCode::OK);
+    assert_eq!(view.id, id.to_string());
+    assert!(!view.is_virtual);
+
+    assert_eq!(view_todo_ids(&app, &view.id).await, vec![id.to_string()]);
+
+    let res = app
+        .patch(&format!("/v1/views/{}", view.id))
+        .json(&json!({ "name": "Open reviews" }))
+        .send()
+        .await;
+    let updated: ViewResponse = res.json().await;
+
+    assert_eq!(updated.name, "Open reviews");
+    assert_eq!(updated.query, view.query);
+
+    let res = app.delete(&format!("/v1/views/{}", view.id)).send().await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = app.get(&format!("/v1/views/{}", view.id)).send().await;
+
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn rejects_views_with_an_invalid_query() {
+    let id = Ulid::new();
+    let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+    let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+    let dependencies = Dependencies::new(clock.clone(), id_generator);
+
+    let app = get_app(dependencies).await;
+
+    let payload = json!({ "name": "Later", "query": "is:later" });
+
+    let res = app.post("/v1/views").json(&payload).send().await;
+
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn built_in_views_cannot_be_deleted() {
+    let id = Ulid::new();
+    let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+    let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+    let dependencies = Dependencies::new(clock.clone(), id_generator);
+
+    let app = get_app(dependencies).await;
+
+    let res = app.delete("/v1/views/today").send().await;
+
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+async fn create_todo_due(app: &TestClient, id: &Ulid, due_date: &str) {
+    let payload = json!({
+      "description": "Dummy description",
+      "dueDate": due_date,
+      "subject": format!("Review {}", id)
+    });
+
+    let res = app.post("/v1/todos").json(&payload).send().await;
+
+    assert_eq!(res.status(), StatusCode::OK, "Unable to create Todo");
+}
+
+async fn view_todo_ids(app: &TestClient, view_id: &str) -> Vec<String> {
+    let res = app
+        .get(&format!("/v1/views/{}/todos", view_id))
+        .send()
+        .await;
+
+    assert_eq!(res.status(), StatusCode::OK, "Unable to get View Todos");
+
+    let todos: Vec<TodoResponse> = res.json().await;
+
+    todos.into_iter().map(|todo| todo.id).collect()
+}
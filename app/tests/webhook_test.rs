@@ -0,0 +1,261 @@
+use app::{
+    docs::v1::webhooks::{DeliveryStatus, WebhookDeliveryResponse, WebhookResponse},
+    resource::v1::webhooks::{sign, EVENT_HEADER, ID_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER},
+    util::API_KEY_HEADER,
+};
+use axum::http::StatusCode;
+use axum_test_helper::TestClient;
+use serde_json::json;
+use ulid::Ulid;
+
+use crate::fixtures::{
+    app::{get_app, Dependencies, ADMIN_API_KEY, DATETIME_STRING},
+    clock::MockClock,
+    id_generator::MockUlidGenerator,
+    webhook::{get_dispatcher, Receiver},
+};
+
+mod fixtures;
+
+// A dispatcher sends every due delivery in the database, so the deliveries
+// that are counted here are only ever due in 2042 and later.
+
+static SECRET: &str = "0123456789abcdef0123456789abcdef";
+
+#[tokio::test]
+async fn returns_the_secret_only_on_create() {
+    let id = Ulid::new();
+    let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+    let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+    let dependencies = Dependencies::new(clock, id_generator);
+    let app = get_app(dependencies).await;
+
+    let res = app
+        .post("/v1/webhooks")
+        .json(&json!({ "url": "http://127.0.0.1:9/hook", "events": ["*"] }))
+        .header(API_KEY_HEADER, ADMIN_API_KEY)
+        .send()
+        .await;
+    let response_status = res.status();
+    let created: WebhookResponse = res.json().await;
+
+    assert_eq!(response_status, StatusCode::OK);
+    assert_eq!(created.id, id.to_string());
+    assert_eq!(created.secret.map(|secret| secret.len()), Some(64));
+
+    let res = app
+        .get(&format!("/v1/webhooks/{}", id))
+        .header(API_KEY_HEADER, ADMIN_API_KEY)
+        .send()
+        .await;
+    let fetched: WebhookResponse = res.json().await;
+
+    assert_eq!(fetched.events, vec!["*"]);
+    assert_eq!(fetched.secret, None);
+
+    let res = app
+        .delete(&format!("/v1/webhooks/{}", id))
+        .header(API_KEY_HEADER, ADMIN_API_KEY)
+        .send()
+        .await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn returns_problem_for_unknown_event_or_scheme() {
+    let id = Ulid::new();
+    let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+    let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+    let dependencies = Dependencies::new(clock, id_generator);
+    let app = get_app(dependencies).await;
+
+    let res = app
+        .post("/v1/webhooks")
+        .json(&json!({ "url": "ftp://example.com/hook", "events": ["todo.archived"] }))
+        .header(API_KEY_HEADER, ADMIN_API_KEY)
+        .send()
+        .await;
+    let response_status = res.status();
+    let response_body: serde_json::Value = res.json().await;
+
+    assert_eq!(response_status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response_body["issues"],
+        json!([
+            "url: `ftp://example.com/hook` is not an http or https URL",
+            "events: `todo.archived` is not an event such as todo.created, or * for every event"
+        ])
+    );
+}
+
+#[tokio::test]
+async fn delivers_signed_events_and_retries_until_dead() {
+    let id = Ulid::new();
+    let clock = MockClock::with_frozen_time("2042-03-01T10:00:00Z".into());
+    let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+    let dependencies = Dependencies::new(clock, id_generator);
+    let app = get_app(dependencies).await;
+    let receiver = Receiver::start().await;
+
+    let res = app
+        .post("/v1/webhooks")
+        .json(&json!({
+            "url": receiver.url,
+            "events": ["todo.created", "todo.completed"],
+            "secret": SECRET
+        }))
+        .header(API_KEY_HEADER, ADMIN_API_KEY)
+        .send()
+        .await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+
+    create_todo(&app, &id).await;
+    tick_at("2042-03-01T10:00:01Z").await;
+
+    let requests = receiver.requests_for(&id);
+
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].header(EVENT_HEADER), "todo.created");
+    assert!(!requests[0].header(ID_HEADER).is_empty());
+    assert_eq!(requests[0].header(TIMESTAMP_HEADER), "2277280801");
+    assert_eq!(
+        requests[0].header(SIGNATURE_HEADER),
+        sign(SECRET, 2277280801, &requests[0].body)
+    );
+    assert_eq!(requests[0].payload().todo.subject, format!("Ship {}", id));
+
+    // Completing the todo while the receiver is down.
+    receiver.answer_with(StatusCode::SERVICE_UNAVAILABLE);
+
+    let res = app
+        .patch(&format!("/v1/todos/{}", id))
+        .json(&json!({ "isDone": true }))
+        .send()
+        .await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+
+    tick_at("2042-03-01T10:00:01Z").await;
+
+    let pending = get_deliveries(&app, &id, "pending").await;
+
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].event, "todo.completed");
+    assert_eq!(pending[0].attempts, 1);
+    assert_eq!(pending[0].last_status_code, Some(503));
+    assert_eq!(
+        pending[0].next_attempt_at.to_rfc3339(),
+        "2042-03-01T10:00:31+00:00"
+    );
+
+    // Not due yet, then the last of two attempts.
+    tick_at("2042-03-01T10:00:20Z").await;
+    tick_at("2042-03-01T10:00:31Z").await;
+
+    assert_eq!(receiver.requests_for(&id).len(), 3);
+
+    let dead = get_deliveries(&app, &id, "dead").await;
+
+    assert_eq!(dead.len(), 1);
+    assert_eq!(dead[0].attempts, 2);
+
+    receiver.answer_with(StatusCode::OK);
+
+    let res = app
+        .post(&format!(
+            "/v1/webhooks/{}/deliveries/{}/redeliver",
+            id, dead[0].id
+        ))
+        .header(API_KEY_HEADER, ADMIN_API_KEY)
+        .send()
+        .await;
+    let response_status = res.status();
+    let redelivery: WebhookDeliveryResponse = res.json().await;
+
+    assert_eq!(response_status, StatusCode::OK);
+    assert_eq!(redelivery.status, DeliveryStatus::Pending);
+    assert_eq!(redelivery.attempts, 0);
+
+    tick_at("2042-03-01T10:00:40Z").await;
+
+    let requests = receiver.requests_for(&id);
+
+    assert_eq!(requests.len(), 4);
+    assert_eq!(requests[3].body, requests[1].body);
+    assert!(get_deliveries(&app, &id, "dead").await.is_empty());
+
+    app.delete(&format!("/v1/webhooks/{}", id))
+        .header(API_KEY_HEADER, ADMIN_API_KEY)
+        .send()
+        .await;
+}
+
+#[tokio::test]
+async fn every_route_takes_the_api_key() {
+    let id = Ulid::new();
+    let clock = MockClock::with_frozen_time(DATETIME_STRING.into());
+    let id_generator = MockUlidGenerator::with_fixed_value(&id.to_string());
+
+    let app = get_app(Dependencies::new(clock, id_generator)).await;
+
+    let res = app.get("/v1/webhooks").send().await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = app
+        .post("/v1/webhooks")
+        .header(API_KEY_HEADER, "not-the-key")
+        .json(&json!({ "url": "https://hooks.example.com/todos", "events": ["*"] }))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let res = app
+        .delete(&format!("/v1/webhooks/{}", id))
+        .header(API_KEY_HEADER, "not-the-key")
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+}
+
+async fn create_todo(app: &TestClient, id: &Ulid) {
+    let payload = json!({
+      "description": "Dummy description",
+      "dueDate": "2042-03-02T18:00:00Z",
+      "subject": format!("Ship {}", id),
+    });
+
+    let res = app.post("/v1/todos").json(&payload).send().await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+/// Runs one tick of a dispatcher that gives up after two attempts.
+async fn tick_at(time: &str) {
+    get_dispatcher(MockClock::with_frozen_time(time.into()))
+        .await
+        .with_max_attempts(2)
+        .tick()
+        .await
+        .unwrap();
+}
+
+async fn get_deliveries(
+    app: &TestClient,
+    webhook_id: &Ulid,
+    status: &str,
+) -> Vec<WebhookDeliveryResponse> {
+    app.get(&format!(
+        "/v1/webhooks/{}/deliveries?status={}",
+        webhook_id, status
+    ))
+    .header(API_KEY_HEADER, ADMIN_API_KEY)
+    .send()
+    .await
+    .json()
+    .await
+}
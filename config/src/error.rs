@@ -0,0 +1,21 @@
+use std::fmt::{self, Display};
+
+/// Every setting that is missing or invalid, so they can all be fixed at once.
+#[derive(Debug, PartialEq)]
+pub struct ConfigError {
+    pub problems: Vec<String>,
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid configuration:")?;
+
+        for problem in &self.problems {
+            write!(f, "\n  - {}", problem)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
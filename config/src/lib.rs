@@ -0,0 +1,7 @@
+pub mod error;
+pub mod settings;
+pub mod vars;
+
+pub use error::*;
+pub use settings::*;
+pub use vars::*;
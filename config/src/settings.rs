@@ -0,0 +1,387 @@
+use std::str::FromStr;
+
+use chrono_tz::Tz;
+
+use crate::{ConfigError, Vars};
+
+#[derive(PartialEq, Debug, Clone, Default)]
+pub enum Environment {
+    Production,
+    #[default]
+    Development,
+    Test,
+}
+
+impl FromStr for Environment {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "prod" => Ok(Environment::Production),
+            "dev" => Ok(Environment::Development),
+            "test" => Ok(Environment::Test),
+            _ => Err(String::from("expected one of prod, dev or test")),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Application {
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct DatabaseConfig {
+    pub url: String,
+    pub namespace: String,
+    pub name: String,
+    pub username: String,
+    pub password: String,
+    /// Connection attempts on startup before giving up
+    pub connect_attempts: u32,
+    /// How often the database is pinged to notice it going down and coming back
+    pub health_interval_seconds: u64,
+}
+
+impl DatabaseConfig {
+    pub fn from_vars(vars: &mut Vars) -> Self {
+        let config = Self {
+            url: vars.required("SURREALDB_URL"),
+            namespace: vars.required("SURREALDB_NAMESPACE"),
+            name: vars.required("SURREALDB_DATABASE"),
+            username: vars.required("SURREALDB_USERNAME"),
+            password: vars.required("SURREALDB_PASSWORD"),
+            connect_attempts: vars.or::<u32>("DB_CONNECT_ATTEMPTS", 10).max(1),
+            health_interval_seconds: vars.or("DB_HEALTH_INTERVAL_SECONDS", 5),
+        };
+
+        if config.health_interval_seconds == 0 {
+            vars.invalid("DB_HEALTH_INTERVAL_SECONDS", "must be at least 1");
+        }
+
+        config
+    }
+}
+
+/// What a rate limit tells clients apart by. There is no per user scope,
+/// requests are not made on behalf of user accounts.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+pub enum RateLimitScope {
+    Ip,
+    ApiKey,
+}
+
+impl FromStr for RateLimitScope {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "ip" => Ok(RateLimitScope::Ip),
+            "key" => Ok(RateLimitScope::ApiKey),
+            "user" => Err(String::from(
+                "user limits are not supported, requests are not made by users",
+            )),
+            _ => Err(String::from("expected one of ip or key")),
+        }
+    }
+}
+
+/// `requests` every `period_seconds` for each client of `scope` on the routes
+/// of `group`, written `group.scope=requests/seconds` as in `todos.ip=100/60`.
+#[derive(PartialEq, Debug, Clone)]
+pub struct RateLimitRule {
+    pub group: String,
+    pub scope: RateLimitScope,
+    pub requests: u32,
+    pub period_seconds: u64,
+}
+
+impl FromStr for RateLimitRule {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("{} is not written group.scope=requests/seconds", value);
+
+        let (target, quota) = value.split_once('=').ok_or_else(invalid)?;
+        let (group, scope) = target.trim().split_once('.').ok_or_else(invalid)?;
+        let (requests, period_seconds) = quota.trim().split_once('/').ok_or_else(invalid)?;
+
+        let rule = Self {
+            group: group.to_lowercase(),
+            scope: scope.parse()?,
+            requests: requests.parse().map_err(|_| invalid())?,
+            period_seconds: period_seconds.parse().map_err(|_| invalid())?,
+        };
+
+        if rule.group.is_empty() || rule.requests == 0 || rule.period_seconds == 0 {
+            return Err(invalid());
+        }
+
+        Ok(rule)
+    }
+}
+
+/// Comma separated [`RateLimitRule`]s.
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct RateLimitRules(pub Vec<RateLimitRule>);
+
+impl FromStr for RateLimitRules {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|rule| !rule.is_empty())
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map(RateLimitRules)
+    }
+}
+
+/// Where the rate limit buckets are kept.
+#[derive(PartialEq, Debug, Clone, Default)]
+pub enum RateLimitBackend {
+    /// Per instance, each one allows the full quota
+    #[default]
+    Memory,
+    /// Shared by every instance using the same database
+    SurrealDb,
+}
+
+impl FromStr for RateLimitBackend {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "memory" => Ok(RateLimitBackend::Memory),
+            "surrealdb" => Ok(RateLimitBackend::SurrealDb),
+            _ => Err(String::from("expected one of memory or surrealdb")),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    pub backend: RateLimitBackend,
+    /// Header the proxy puts the client address in, such as `Fly-Client-IP`.
+    /// The address of the connection is used when unset.
+    pub client_ip_header: Option<String>,
+    /// Proxies in front of the one that sets the header, each appending to
+    /// it. 0 takes the right-most address, which is all a single value
+    /// header such as `Fly-Client-IP` has.
+    pub trusted_hops: usize,
+    /// Rules of the `default` group apply to the groups without their own
+    pub rules: Vec<RateLimitRule>,
+}
+
+static DEFAULT_RATE_LIMITS: &str = "default.ip=300/60,default.key=1200/60,webhooks.ip=30/60";
+
+impl RateLimitConfig {
+    pub fn from_vars(vars: &mut Vars) -> Self {
+        let default_rules = DEFAULT_RATE_LIMITS
+            .parse::<RateLimitRules>()
+            .expect("default rate limits are valid");
+
+        Self {
+            enabled: vars.or("RATE_LIMIT_ENABLED", true),
+            backend: vars.or("RATE_LIMIT_BACKEND", RateLimitBackend::Memory),
+            client_ip_header: vars.optional("RATE_LIMIT_CLIENT_IP_HEADER"),
+            trusted_hops: vars.or("RATE_LIMIT_TRUSTED_HOPS", 0),
+            rules: vars.or("RATE_LIMITS", default_rules).0,
+        }
+    }
+}
+
+/// Origin that allows every origin in `CORS_ALLOWED_ORIGINS`.
+pub static ANY_ORIGIN: &str = "*";
+
+/// The middleware wrapped around every route, each one can be turned off.
+#[derive(Debug, Clone)]
+pub struct HttpConfig {
+    pub cors_enabled: bool,
+    /// Origins such as `https://todo.example.com`, or `*` for any
+    pub cors_allowed_origins: Vec<String>,
+    pub cors_allowed_methods: Vec<String>,
+    /// Lets browsers send cookies and `Authorization`, not with any origin
+    pub cors_allow_credentials: bool,
+    /// HSTS, nosniff, frame denial and a content security policy
+    pub security_headers_enabled: bool,
+    /// Left out of `Strict-Transport-Security` when 0
+    pub hsts_max_age_seconds: u64,
+    /// Largest request body read, unlimited when 0
+    pub body_limit_bytes: usize,
+    /// Time to start answering a request, unlimited when 0
+    pub request_timeout_seconds: u64,
+    /// gzip and brotli, as the client accepts
+    pub compression_enabled: bool,
+}
+
+static DEFAULT_CORS_ALLOWED_METHODS: &str = "GET,POST,PUT,PATCH,DELETE";
+
+impl HttpConfig {
+    pub fn from_vars(vars: &mut Vars) -> Self {
+        let config = Self {
+            cors_enabled: vars.or("CORS_ENABLED", false),
+            cors_allowed_origins: vars.list("CORS_ALLOWED_ORIGINS", ""),
+            cors_allowed_methods: vars
+                .list("CORS_ALLOWED_METHODS", DEFAULT_CORS_ALLOWED_METHODS)
+                .into_iter()
+                .map(|method| method.to_uppercase())
+                .collect(),
+            cors_allow_credentials: vars.or("CORS_ALLOW_CREDENTIALS", false),
+            security_headers_enabled: vars.or("SECURITY_HEADERS_ENABLED", true),
+            hsts_max_age_seconds: vars.or("HSTS_MAX_AGE_SECONDS", 31_536_000),
+            body_limit_bytes: vars.or("BODY_LIMIT_BYTES", 2 * 1024 * 1024),
+            request_timeout_seconds: vars.or("REQUEST_TIMEOUT_SECONDS", 30),
+            compression_enabled: vars.or("COMPRESSION_ENABLED", true),
+        };
+
+        if let Some(method) = config
+            .cors_allowed_methods
+            .iter()
+            .find(|method| !method.bytes().all(|byte| byte.is_ascii_alphabetic()))
+        {
+            vars.invalid(
+                "CORS_ALLOWED_METHODS",
+                &format!("{} is not an HTTP method", method),
+            );
+        }
+
+        // Browsers refuse credentials from a wildcard origin.
+        if config.cors_allow_credentials
+            && config
+                .cors_allowed_origins
+                .iter()
+                .any(|origin| origin == ANY_ORIGIN)
+        {
+            vars.invalid(
+                "CORS_ALLOWED_ORIGINS",
+                "cannot allow any origin while CORS_ALLOW_CREDENTIALS is on",
+            );
+        }
+
+        config
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub env: Environment,
+    pub port: u16,
+    /// Serves `/metrics` on this port instead of the API port when set
+    pub metrics_port: Option<u16>,
+    /// OTLP/HTTP collector that spans are exported to, none are when unset
+    pub otlp_endpoint: Option<String>,
+    /// Log fields whose values are masked, a name also covers `*_name` and `*.name`
+    pub redacted_fields: Vec<String>,
+    pub db: DatabaseConfig,
+    pub rate_limit: RateLimitConfig,
+    pub http: HttpConfig,
+    /// Key for the routes that need one, such as managing webhooks or
+    /// rotating the calendar feed token. Those routes refuse every request
+    /// while it is unset.
+    pub admin_api_key: Option<String>,
+    pub auto_migrate: bool,
+    /// Wall clock time zone used to read natural due dates such as `tomorrow 5pm`
+    pub timezone: Tz,
+    /// Due reminders are POSTed here when set, and only logged otherwise
+    pub reminder_webhook_url: Option<String>,
+    pub reminder_interval_seconds: u64,
+    pub webhook_interval_seconds: u64,
+    /// Attempts before a webhook delivery is given up on and marked dead
+    pub webhook_max_attempts: u32,
+    /// Lets webhooks reach loopback and private network addresses, which are
+    /// refused otherwise so that they cannot probe the internal network
+    pub webhook_allow_private_urls: bool,
+    /// How often todo changes that could not be published right away are retried
+    pub outbox_interval_seconds: u64,
+    /// How long in-flight requests and background tasks get to finish on shutdown
+    pub shutdown_timeout_seconds: u64,
+    /// How long readiness fails before the server stops accepting, for load
+    /// balancers to notice. Part of the shutdown timeout.
+    pub shutdown_drain_delay_seconds: u64,
+
+    pub app: Application,
+}
+
+static DEFAULT_REDACTED_FIELDS: &str = "description,email,password,secret,token,authorization";
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Config {
+    /// Like [`Self::load`], but panics with every problem. For tests and
+    /// tools that cannot do anything without a configuration.
+    pub fn new() -> Self {
+        Self::load().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Reads every setting, see [`Vars`] for where from, and reports all the
+    /// missing or invalid ones at once.
+    pub fn load() -> Result<Self, ConfigError> {
+        Self::from_vars(Vars::load())
+    }
+
+    pub fn from_vars(mut vars: Vars) -> Result<Self, ConfigError> {
+        let config = Self {
+            env: vars.required("ENV"),
+            port: vars.or("PORT", 4242),
+            metrics_port: vars.optional("METRICS_PORT"),
+            otlp_endpoint: vars.optional("OTEL_EXPORTER_OTLP_ENDPOINT"),
+            redacted_fields: vars.list("LOG_REDACTED_FIELDS", DEFAULT_REDACTED_FIELDS),
+            db: DatabaseConfig::from_vars(&mut vars),
+            rate_limit: RateLimitConfig::from_vars(&mut vars),
+            http: HttpConfig::from_vars(&mut vars),
+            admin_api_key: vars.optional("ADMIN_API_KEY"),
+            auto_migrate: vars.or("AUTO_MIGRATE", false),
+            timezone: vars.or("TIMEZONE", Tz::UTC),
+            reminder_webhook_url: vars.optional("REMINDER_WEBHOOK_URL"),
+            reminder_interval_seconds: vars.or("REMINDER_INTERVAL_SECONDS", 30),
+            webhook_interval_seconds: vars.or("WEBHOOK_INTERVAL_SECONDS", 10),
+            webhook_max_attempts: vars.or("WEBHOOK_MAX_ATTEMPTS", 8),
+            webhook_allow_private_urls: vars.or("WEBHOOK_ALLOW_PRIVATE_URLS", false),
+            outbox_interval_seconds: vars.or("OUTBOX_INTERVAL_SECONDS", 30),
+            shutdown_timeout_seconds: vars.or("SHUTDOWN_TIMEOUT_SECONDS", 20),
+            shutdown_drain_delay_seconds: vars.or("SHUTDOWN_DRAIN_DELAY_SECONDS", 5),
+            app: Application {
+                name: vars.required("APP_NAME"),
+                version: vars.required("APP_VERSION"),
+            },
+        };
+
+        // Background tasks tick on these, and a tick cannot take no time.
+        for (name, seconds) in [
+            (
+                "REMINDER_INTERVAL_SECONDS",
+                config.reminder_interval_seconds,
+            ),
+            ("WEBHOOK_INTERVAL_SECONDS", config.webhook_interval_seconds),
+            ("OUTBOX_INTERVAL_SECONDS", config.outbox_interval_seconds),
+        ] {
+            if seconds == 0 {
+                vars.invalid(name, "must be at least 1");
+            }
+        }
+
+        if config.metrics_port == Some(config.port) {
+            vars.invalid("METRICS_PORT", "must not be the same as PORT");
+        }
+
+        if config.shutdown_drain_delay_seconds > 0
+            && config.shutdown_drain_delay_seconds >= config.shutdown_timeout_seconds
+        {
+            vars.invalid(
+                "SHUTDOWN_DRAIN_DELAY_SECONDS",
+                "must be shorter than SHUTDOWN_TIMEOUT_SECONDS",
+            );
+        }
+
+        vars.finish(config)
+    }
+}
@@ -0,0 +1,224 @@
+use std::{collections::HashMap, env, fmt::Display, fs, str::FromStr};
+
+use toml::Value;
+
+use crate::ConfigError;
+
+/// Names the optional TOML file that settings are also read from.
+pub static CONFIG_FILE_VAR: &str = "CONFIG_FILE";
+
+/// Suffix of the variables naming a file that holds the value, as Docker
+/// mounts secrets.
+static FILE_SUFFIX: &str = "_FILE";
+
+enum Lookup {
+    Found(String),
+    Missing,
+    /// The problem is already recorded.
+    Failed,
+}
+
+/// Reads settings by variable name, recording every problem instead of
+/// stopping at the first. A variable `NAME` is looked up, in order, as
+/// - the environment variable, `.env` included,
+/// - the content of the file named by `NAME_FILE`,
+/// - the `name` key of the TOML file named by `CONFIG_FILE`.
+///
+/// An empty value counts as unset.
+pub struct Vars {
+    env: HashMap<String, String>,
+    file: HashMap<String, String>,
+    problems: Vec<String>,
+}
+
+impl Vars {
+    /// Reads the process environment, after loading `.env` into it.
+    pub fn load() -> Self {
+        dotenvy::dotenv().ok();
+
+        Self::new(env::vars().collect())
+    }
+
+    pub fn new(env: HashMap<String, String>) -> Self {
+        let mut vars = Self {
+            env,
+            file: HashMap::new(),
+            problems: vec![],
+        };
+
+        if let Some(path) = vars.env_value(CONFIG_FILE_VAR) {
+            match read_toml(&path) {
+                Ok(file) => vars.file = file,
+                Err(problem) => vars.problems.push(problem),
+            }
+        }
+
+        vars
+    }
+
+    /// The value is only a placeholder when the variable is missing or
+    /// invalid, [`Self::finish`] fails then.
+    pub fn required<T>(&mut self, name: &str) -> T
+    where
+        T: FromStr + Default,
+        T::Err: Display,
+    {
+        match self.lookup(name) {
+            Lookup::Found(value) => self.parse(name, &value).unwrap_or_default(),
+            Lookup::Missing => {
+                self.problems.push(format!("{} is required", name));
+
+                T::default()
+            }
+            Lookup::Failed => T::default(),
+        }
+    }
+
+    pub fn or<T>(&mut self, name: &str, default: T) -> T
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.optional(name).unwrap_or(default)
+    }
+
+    pub fn optional<T>(&mut self, name: &str) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        match self.lookup(name) {
+            Lookup::Found(value) => self.parse(name, &value),
+            Lookup::Missing | Lookup::Failed => None,
+        }
+    }
+
+    /// A comma separated list, without blank items.
+    pub fn list(&mut self, name: &str, default: &str) -> Vec<String> {
+        let value = match self.lookup(name) {
+            Lookup::Found(value) => value,
+            Lookup::Missing | Lookup::Failed => default.to_owned(),
+        };
+
+        value
+            .split(',')
+            .map(|item| item.trim().to_owned())
+            .filter(|item| !item.is_empty())
+            .collect()
+    }
+
+    /// Records a problem with a setting that was read fine on its own, but
+    /// does not go with others.
+    pub fn invalid(&mut self, name: &str, reason: &str) {
+        self.problems
+            .push(format!("{} is invalid: {}", name, reason));
+    }
+
+    /// `value` when no problem was recorded.
+    pub fn finish<T>(self, value: T) -> Result<T, ConfigError> {
+        if self.problems.is_empty() {
+            Ok(value)
+        } else {
+            Err(ConfigError {
+                problems: self.problems,
+            })
+        }
+    }
+
+    fn lookup(&mut self, name: &str) -> Lookup {
+        if let Some(value) = self.env_value(name) {
+            return Lookup::Found(value);
+        }
+
+        let file_var = format!("{}{}", name, FILE_SUFFIX);
+
+        if let Some(path) = self.env_value(&file_var) {
+            return match fs::read_to_string(&path) {
+                Ok(content) => {
+                    let value = content.trim_end_matches(['\r', '\n']);
+
+                    if value.is_empty() {
+                        Lookup::Missing
+                    } else {
+                        Lookup::Found(value.to_owned())
+                    }
+                }
+                Err(err) => {
+                    self.problems.push(format!(
+                        "{} names {}, which cannot be read: {}",
+                        file_var, path, err
+                    ));
+
+                    Lookup::Failed
+                }
+            };
+        }
+
+        match self.file.get(&name.to_lowercase()) {
+            Some(value) if !value.is_empty() => Lookup::Found(value.clone()),
+            _ => Lookup::Missing,
+        }
+    }
+
+    fn env_value(&self, name: &str) -> Option<String> {
+        self.env
+            .get(name)
+            .filter(|value| !value.is_empty())
+            .cloned()
+    }
+
+    // The value is left out of the problem, it may be a secret.
+    fn parse<T>(&mut self, name: &str, value: &str) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        match value.parse() {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.problems.push(format!("{} is invalid: {}", name, err));
+
+                None
+            }
+        }
+    }
+}
+
+/// The top level keys of the file, as the strings an environment variable
+/// would hold. Lists are joined with commas.
+fn read_toml(path: &str) -> Result<HashMap<String, String>, String> {
+    let content = fs::read_to_string(path).map_err(|err| {
+        format!(
+            "{} names {}, which cannot be read: {}",
+            CONFIG_FILE_VAR, path, err
+        )
+    })?;
+    let table: toml::value::Table =
+        toml::from_str(&content).map_err(|err| format!("{} is not valid TOML: {}", path, err))?;
+
+    table
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                Value::String(value) => value,
+                Value::Array(items) => items
+                    .into_iter()
+                    .map(|item| match item {
+                        Value::String(item) => item,
+                        item => item.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(","),
+                Value::Table(_) => {
+                    return Err(format!(
+                        "{} must only hold top level settings, {} is a table",
+                        path, key
+                    ))
+                }
+                value => value.to_string(),
+            };
+
+            Ok((key.to_lowercase(), value))
+        })
+        .collect()
+}
@@ -0,0 +1,252 @@
+use std::{collections::HashMap, env, fs, path::PathBuf};
+
+use chrono_tz::Tz;
+use config::{Config, Environment, RateLimitBackend, RateLimitRule, RateLimitScope, Vars};
+
+fn vars(pairs: &[(&str, &str)]) -> Vars {
+    Vars::new(
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect::<HashMap<_, _>>(),
+    )
+}
+
+/// A file in the temp directory, unique to the test.
+fn temp_file(name: &str, content: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("config_test_{}_{}", std::process::id(), name));
+    fs::write(&path, content).unwrap();
+
+    path
+}
+
+static REQUIRED: [(&str, &str); 8] = [
+    ("ENV", "prod"),
+    ("APP_NAME", "Todo"),
+    ("APP_VERSION", "1.2.3"),
+    ("SURREALDB_URL", "db.example.com"),
+    ("SURREALDB_NAMESPACE", "todo"),
+    ("SURREALDB_DATABASE", "todo"),
+    ("SURREALDB_USERNAME", "root"),
+    ("SURREALDB_PASSWORD", "hunter2"),
+];
+
+#[test]
+fn loads_settings_with_defaults() {
+    let config = Config::from_vars(vars(&REQUIRED)).unwrap();
+
+    assert_eq!(config.env, Environment::Production);
+    assert_eq!(config.app.name, "Todo");
+    assert_eq!(config.db.url, "db.example.com");
+    assert_eq!(config.db.connect_attempts, 10);
+    assert_eq!(config.port, 4242);
+    assert_eq!(config.metrics_port, None);
+    assert_eq!(config.timezone, Tz::UTC);
+    assert!(!config.auto_migrate);
+}
+
+#[test]
+fn reports_every_missing_or_invalid_setting_at_once() {
+    let err = Config::from_vars(vars(&[
+        ("ENV", "staging"),
+        ("PORT", "http"),
+        ("TIMEZONE", "Mars/Olympus"),
+        ("APP_NAME", "Todo"),
+        ("SURREALDB_URL", ""),
+    ]))
+    .unwrap_err();
+
+    assert_eq!(
+        err.problems,
+        vec![
+            "ENV is invalid: expected one of prod, dev or test",
+            "PORT is invalid: invalid digit found in string",
+            "SURREALDB_URL is required",
+            "SURREALDB_NAMESPACE is required",
+            "SURREALDB_DATABASE is required",
+            "SURREALDB_USERNAME is required",
+            "SURREALDB_PASSWORD is required",
+            "TIMEZONE is invalid: 'Mars/Olympus' is not a valid timezone",
+            "APP_VERSION is required",
+        ]
+    );
+}
+
+#[test]
+fn reads_secrets_from_files() {
+    let password = temp_file("password", "s3cr3t\n");
+    let mut pairs: Vec<(&str, &str)> = REQUIRED
+        .into_iter()
+        .filter(|(name, _)| *name != "SURREALDB_PASSWORD")
+        .collect();
+    let password_path = password.to_string_lossy().to_string();
+    pairs.push(("SURREALDB_PASSWORD_FILE", &password_path));
+    pairs.push(("SURREALDB_USERNAME_FILE", "/does/not/exist"));
+
+    let config = Config::from_vars(vars(&pairs)).unwrap();
+
+    // The variable itself wins over its file.
+    assert_eq!(config.db.username, "root");
+    assert_eq!(config.db.password, "s3cr3t");
+
+    pairs.retain(|(name, _)| *name != "SURREALDB_USERNAME");
+    let err = Config::from_vars(vars(&pairs)).unwrap_err();
+
+    assert_eq!(err.problems.len(), 1);
+    assert!(err.problems[0].starts_with("SURREALDB_USERNAME_FILE names /does/not/exist"));
+}
+
+#[test]
+fn reads_a_toml_file_under_the_environment() {
+    let file = temp_file(
+        "config.toml",
+        r#"
+        app_name = "Todo"
+        app_version = "1.2.3"
+        port = 8080
+        auto_migrate = true
+        log_redacted_fields = ["email", "token"]
+        surrealdb_url = "from-file"
+        "#,
+    );
+    let file_path = file.to_string_lossy().to_string();
+    let pairs: Vec<(&str, &str)> = REQUIRED
+        .into_iter()
+        .filter(|(name, _)| !name.starts_with("APP_"))
+        .chain([("CONFIG_FILE", file_path.as_str())])
+        .collect();
+
+    let config = Config::from_vars(vars(&pairs)).unwrap();
+
+    assert_eq!(config.app.name, "Todo");
+    assert_eq!(config.port, 8080);
+    assert!(config.auto_migrate);
+    assert_eq!(config.redacted_fields, vec!["email", "token"]);
+    assert_eq!(config.db.url, "db.example.com");
+}
+
+#[test]
+fn reports_an_unusable_toml_file() {
+    let file = temp_file("nested.toml", "[database]\nurl = \"db\"\n");
+    let file_path = file.to_string_lossy().to_string();
+    let pairs: Vec<(&str, &str)> = REQUIRED
+        .into_iter()
+        .chain([("CONFIG_FILE", file_path.as_str())])
+        .collect();
+
+    let err = Config::from_vars(vars(&pairs)).unwrap_err();
+
+    assert_eq!(
+        err.problems,
+        vec![format!(
+            "{} must only hold top level settings, database is a table",
+            file_path
+        )]
+    );
+}
+
+#[test]
+fn reads_rate_limits_per_route_group() {
+    let mut pairs = REQUIRED.to_vec();
+    pairs.push(("RATE_LIMIT_BACKEND", "surrealdb"));
+    pairs.push(("RATE_LIMITS", "default.ip=100/60, todos.key=10/1"));
+    let config = Config::from_vars(vars(&pairs)).unwrap();
+
+    assert_eq!(config.rate_limit.backend, RateLimitBackend::SurrealDb);
+    assert_eq!(
+        config.rate_limit.rules,
+        vec![
+            RateLimitRule {
+                group: String::from("default"),
+                scope: RateLimitScope::Ip,
+                requests: 100,
+                period_seconds: 60,
+            },
+            RateLimitRule {
+                group: String::from("todos"),
+                scope: RateLimitScope::ApiKey,
+                requests: 10,
+                period_seconds: 1,
+            },
+        ]
+    );
+
+    let mut pairs = REQUIRED.to_vec();
+    pairs.push(("RATE_LIMITS", "todos.ip=ten/60"));
+    let err = Config::from_vars(vars(&pairs)).unwrap_err();
+
+    assert_eq!(
+        err.problems,
+        vec!["RATE_LIMITS is invalid: todos.ip=ten/60 is not written group.scope=requests/seconds"]
+    );
+
+    let mut pairs = REQUIRED.to_vec();
+    pairs.push(("RATE_LIMITS", "todos.user=10/60"));
+    let err = Config::from_vars(vars(&pairs)).unwrap_err();
+
+    assert_eq!(
+        err.problems,
+        vec![
+            "RATE_LIMITS is invalid: user limits are not supported, requests are not made by users"
+        ]
+    );
+}
+
+#[test]
+fn refuses_credentials_for_any_origin() {
+    let mut pairs = REQUIRED.to_vec();
+    pairs.push(("CORS_ALLOWED_ORIGINS", "https://todo.example.com,*"));
+    pairs.push(("CORS_ALLOWED_METHODS", "get,post"));
+    let config = Config::from_vars(vars(&pairs)).unwrap();
+
+    assert_eq!(config.http.cors_allowed_methods, vec!["GET", "POST"]);
+    assert_eq!(config.http.body_limit_bytes, 2 * 1024 * 1024);
+
+    pairs.push(("CORS_ALLOW_CREDENTIALS", "true"));
+    pairs.push(("CORS_ALLOWED_METHODS", "GET,P O S T"));
+    let err = Config::from_vars(vars(&pairs)).unwrap_err();
+
+    assert_eq!(
+        err.problems,
+        vec![
+            "CORS_ALLOWED_METHODS is invalid: P O S T is not an HTTP method",
+            "CORS_ALLOWED_ORIGINS is invalid: cannot allow any origin while CORS_ALLOW_CREDENTIALS is on",
+        ]
+    );
+}
+
+#[test]
+fn drains_within_the_shutdown_timeout() {
+    let config = Config::from_vars(vars(&REQUIRED)).unwrap();
+
+    assert_eq!(config.shutdown_drain_delay_seconds, 5);
+
+    let mut pairs = REQUIRED.to_vec();
+    pairs.push(("SHUTDOWN_TIMEOUT_SECONDS", "10"));
+    pairs.push(("SHUTDOWN_DRAIN_DELAY_SECONDS", "10"));
+    let err = Config::from_vars(vars(&pairs)).unwrap_err();
+
+    assert_eq!(
+        err.problems,
+        vec!["SHUTDOWN_DRAIN_DELAY_SECONDS is invalid: must be shorter than SHUTDOWN_TIMEOUT_SECONDS"]
+    );
+}
+
+#[test]
+fn refuses_zero_intervals_and_a_shared_metrics_port() {
+    let mut pairs = REQUIRED.to_vec();
+    pairs.push(("DB_HEALTH_INTERVAL_SECONDS", "0"));
+    pairs.push(("WEBHOOK_INTERVAL_SECONDS", "0"));
+    pairs.push(("PORT", "8080"));
+    pairs.push(("METRICS_PORT", "8080"));
+    let err = Config::from_vars(vars(&pairs)).unwrap_err();
+
+    assert_eq!(
+        err.problems,
+        vec![
+            "DB_HEALTH_INTERVAL_SECONDS is invalid: must be at least 1",
+            "WEBHOOK_INTERVAL_SECONDS is invalid: must be at least 1",
+            "METRICS_PORT is invalid: must not be the same as PORT",
+        ]
+    );
+}
@@ -1,3 +1,4 @@
+use config::{DatabaseConfig, Environment};
 use surrealdb::{
     engine::remote::ws::{Client, Ws, Wss},
     opt::auth::Root,
@@ -5,46 +6,44 @@ use surrealdb::{
 };
 use tracing::info;
 
-use crate::config::Environment;
-
-use super::Config;
-
 pub struct DatabaseDriver {
     pub client: Surreal<Client>,
 }
 
 impl DatabaseDriver {
-    pub async fn init(config: &Config) -> Result<Self, ()> {
-        let client = match config.env {
-            Environment::Production => Surreal::new::<Wss>(&config.db_url)
-                .await
-                .expect("Unable to connect to DB!"),
-            _ => Surreal::new::<Ws>(&config.db_url)
-                .await
-                .expect("Unable to connect to test DB!"),
-        };
-
-        info!("Connected to the Database on {}", &config.db_url);
+    pub async fn init(env: &Environment, config: &DatabaseConfig) -> Result<Self, String> {
+        let client = match env {
+            Environment::Production => Surreal::new::<Wss>(&config.url).await,
+            _ => Surreal::new::<Ws>(&config.url).await,
+        }
+        .map_err(|err| {
+            format!(
+                "Unable to connect to the database on {}: {}",
+                config.url, err
+            )
+        })?;
+
+        info!("Connected to the Database on {}", &config.url);
 
         client
             .signin(Root {
-                username: &config.db_username,
-                password: &config.db_password,
+                username: &config.username,
+                password: &config.password,
             })
             .await
-            .expect("Failed to authorize DB access!");
+            .map_err(|err| format!("Failed to authorize database access: {}", err))?;
 
-        info!("Database access granted to {}", &config.db_username);
+        info!("Database access granted to {}", &config.username);
 
         client
-            .use_ns(&config.db_namespace)
-            .use_db(&config.db_name)
+            .use_ns(&config.namespace)
+            .use_db(&config.name)
             .await
-            .expect("Unable to config namespace!");
+            .map_err(|err| format!("Unable to use the namespace and database: {}", err))?;
 
         info!(
             "Using {} namespace and {} database",
-            &config.db_namespace, &config.db_name
+            &config.namespace, &config.name
         );
 
         Ok(Self { client })
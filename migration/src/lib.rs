@@ -0,0 +1,5 @@
+pub mod migrations;
+pub mod migrator;
+
+pub use migrations::*;
+pub use migrator::*;
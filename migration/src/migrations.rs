@@ -0,0 +1,248 @@
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    pub statements: &'static str,
+}
+
+pub static MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "create_todo_table",
+        statements: r#"
+    DEFINE TABLE todo SCHEMAFULL;
+
+    DEFINE FIELD id ON todo TYPE record;// DEFAULT rand::ulid();
+    DEFINE FIELD subject ON todo TYPE string;
+    DEFINE FIELD description ON todo TYPE string;
+    DEFINE FIELD due_date ON todo TYPE datetime;
+    DEFINE FIELD is_done ON todo TYPE bool DEFAULT false;
+    DEFINE FIELD created_at ON todo TYPE datetime;
+    DEFINE FIELD updated_at ON todo TYPE datetime VALUE (
+        IF $value < time::now() THEN
+            time::now()
+        ELSE
+            $value
+        END
+    );
+    "#,
+    },
+    Migration {
+        version: 2,
+        name: "create_todo_index",
+        statements: r#"
+    // DEFINE INDEX todoSearchIndex ON TABLE todo COLUMNS subject SEARCH ANALYZER ascii BM25 HIGHLIGHTS;
+    // DEFINE ANALYZER english TOKENIZERS class FILTERS snowball(english);
+    // DEFINE ANALYZER todo_search TOKENIZERS class FILTERS ascii;
+
+    DEFINE ANALYZER todo_search TOKENIZERS class FILTERS ascii, snowball(english);
+    DEFINE INDEX todo_subject_index
+        ON todo FIELDS subject
+        SEARCH
+        ANALYZER todo_search
+        BM25(1.2, 0.75);
+
+    DEFINE INDEX todo_description_index
+        ON todo FIELDS description
+        SEARCH
+        ANALYZER todo_search
+        BM25(1.2, 0.75);
+    "#,
+    },
+    Migration {
+        version: 3,
+        name: "create_calendar_feed_table",
+        statements: r#"
+    DEFINE TABLE calendar_feed SCHEMAFULL;
+
+    DEFINE FIELD token_hash ON calendar_feed TYPE string;
+    DEFINE FIELD created_at ON calendar_feed TYPE datetime;
+
+    DEFINE INDEX calendar_feed_token_index ON calendar_feed FIELDS token_hash UNIQUE;
+    "#,
+    },
+    Migration {
+        version: 4,
+        name: "enable_todo_search_highlights",
+        statements: r#"
+    REMOVE INDEX todo_subject_index ON TABLE todo;
+    REMOVE INDEX todo_description_index ON TABLE todo;
+
+    DEFINE INDEX todo_subject_index
+        ON todo FIELDS subject
+        SEARCH
+        ANALYZER todo_search
+        BM25(1.2, 0.75)
+        HIGHLIGHTS;
+
+    DEFINE INDEX todo_description_index
+        ON todo FIELDS description
+        SEARCH
+        ANALYZER todo_search
+        BM25(1.2, 0.75)
+        HIGHLIGHTS;
+    "#,
+    },
+    Migration {
+        version: 5,
+        name: "create_todo_suggest_index",
+        statements: r#"
+    DEFINE ANALYZER todo_suggest TOKENIZERS blank, class FILTERS lowercase, ascii, edgengram(1, 15);
+
+    // A field can only be served by one search index, so the prefix index gets
+    // its own copy of the subject.
+    DEFINE FIELD subject_suggest ON todo TYPE string VALUE subject;
+
+    DEFINE INDEX todo_subject_suggest_index
+        ON todo FIELDS subject_suggest
+        SEARCH
+        ANALYZER todo_suggest
+        BM25(1.2, 0.75);
+
+    // Backfill existing todos without bumping their updated_at.
+    DEFINE FIELD updated_at ON todo TYPE datetime;
+    UPDATE todo;
+    DEFINE FIELD updated_at ON todo TYPE datetime VALUE (
+        IF $value < time::now() THEN
+            time::now()
+        ELSE
+            $value
+        END
+    );
+    "#,
+    },
+    Migration {
+        version: 6,
+        name: "create_saved_view_table",
+        statements: r#"
+    DEFINE TABLE saved_view SCHEMAFULL;
+
+    DEFINE FIELD name ON saved_view TYPE string;
+    DEFINE FIELD query ON saved_view TYPE string;
+    DEFINE FIELD created_at ON saved_view TYPE datetime;
+    DEFINE FIELD updated_at ON saved_view TYPE datetime;
+    "#,
+    },
+    Migration {
+        version: 7,
+        name: "add_todo_timezone_and_all_day",
+        statements: r#"
+    // Todos without a zone are read in the server's TIMEZONE.
+    DEFINE FIELD timezone ON todo TYPE option<string>;
+    DEFINE FIELD all_day ON todo TYPE bool DEFAULT false;
+
+    // Backfill existing todos without bumping their updated_at.
+    DEFINE FIELD updated_at ON todo TYPE datetime;
+    UPDATE todo SET all_day = false WHERE all_day = NONE;
+    DEFINE FIELD updated_at ON todo TYPE datetime VALUE (
+        IF $value < time::now() THEN
+            time::now()
+        ELSE
+            $value
+        END
+    );
+    "#,
+    },
+    Migration {
+        version: 8,
+        name: "create_reminder_table",
+        statements: r#"
+    // Minutes before the due date, the reminder table holds when each fires.
+    DEFINE FIELD reminders ON todo TYPE array<int> DEFAULT [];
+    DEFINE FIELD reminders.* ON todo TYPE int;
+
+    DEFINE TABLE reminder SCHEMAFULL;
+
+    DEFINE FIELD todo ON reminder TYPE record<todo>;
+    DEFINE FIELD remind_at ON reminder TYPE datetime;
+    DEFINE FIELD claimed_by ON reminder TYPE option<string>;
+    DEFINE FIELD claimed_at ON reminder TYPE option<datetime>;
+    DEFINE FIELD sent_at ON reminder TYPE option<datetime>;
+
+    DEFINE INDEX reminder_remind_at_index ON reminder FIELDS remind_at;
+    DEFINE INDEX reminder_todo_index ON reminder FIELDS todo;
+
+    // Backfill existing todos without bumping their updated_at.
+    DEFINE FIELD updated_at ON todo TYPE datetime;
+    UPDATE todo SET reminders = [] WHERE reminders = NONE;
+    DEFINE FIELD updated_at ON todo TYPE datetime VALUE (
+        IF $value < time::now() THEN
+            time::now()
+        ELSE
+            $value
+        END
+    );
+    "#,
+    },
+    Migration {
+        version: 9,
+        name: "create_webhook_tables",
+        statements: r#"
+    DEFINE TABLE webhook SCHEMAFULL;
+
+    DEFINE FIELD url ON webhook TYPE string;
+    DEFINE FIELD events ON webhook TYPE array<string>;
+    DEFINE FIELD events.* ON webhook TYPE string;
+    DEFINE FIELD secret ON webhook TYPE string;
+    DEFINE FIELD created_at ON webhook TYPE datetime;
+    DEFINE FIELD updated_at ON webhook TYPE datetime;
+
+    // The payload is the exact JSON text that is signed, so retries match.
+    DEFINE TABLE webhook_delivery SCHEMAFULL;
+
+    DEFINE FIELD webhook ON webhook_delivery TYPE record<webhook>;
+    DEFINE FIELD event ON webhook_delivery TYPE string;
+    DEFINE FIELD payload ON webhook_delivery TYPE string;
+    DEFINE FIELD status ON webhook_delivery TYPE string
+        ASSERT $value INSIDE ['pending', 'delivered', 'dead'];
+    DEFINE FIELD attempts ON webhook_delivery TYPE int;
+    DEFINE FIELD next_attempt_at ON webhook_delivery TYPE datetime;
+    DEFINE FIELD last_status_code ON webhook_delivery TYPE option<int>;
+    DEFINE FIELD last_error ON webhook_delivery TYPE option<string>;
+    DEFINE FIELD claimed_by ON webhook_delivery TYPE option<string>;
+    DEFINE FIELD claimed_at ON webhook_delivery TYPE option<datetime>;
+    DEFINE FIELD created_at ON webhook_delivery TYPE datetime;
+    DEFINE FIELD delivered_at ON webhook_delivery TYPE option<datetime>;
+
+    DEFINE INDEX webhook_delivery_due_index ON webhook_delivery FIELDS status, next_attempt_at;
+    DEFINE INDEX webhook_delivery_webhook_index ON webhook_delivery FIELDS webhook;
+    "#,
+    },
+    Migration {
+        version: 10,
+        name: "create_outbox_table",
+        statements: r#"
+    // Written in the same transaction as the todo change it records.
+    DEFINE TABLE outbox SCHEMAFULL;
+
+    DEFINE FIELD change ON outbox TYPE string
+        ASSERT $value INSIDE ['created', 'updated', 'deleted'];
+    DEFINE FIELD before ON outbox FLEXIBLE TYPE option<object>;
+    DEFINE FIELD after ON outbox FLEXIBLE TYPE option<object>;
+    DEFINE FIELD occurred_at ON outbox TYPE datetime;
+    DEFINE FIELD claimed_by ON outbox TYPE option<string>;
+    DEFINE FIELD claimed_at ON outbox TYPE option<datetime>;
+    DEFINE FIELD processed_at ON outbox TYPE option<datetime>;
+
+    DEFINE INDEX outbox_unprocessed_index ON outbox FIELDS processed_at, occurred_at;
+    "#,
+    },
+    Migration {
+        version: 11,
+        name: "create_rate_limit_table",
+        statements: r#"
+    // One token bucket per client and route group, shared by every instance.
+    // A bucket is full again at expires_at, so it can be deleted from then on.
+    DEFINE TABLE rate_limit SCHEMAFULL;
+
+    DEFINE FIELD tokens ON rate_limit TYPE float;
+    DEFINE FIELD updated_at ON rate_limit TYPE datetime;
+    DEFINE FIELD expires_at ON rate_limit TYPE datetime;
+
+    DEFINE INDEX rate_limit_expiry_index ON rate_limit FIELDS expires_at;
+    "#,
+    },
+];
+
+pub fn latest_version() -> u32 {
+    MIGRATIONS.iter().map(|m| m.version).max().unwrap_or(0)
+}
@@ -0,0 +1,210 @@
+use std::{fmt::Display, time::Duration};
+
+use serde::Deserialize;
+use surrealdb::{engine::remote::ws::Client, sql::Value, Surreal};
+use tracing::{info, warn};
+use ulid::Ulid;
+
+use crate::migrations::{latest_version, Migration, MIGRATIONS};
+
+static LOCK_RETRY_INTERVAL: Duration = Duration::from_secs(2);
+static LOCK_WAIT_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Debug)]
+pub enum MigrationError {
+    Database(String),
+    LockTimeout,
+    SchemaAhead { database: u32, binary: u32 },
+    Failed { version: u32, reason: String },
+}
+
+pub type MigrationResult<T> = Result<T, MigrationError>;
+
+#[derive(Deserialize)]
+struct AppliedMigration {
+    version: u32,
+}
+
+pub struct Migrator<'a> {
+    client: &'a Surreal<Client>,
+    owner: String,
+}
+
+impl<'a> Migrator<'a> {
+    pub fn new(client: &'a Surreal<Client>) -> Self {
+        Self {
+            client,
+            owner: Ulid::new().to_string(),
+        }
+    }
+
+    /// Applies every pending migration while holding the migration lock, so that
+    /// only one of several instances booting together touches the schema.
+    pub async fn run(&self) -> MigrationResult<Vec<u32>> {
+        self.acquire_lock().await?;
+
+        let result = self.apply_pending().await;
+
+        if let Err(err) = self.release_lock().await {
+            warn!("Unable to release migration lock: {}", err);
+        }
+
+        result
+    }
+
+    /// Fails when the database has been migrated by a newer binary than this one.
+    pub async fn verify(&self) -> MigrationResult<u32> {
+        let current = self.current_version().await?;
+        let binary = latest_version();
+
+        if current > binary {
+            return Err(MigrationError::SchemaAhead {
+                database: current,
+                binary,
+            });
+        }
+
+        Ok(current)
+    }
+
+    pub async fn current_version(&self) -> MigrationResult<u32> {
+        let applied = self.applied_versions().await?;
+
+        Ok(applied.into_iter().max().unwrap_or(0))
+    }
+
+    async fn apply_pending(&self) -> MigrationResult<Vec<u32>> {
+        self.verify().await?;
+
+        let applied = self.applied_versions().await?;
+        let pending: Vec<&Migration> = MIGRATIONS
+            .iter()
+            .filter(|m| !applied.contains(&m.version))
+            .collect();
+
+        if pending.is_empty() {
+            info!("Database schema is up to date");
+        }
+
+        let mut versions = vec![];
+
+        for migration in pending {
+            self.apply(migration).await?;
+
+            info!(
+                "Applied migration {} ({})",
+                migration.version, migration.name
+            );
+            versions.push(migration.version);
+        }
+
+        Ok(versions)
+    }
+
+    async fn apply(&self, migration: &Migration) -> MigrationResult<()> {
+        let query = format!(
+            r#"
+            BEGIN TRANSACTION;
+            {}
+            CREATE type::thing('migration', $version) CONTENT {{
+                version: $version,
+                name: $name,
+                applied_at: time::now(),
+            }};
+            COMMIT TRANSACTION;
+            "#,
+            migration.statements
+        );
+
+        self.client
+            .query(query)
+            .bind(("version", migration.version))
+            .bind(("name", migration.name))
+            .await
+            .and_then(|response| response.check())
+            .map_err(|err| MigrationError::Failed {
+                version: migration.version,
+                reason: err.to_string(),
+            })?;
+
+        Ok(())
+    }
+
+    async fn applied_versions(&self) -> MigrationResult<Vec<u32>> {
+        let mut response = self.client.query("SELECT version FROM migration").await?;
+
+        let applied: Vec<AppliedMigration> = response.take(0)?;
+
+        Ok(applied.into_iter().map(|m| m.version).collect())
+    }
+
+    async fn acquire_lock(&self) -> MigrationResult<()> {
+        let query = r#"
+            DELETE migration_lock:global WHERE expires_at < time::now();
+            CREATE migration_lock:global CONTENT {
+                owner: $owner,
+                acquired_at: time::now(),
+                expires_at: time::now() + 5m,
+            };
+        "#;
+
+        let mut waited = Duration::ZERO;
+
+        loop {
+            let mut response = self
+                .client
+                .query(query)
+                .bind(("owner", &self.owner))
+                .await?;
+
+            if response.take::<Value>(1).is_ok() {
+                info!("Acquired migration lock as {}", &self.owner);
+
+                return Ok(());
+            }
+
+            if waited >= LOCK_WAIT_TIMEOUT {
+                return Err(MigrationError::LockTimeout);
+            }
+
+            info!("Migration lock is held by another instance, waiting...");
+            tokio::time::sleep(LOCK_RETRY_INTERVAL).await;
+            waited += LOCK_RETRY_INTERVAL;
+        }
+    }
+
+    async fn release_lock(&self) -> MigrationResult<()> {
+        self.client
+            .query("DELETE migration_lock:global WHERE owner = $owner")
+            .bind(("owner", &self.owner))
+            .await?
+            .check()?;
+
+        Ok(())
+    }
+}
+
+impl From<surrealdb::Error> for MigrationError {
+    fn from(value: surrealdb::Error) -> Self {
+        MigrationError::Database(value.to_string())
+    }
+}
+
+impl Display for MigrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MigrationError::Database(err) => write!(f, "Database error: {}", err),
+            MigrationError::LockTimeout => {
+                write!(f, "Timed out waiting for the migration lock")
+            }
+            MigrationError::SchemaAhead { database, binary } => write!(
+                f,
+                "Database schema is at version {} but this binary only knows up to version {}",
+                database, binary
+            ),
+            MigrationError::Failed { version, reason } => {
+                write!(f, "Migration {} failed: {}", version, reason)
+            }
+        }
+    }
+}
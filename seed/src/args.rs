@@ -0,0 +1,26 @@
+use chrono::{DateTime, Utc};
+use clap::Parser;
+
+#[derive(Parser, Debug)]
+#[command(about = "Populate the database with realistic fake todos")]
+pub struct Args {
+    /// Number of todos to insert
+    #[arg(short, long, default_value_t = 50)]
+    pub count: usize,
+
+    /// Seed for the random generator, the same seed always yields the same todos
+    #[arg(short, long, default_value_t = 42)]
+    pub seed: u64,
+
+    /// Namespace to seed, defaults to SURREALDB_NAMESPACE
+    #[arg(short, long)]
+    pub namespace: Option<String>,
+
+    /// Delete every existing todo before seeding
+    #[arg(short, long)]
+    pub wipe: bool,
+
+    /// Reference time (RFC 3339) that due dates are spread around, defaults to now
+    #[arg(long)]
+    pub now: Option<DateTime<Utc>>,
+}
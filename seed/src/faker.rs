@@ -0,0 +1,140 @@
+use std::sync::Mutex;
+
+use app::{
+    docs::v1::todos::Todo,
+    util::{Clock, IdGenerator, ParseResult, UlidGenerator},
+};
+use chrono::{DateTime, Duration, Utc};
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use ulid::Ulid;
+
+static VERBS: &[&str] = &[
+    "Buy", "Call", "Email", "Review", "Prepare", "Schedule", "Fix", "Clean", "Book", "Finish",
+    "Plan", "Renew", "Pay", "Update", "Organise",
+];
+
+static OBJECTS: &[&str] = &[
+    "groceries",
+    "the dentist",
+    "quarterly report",
+    "team offsite",
+    "car insurance",
+    "garage",
+    "flight tickets",
+    "pull request",
+    "electricity bill",
+    "passport",
+    "birthday gift",
+    "project roadmap",
+    "gym membership",
+    "landlord",
+    "kitchen sink",
+];
+
+static DETAILS: &[&str] = &[
+    "Make sure to double check everything before sending it off.",
+    "Ask around for recommendations first.",
+    "This has been on the list for a while, get it done this time.",
+    "Keep the receipt for the expense claim.",
+    "Loop in the rest of the team once it is ready.",
+    "Compare at least three options before deciding.",
+    "Needs to happen before the end of the month.",
+    "Set aside an hour in the morning for this.",
+];
+
+#[derive(Clone)]
+pub struct FixedClock {
+    now: DateTime<Utc>,
+}
+
+impl FixedClock {
+    pub fn at(now: DateTime<Utc>) -> Self {
+        Self { now }
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.now
+    }
+}
+
+/// Generates ULIDs from the clock time and a seeded random source, so that
+/// repeated runs with the same seed and reference time yield the same ids.
+pub struct SeededUlidGenerator<C: Clock> {
+    clock: C,
+    rng: Mutex<ChaCha8Rng>,
+}
+
+impl<C: Clock> SeededUlidGenerator<C> {
+    pub fn new(clock: C, seed: u64) -> Self {
+        Self {
+            clock,
+            rng: Mutex::new(ChaCha8Rng::seed_from_u64(seed)),
+        }
+    }
+}
+
+impl<C: Clock> IdGenerator<Ulid> for SeededUlidGenerator<C> {
+    fn generate(&self) -> Ulid {
+        let random = self.rng.lock().expect("RNG lock poisoned").gen();
+
+        Ulid::from_parts(self.clock.now().timestamp_millis() as u64, random)
+    }
+
+    fn parse(&self, id: &str) -> ParseResult<Ulid> {
+        UlidGenerator::new().parse(id)
+    }
+
+    fn to_string(&self, id: &Ulid) -> String {
+        id.to_string()
+    }
+}
+
+pub struct TodoFaker<C: Clock, G: IdGenerator<Ulid>> {
+    clock: C,
+    id_generator: G,
+    rng: ChaCha8Rng,
+}
+
+impl<C: Clock, G: IdGenerator<Ulid>> TodoFaker<C, G> {
+    pub fn new(clock: C, id_generator: G, seed: u64) -> Self {
+        Self {
+            clock,
+            id_generator,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn todo(&mut self) -> Todo {
+        let now = self.clock.now();
+
+        let verb = VERBS.choose(&mut self.rng).unwrap_or(&VERBS[0]);
+        let object = OBJECTS.choose(&mut self.rng).unwrap_or(&OBJECTS[0]);
+        let detail = DETAILS.choose(&mut self.rng).unwrap_or(&DETAILS[0]);
+
+        // Spread due dates from two weeks in the past to a month ahead, at
+        // quarter-hour marks, and mark most of the past ones as done.
+        let due_date = now + Duration::minutes(self.rng.gen_range(-14 * 96..30 * 96) * 15);
+        let is_done = if due_date < now {
+            self.rng.gen_bool(0.7)
+        } else {
+            self.rng.gen_bool(0.1)
+        };
+        let created_at = now - Duration::hours(self.rng.gen_range(1..24 * 21));
+
+        Todo {
+            id: self.id_generator.generate(),
+            subject: format!("{} {}", verb, object),
+            description: format!("{} {} {}", verb, object, detail.to_lowercase()),
+            is_done,
+            due_date,
+            timezone: None,
+            all_day: false,
+            reminders: vec![],
+            created_at,
+            updated_at: created_at,
+        }
+    }
+}
@@ -1,3 +1,63 @@
-fn main() {
-    println!("Hello, world!");
+use std::{fmt::Display, process};
+
+use app::{
+    common::{Config, DatabaseDriver},
+    resource::v1::todos::{TodoRepository, TodoRepositoryImpl},
+    util::{Clock, SystemClock},
+};
+use args::Args;
+use clap::Parser;
+use faker::{FixedClock, SeededUlidGenerator, TodoFaker};
+
+mod args;
+mod faker;
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    let mut config = Config::load().unwrap_or_else(|err| fail(err));
+
+    if let Some(namespace) = args.namespace {
+        config.db.namespace = namespace;
+    }
+
+    let database_driver = DatabaseDriver::init(&config)
+        .await
+        .unwrap_or_else(|err| fail(err));
+
+    if args.wipe {
+        database_driver
+            .client
+            .query("DELETE todo")
+            .await
+            .and_then(|response| response.check())
+            .unwrap_or_else(|err| fail(format!("Unable to wipe todos: {}", err)));
+
+        println!("Deleted existing todos in '{}'", &config.db.namespace);
+    }
+
+    let clock = FixedClock::at(args.now.unwrap_or_else(|| SystemClock::new().now()));
+    let id_generator = SeededUlidGenerator::new(clock.clone(), args.seed);
+    let mut faker = TodoFaker::new(clock, id_generator, args.seed);
+
+    let repository = TodoRepositoryImpl::new(database_driver);
+
+    for _ in 0..args.count {
+        let todo = faker.todo();
+
+        if let Err(err) = repository.create_todo(todo).await {
+            fail(format!("Unable to insert seeded todo: {}", err));
+        }
+    }
+
+    println!(
+        "Seeded {} todos into '{}' with seed {}",
+        args.count, &config.db.namespace, args.seed
+    );
+}
+
+fn fail(reason: impl Display) -> ! {
+    eprintln!("{}", reason);
+    process::exit(1)
 }
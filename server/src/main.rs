@@ -1,12 +1,37 @@
+use std::{env, fmt::Display, net::SocketAddr, process, time::Duration};
+
 use app::{
-    common::{Config, Constant, DatabaseDriver},
-    util::{SystemClock, SystemTelemetry, UlidGenerator},
+    common::{Config, Constant, DatabaseDriver, DatabaseMonitor},
+    resource::v1::{
+        health::Readiness,
+        reminders::{notifier_from_config, ReminderRepository, ReminderScheduler},
+        webhooks::{WebhookDispatcher, WebhookRepository},
+    },
+    util::{
+        metrics_router, CircuitBreaker, Metrics, Shutdown, SystemClock, SystemTelemetry,
+        UlidGenerator,
+    },
     AppBuilder,
 };
+use migration::Migrator;
+use tokio::time::Instant;
+use tracing::{error, info, warn};
+
+/// Validates the configuration and exits, without connecting to anything.
+static CHECK_CONFIG_FLAG: &str = "--check-config";
 
 #[tokio::main]
 async fn main() {
-    let config = Config::new();
+    let config = Config::load().unwrap_or_else(|err| unable_to_start(err));
+
+    if env::args().any(|arg| arg == CHECK_CONFIG_FLAG) {
+        println!(
+            "Configuration is valid for {} {} in {:?}",
+            config.app.name, config.app.version, config.env
+        );
+        process::exit(0);
+    }
+
     let _constant = Constant::new();
     let clock = SystemClock::new();
     let telemetry = SystemTelemetry::init(&config);
@@ -14,24 +39,180 @@ async fn main() {
 
     let database_driver = DatabaseDriver::init(&config)
         .await
-        .expect("Unable to connect to DB!");
+        .unwrap_or_else(|err| unable_to_start(err));
+
+    let migrator = Migrator::new(&database_driver.client);
+
+    if config.auto_migrate {
+        migrator.run().await.unwrap_or_else(|err| {
+            unable_to_start(format!("Unable to migrate the database: {}", err))
+        });
+    }
+
+    migrator
+        .verify()
+        .await
+        .unwrap_or_else(|err| unable_to_start(format!("Refusing to start: {}", err)));
+
+    let shutdown = Shutdown::new();
+    let readiness = Readiness::new();
+    let circuit_breaker = CircuitBreaker::default();
+
+    shutdown.track(
+        DatabaseMonitor::new(
+            database_driver.clone(),
+            config.clone(),
+            circuit_breaker.clone(),
+        )
+        .with_interval(Duration::from_secs(config.db.health_interval_seconds))
+        .with_shutdown(shutdown.clone())
+        .spawn(),
+    );
+
+    shutdown.track(
+        ReminderScheduler::new(
+            ReminderRepository::new(database_driver.clone()),
+            clock.clone(),
+            notifier_from_config(&config),
+        )
+        .with_interval(Duration::from_secs(config.reminder_interval_seconds))
+        .with_shutdown(shutdown.clone())
+        .spawn(),
+    );
+
+    shutdown.track(
+        WebhookDispatcher::new(
+            WebhookRepository::new(database_driver.clone()),
+            clock.clone(),
+        )
+        .with_interval(Duration::from_secs(config.webhook_interval_seconds))
+        .with_max_attempts(config.webhook_max_attempts)
+        .with_private_urls(config.webhook_allow_private_urls)
+        .with_shutdown(shutdown.clone())
+        .spawn(),
+    );
+
+    let metrics = Metrics::new();
+
+    if let Some(port) = config.metrics_port {
+        let addr = SocketAddr::from(([0, 0, 0, 0], port));
+
+        let metrics_server = axum::Server::try_bind(&addr)
+            .unwrap_or_else(|err| unable_to_listen(&addr, err))
+            .serve(metrics_router(metrics.clone()).into_make_service())
+            .with_graceful_shutdown(stopped(shutdown.clone()));
+
+        info!("Serving metrics on {}", &addr);
+
+        tokio::spawn(async move {
+            if let Err(err) = metrics_server.await {
+                error!("Metrics server on {} stopped: {}", &addr, err);
+            }
+        });
+    }
 
     let app = AppBuilder::new()
         .config(config.clone())
+        .metrics(metrics)
+        .readiness(readiness.clone())
+        .shutdown(shutdown.clone())
+        .circuit_breaker(circuit_breaker)
         .clock(clock)
-        .database_driver(database_driver)
+        .database_driver(database_driver.clone())
         .telemetry(telemetry)
         .id_generator(id_generator)
+        .outbox_relay_interval(Duration::from_secs(config.outbox_interval_seconds))
         .build()
         .await
-        .unwrap_or_else(|_| panic!("Unable to build App"));
+        .unwrap_or_else(|err| unable_to_start(format!("Unable to build the app: {}", err)));
 
-    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], config.port));
+    let addr = SocketAddr::from(([0, 0, 0, 0], config.port));
 
-    tracing::info!("Listening on {}", &addr);
+    let server = axum::Server::try_bind(&addr).unwrap_or_else(|err| unable_to_listen(&addr, err));
 
-    axum::Server::bind(&addr)
-        .serve(app.into_make_service())
-        .await
-        .unwrap_or_else(|_| panic!("Unable to start server on {}", &addr))
+    info!("Listening on {}", &addr);
+
+    let mut server = tokio::spawn(
+        server
+            // The peer address is what clients are rate limited by, unless
+            // RATE_LIMIT_CLIENT_IP_HEADER names the header of a proxy.
+            .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+            .with_graceful_shutdown(stopped(shutdown.clone())),
+    );
+
+    tokio::select! {
+        result = &mut server => {
+            panic!("Server on {} stopped unexpectedly: {:?}", &addr, result)
+        }
+        _ = shutdown_signal() => {}
+    }
+
+    // Failing readiness first keeps new traffic away while the rest drains,
+    // the server still accepts until load balancers have noticed.
+    info!("Shutting down");
+    let deadline = Instant::now() + Duration::from_secs(config.shutdown_timeout_seconds);
+    readiness.drain();
+    tokio::time::sleep(Duration::from_secs(config.shutdown_drain_delay_seconds)).await;
+    shutdown.trigger();
+
+    match tokio::time::timeout_at(deadline, server).await {
+        Ok(Ok(Ok(()))) => info!("Drained all connections"),
+        Ok(result) => error!("Server failed while draining: {:?}", result),
+        Err(_) => warn!(
+            "Requests still in flight after {}s, dropping them",
+            config.shutdown_timeout_seconds
+        ),
+    }
+
+    shutdown
+        .wait_for_tasks(deadline.saturating_duration_since(Instant::now()))
+        .await;
+
+    if let Err(err) = database_driver.close().await {
+        warn!("Unable to close the database connection: {}", err);
+    }
+
+    SystemTelemetry::shutdown().await;
+}
+
+/// Exits with the reason the server cannot start, without a backtrace.
+fn unable_to_start(reason: impl Display) -> ! {
+    eprintln!("{}", reason);
+    process::exit(1)
+}
+
+/// Exits when a port cannot be listened on, for example because it is taken.
+fn unable_to_listen(addr: &SocketAddr, err: impl Display) -> ! {
+    error!("Unable to listen on {}: {}", addr, err);
+    process::exit(1)
+}
+
+/// Resolves once the shutdown is triggered, for a server to stop accepting.
+async fn stopped(shutdown: Shutdown) {
+    shutdown.triggered().await
+}
+
+/// Resolves on SIGINT, or SIGTERM as sent by fly.io and most orchestrators.
+async fn shutdown_signal() {
+    let interrupt = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Unable to listen for SIGINT")
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Unable to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = interrupt => {}
+        _ = terminate => {}
+    }
 }